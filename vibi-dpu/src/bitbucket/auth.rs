@@ -1,11 +1,23 @@
 use std::env;
 use std::str;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use tokio::sync::Mutex;
+
 use crate::db::bitbucket::auth::{save_bitbucket_auth_info_to_db, bitbucket_auth_info};
 use crate::utils::gitops::set_git_remote_url;
-use crate::utils::reqwest_client::get_client;
+use crate::utils::reqwest_client::{get_client, send_request};
 use crate::utils::bitbucket_auth_info::BitbucketAuthInfo;
 use crate::utils::review::Review;
+use crate::health::status::{clear_auth_failure, record_auth_failure, send_status_failed};
+
+const DEFAULT_RENEWAL_MARGIN_SECS: u64 = 300;
+const REFRESH_ATTEMPTS: u32 = 3;
+const REFRESH_BACKOFF_SECS: u64 = 2;
+
+// Held while a refresh is in flight so concurrent reviews don't each spend the refresh token
+static REFRESH_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
 
 pub async fn get_access_token_from_bitbucket(code: &str) -> Option<BitbucketAuthInfo> {
     let client = get_client();
@@ -20,11 +32,9 @@ pub async fn get_access_token_from_bitbucket(code: &str) -> Option<BitbucketAuth
     params.insert("grant_type", "authorization_code".to_owned());
     params.insert("redirect_uri", redirect_uri);
     log::debug!("[get_access_token_from_bitbucket] params = {:?}", &params);
-    let post_res = client
+    let post_res = send_request(client
         .post("https://bitbucket.org/site/oauth2/access_token")
-        .form(&params)
-        .send()
-        .await;
+        .form(&params)).await;
     if post_res.is_err() {
         let e = post_res.expect_err("No error in post_res");
         log::error!("[get_access_token_from_bitbucket] error in calling api : {:?}", e);
@@ -68,25 +78,30 @@ pub async fn refresh_git_auth(review: &Option<Review>) -> Option<String>{
 
 pub async fn update_access_token(auth_info: &BitbucketAuthInfo, review_opt: &Option<Review>) -> Option<BitbucketAuthInfo> {
     let repo_provider = "bitbucket".to_string();
-    let now = SystemTime::now();
-    let now_secs = now.duration_since(UNIX_EPOCH).expect("Time went backwards").as_secs();
-    let timestamp_opt = auth_info.timestamp();
-    if timestamp_opt.is_none() {
-        log::error!("[update_access_token] No timestamp in BitbucketAuthInfo");
-        return None;
-    }
-    let timestamp = timestamp_opt.expect("Empty timestamp");
-    let expires_at = timestamp + auth_info.expires_in();
-    if expires_at > now_secs {  
-        log::error!("[update_access_token] Not yet expired, expires_at = {}, now_secs = {}", expires_at, now_secs);
+    if !needs_renewal(auth_info) {
         return Some(auth_info.to_owned());
     }
-    // auth info has expired
-    log::info!("Updating expired authentication...");
-    log::debug!("[update_access_token] auth info expired, expires_at = {}, now_secs = {}", expires_at, now_secs);
-    let new_auth_info_opt = bitbucket_refresh_token(auth_info.refresh_token()).await;
-    let mut new_auth_info = new_auth_info_opt.clone()
-        .expect("empty auhtinfo_opt from update_access_token");
+    // only one task refreshes, the others wait and pick up its token
+    let _refresh_guard = REFRESH_LOCK.lock().await;
+    let latest_auth_info = bitbucket_auth_info().unwrap_or(auth_info.to_owned());
+    if !needs_renewal(&latest_auth_info) {
+        log::debug!("[update_access_token] Token already renewed by another task");
+        return Some(latest_auth_info);
+    }
+    log::info!("Renewing authentication...");
+    let new_auth_info_opt = refresh_with_retries(latest_auth_info.refresh_token()).await;
+    if new_auth_info_opt.is_none() {
+        log::error!("[update_access_token] Unable to renew bitbucket access token");
+        record_auth_failure(&repo_provider, "Bitbucket token refresh failed");
+        send_status_failed().await;
+        // an early renewal can fail without taking down calls, the old token still works
+        if expires_at(&latest_auth_info).is_some_and(|expires_at| expires_at > now_secs()) {
+            return Some(latest_auth_info);
+        }
+        return None;
+    }
+    clear_auth_failure(&repo_provider);
+    let mut new_auth_info = new_auth_info_opt.expect("Empty new_auth_info_opt");
     log::debug!("[update_access_token] New auth info  = {:?}", &new_auth_info);
     let access_token = new_auth_info.access_token().to_string();
     if review_opt.is_some() {
@@ -94,7 +109,61 @@ pub async fn update_access_token(auth_info: &BitbucketAuthInfo, review_opt: &Opt
         set_git_remote_url(&review, &access_token, &repo_provider);
     }
     save_bitbucket_auth_info_to_db(&mut new_auth_info);
-    return new_auth_info_opt;
+    Some(new_auth_info)
+}
+
+fn needs_renewal(auth_info: &BitbucketAuthInfo) -> bool {
+    renewal_due(expires_at(auth_info), now_secs(), renewal_margin_secs())
+}
+
+// True once the token is within the renewal margin of its expiry
+fn renewal_due(expires_at_opt: Option<u64>, now_secs: u64, margin_secs: u64) -> bool {
+    if expires_at_opt.is_none() {
+        log::error!("[renewal_due] No timestamp in BitbucketAuthInfo");
+        return true;
+    }
+    let expires_at = expires_at_opt.expect("Empty expires_at_opt");
+    let renew_at = expires_at.saturating_sub(margin_secs);
+    log::debug!("[renewal_due] expires_at = {}, renew_at = {}, now_secs = {}", expires_at, renew_at, now_secs);
+    renew_at <= now_secs
+}
+
+fn expires_at(auth_info: &BitbucketAuthInfo) -> Option<u64> {
+    auth_info.timestamp().map(|timestamp| timestamp + auth_info.expires_in())
+}
+
+fn renewal_margin_secs() -> u64 {
+    margin_secs(env::var("BITBUCKET_TOKEN_RENEWAL_MARGIN_SECS").ok())
+}
+
+fn margin_secs(margin_opt: Option<String>) -> u64 {
+    margin_opt
+        .and_then(|margin| margin.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_RENEWAL_MARGIN_SECS)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards").as_secs()
+}
+
+async fn refresh_with_retries(refresh_token: &str) -> Option<BitbucketAuthInfo> {
+    for attempt in 0..REFRESH_ATTEMPTS {
+        if attempt > 0 {
+            let backoff = refresh_backoff(attempt);
+            log::warn!("[refresh_with_retries] Retrying token refresh in {:?}, attempt {}", backoff, attempt + 1);
+            tokio::time::sleep(backoff).await;
+        }
+        let new_auth_info_opt = bitbucket_refresh_token(refresh_token).await;
+        if new_auth_info_opt.is_some() {
+            return new_auth_info_opt;
+        }
+    }
+    None
+}
+
+// Doubles after every failed attempt, starting at REFRESH_BACKOFF_SECS
+fn refresh_backoff(attempt: u32) -> Duration {
+    Duration::from_secs(REFRESH_BACKOFF_SECS << (attempt - 1))
 }
 
 async fn bitbucket_refresh_token(refresh_token: &str) -> Option<BitbucketAuthInfo> {
@@ -111,12 +180,10 @@ async fn bitbucket_refresh_token(refresh_token: &str) -> Option<BitbucketAuthInf
         ("refresh_token", refresh_token)
     ];
     let client = get_client();
-    let post_res = client.post(token_url)
+    let post_res = send_request(client.post(token_url)
         .headers(headers)
         .basic_auth(client_id, Some(client_secret))
-        .form(payload)
-        .send()
-        .await;
+        .form(payload)).await;
     if post_res.is_err() {
         let e = post_res.expect_err("No error in post_err for refres token");
         log::error!("[update_access_token] Unable to get refresh token: {}", e);
@@ -136,4 +203,43 @@ async fn bitbucket_refresh_token(refresh_token: &str) -> Option<BitbucketAuthInf
     }
     let refresh_token_resbody = parse_res.expect("Uncaught error in parse_res");
     return Some(refresh_token_resbody);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn renewal_is_due_within_the_margin() {
+        assert!(!renewal_due(Some(1000), 699, 300));
+        assert!(renewal_due(Some(1000), 700, 300));
+        assert!(renewal_due(Some(1000), 1200, 300));
+        assert!(renewal_due(Some(100), 0, 300));
+        assert!(renewal_due(None, 0, 300));
+    }
+
+    #[test]
+    fn expiry_needs_a_timestamp() {
+        let mut auth_info: BitbucketAuthInfo = serde_json::from_value(json!({
+            "access_token": "access", "refresh_token": "refresh", "expires_in": 7200, "timestamp": null,
+        })).expect("Unable to deserialize auth info");
+        assert_eq!(expires_at(&auth_info), None);
+        auth_info.set_timestamp(1000);
+        assert_eq!(expires_at(&auth_info), Some(8200));
+    }
+
+    #[test]
+    fn margin_falls_back_to_default() {
+        assert_eq!(margin_secs(Some(" 60 ".to_string())), 60);
+        assert_eq!(margin_secs(Some("soon".to_string())), DEFAULT_RENEWAL_MARGIN_SECS);
+        assert_eq!(margin_secs(None), DEFAULT_RENEWAL_MARGIN_SECS);
+    }
+
+    #[test]
+    fn backoff_doubles_between_attempts() {
+        assert_eq!(refresh_backoff(1), Duration::from_secs(2));
+        assert_eq!(refresh_backoff(2), Duration::from_secs(4));
+    }
 }
\ No newline at end of file
@@ -2,20 +2,37 @@ use std::env;
 
 use reqwest::{Response, header::{HeaderMap, HeaderValue}};
 use serde_json::Value;
-use crate::utils::reqwest_client::get_client;
+use crate::utils::reqwest_client::{get_client, send_request};
 
 pub fn bitbucket_base_url() -> String {
     env::var("BITBUCKET_BASE_URL").expect("BITBUCKET_BASE_URL must be set")
 }
 
-pub async fn get_api_values(url: &str, access_token: &str ) -> Vec<Value> {
+// Web host for links shown to users, e.g. https://bitbucket.org for https://api.bitbucket.org/2.0
+pub fn bitbucket_web_url() -> String {
+    let base_url = bitbucket_base_url();
+    base_url.trim_end_matches('/').trim_end_matches("/2.0").replace("://api.", "://")
+}
+
+// Values of all pages, None if any page fails so callers never work on a partial list
+pub async fn get_api_values(url: &str, access_token: &str ) -> Option<Vec<Value>> {
     let response_opt = get_api_response(url, None, access_token).await;
-    let (mut response_values, next_url) = deserialize_paginated_response(response_opt).await;
+    let page_opt = deserialize_paginated_response(response_opt).await;
+    if page_opt.is_none() {
+        log::error!("[get_api_values] Unable to get first page of {}", url);
+        return None;
+    }
+    let (mut response_values, next_url) = page_opt.expect("Empty page_opt");
     if next_url.is_some() {
-        let mut page_values = get_all_pages(next_url, access_token).await;
+        let page_values_opt = get_all_pages(next_url, access_token).await;
+        if page_values_opt.is_none() {
+            log::error!("[get_api_values] Unable to get all pages of {}", url);
+            return None;
+        }
+        let mut page_values = page_values_opt.expect("Empty page_values_opt");
         response_values.append(&mut page_values);
     }
-    return response_values;
+    Some(response_values)
 }
 
 pub async fn get_api_response(url: &str, headers_opt: Option<reqwest::header::HeaderMap>, access_token: &str) -> Option<Response>{
@@ -31,7 +48,7 @@ pub async fn get_api_response(url: &str, headers_opt: Option<reqwest::header::He
         headers = headers_opt.expect("Empty headers_opt");
     }
     let client = get_client();
-    let get_res = client.get(url).headers(headers).send().await;
+    let get_res = send_request(client.get(url).headers(headers)).await;
     if get_res.is_err() {
         let e = get_res.expect_err("No error in get_res");
         log::error!("[get_api_response] Error sending GET request without params to {}, error: {}", url, e);
@@ -45,34 +62,34 @@ pub async fn get_api_response(url: &str, headers_opt: Option<reqwest::header::He
     return Some(response);
 }
 
-async fn deserialize_paginated_response(response_opt: Option<Response>) -> (Vec<Value>, Option<String>) {
+async fn deserialize_paginated_response(response_opt: Option<Response>) -> Option<(Vec<Value>, Option<String>)> {
     let mut values_vec = Vec::new();
     if response_opt.is_none() {
         log::error!("[deserialize_paginated_response] Response is None, can't deserialize");
-        return (values_vec, None);
+        return None;
     }
     let response = response_opt.expect("Uncaught empty response_opt");
     let parse_res = response.json::<serde_json::Value>().await;
     if parse_res.is_err() {
         let e = parse_res.expect_err("No error in parse_res");
         log::error!("[deserialize_paginated_response] Unable to deserialize response: {}", e);
-        return (values_vec, None);
+        return None;
     }
     let response_json = parse_res
         .expect("Uncaught error in parse_res in deserialize_response");
     let res_values_opt = response_json["values"].as_array();
     if res_values_opt.is_none() {
         log::error!("[deserialize_paginated_response] response_json[values] is empty");
-        return (values_vec, None);
+        return None;
     }
     let values = res_values_opt.expect("res_values_opt is empty");
     for value in values {
         values_vec.push(value.to_owned()); 
     }
-    return (values_vec, Some(response_json["next"].to_string()));
+    Some((values_vec, Some(response_json["next"].to_string())))
 }
 
-async fn get_all_pages(next_url: Option<String>, access_token: &str) -> Vec<Value>{
+async fn get_all_pages(next_url: Option<String>, access_token: &str) -> Option<Vec<Value>> {
     let mut values_vec = Vec::new();
     let mut next_url_mut = next_url;
     while next_url_mut.is_some() {
@@ -86,11 +103,16 @@ async fn get_all_pages(next_url: Option<String>, access_token: &str) -> Vec<Valu
             break;   
         }
         let response_opt = get_api_response(url, None, access_token).await;
-        let (mut response_values, url_opt) = deserialize_paginated_response(response_opt).await;
+        let page_opt = deserialize_paginated_response(response_opt).await;
+        if page_opt.is_none() {
+            log::error!("[get_all_pages] Unable to get page {}", url);
+            return None;
+        }
+        let (mut response_values, url_opt) = page_opt.expect("Empty page_opt");
         next_url_mut = url_opt.clone();
         values_vec.append(&mut response_values);
     }
-    return values_vec;
+    Some(values_vec)
 }
 
 pub fn prepare_auth_headers(access_token: &str) -> Option<HeaderMap>{
@@ -6,4 +6,5 @@ pub mod webhook;
 pub mod user;
 pub mod comment;
 pub mod reviewer;
-pub mod prs;
\ No newline at end of file
+pub mod prs;
+pub mod status;
\ No newline at end of file
@@ -1,81 +1,27 @@
 use crate::db::prs::update_pr_info_in_db;
-use crate::utils::{pr_info::PrInfo, reqwest_client::get_client};
-use reqwest::header::HeaderMap;
+use crate::utils::{pr_info::{PrInfo, PrSummary}, reqwest_client::{get_client, send_request}};
 use serde_json::Value;
-use std::collections::HashMap;
 use std::str;
 
-use super::config::{bitbucket_base_url, prepare_auth_headers};
+use super::config::{bitbucket_base_url, get_api_values, prepare_auth_headers};
 
-pub async fn list_prs_bitbucket(repo_owner: &str,repo_name: &str,access_token: &str,state: &str,) -> Option<Vec<String>> {
-    let headers_opt = prepare_auth_headers(access_token);
-    if headers_opt.is_none() {
-        log::error!("[list_prs_bitbucket] Unable to prepare auth headers: {}", repo_name);
-        return None;
-    }
-    let headers = headers_opt.expect("Empty headers_opt");
-    let mut params = HashMap::new();
-    params.insert("state".to_string(), state.to_string());
-    let pr_list_opt = get_list_prs(&headers, &params, repo_owner, repo_name).await;
-    return pr_list_opt;
-}
-
-async fn get_list_prs(headers: &HeaderMap, params: &HashMap<String, String>, repo_owner: &str, repo_name: &str) -> Option<Vec<String>> {
-    let client = get_client();
-    let base_url = bitbucket_base_url();
-    let response_result = client
-        .get(&format!(
-            "{}/repositories/{}/{}/pullrequests",
-            &base_url, repo_owner, repo_name
-        ))
-        .headers(headers.to_owned())
-        .query(params)
-        .send()
-        .await;
-    if response_result.is_err() {
-        let e = response_result.expect_err("No error in sending request");
-        log::error!("[get_list_prs] Failed to send the request {:?}", e);
-        return None;
-    }
-
-    let response = response_result.expect("Uncaught error in parsing response");
-    if !response.status().is_success() {
-        log::error!(
-            "[get_list_prs] Request failed with status: {:?}",
-            response.status()
-        );
-        return None;
-    }
-
-    let parse_result = response.json::<Value>().await;
-    if parse_result.is_err() {
-        let parse_result_err = parse_result.expect_err("No error in parsing");
-        log::error!(
-            "[get_list_prs] Failed to parse JSON: {:?}",
-            parse_result_err
-        );
-        return None;
-    }
-    let prs_data = parse_result.expect("Uncaught error in parsing Prs data");
-    let pr_list_parse_res = serde_json::from_value(prs_data["values"].clone());
-    if pr_list_parse_res.is_err() {
-        let e = pr_list_parse_res.expect_err("Empty error in pr_list_parse_res");
-        log::error!("[get_list_prs] Unable to parse get_list_prs: {:?}", e);
-        return None;
-    }
-    let pr_list_parsed: Vec<Value> =
-        pr_list_parse_res.expect("Uncaught error in pr_list_parse_res");
-    let mut pr_list: Vec<String> = Vec::new();
-    for pr in pr_list_parsed.iter() {
-        pr_list.push(pr["id"].to_string().trim_matches('"').to_string());
-    }
-    if pr_list.is_empty() {
-        log::error!(
-            "[get_list_prs] pr_list is empty for parsed value: {:?}",
-            &pr_list_parsed
-        );
+pub async fn list_prs_bitbucket(repo_owner: &str, repo_name: &str, access_token: &str, state: &str) -> Option<Vec<PrSummary>> {
+    let url = format!("{}/repositories/{}/{}/pullrequests?state={}&pagelen=50",
+        bitbucket_base_url(), repo_owner, repo_name, state);
+    let prs_opt = get_api_values(&url, access_token).await;
+    if prs_opt.is_none() {
+        log::error!("[list_prs_bitbucket] Unable to get pull requests for {}/{}", repo_owner, repo_name);
         return None;
     }
+    let prs = prs_opt.expect("Empty prs_opt");
+    let pr_list = prs.iter().map(|pr| PrSummary {
+        number: pr["id"].to_string(),
+        title: pr["title"].as_str().unwrap_or_default().to_string(),
+        draft: pr["draft"].as_bool().unwrap_or(false),
+        base_ref: pr["destination"]["branch"]["name"].as_str().unwrap_or_default().to_string(),
+        head_ref: pr["source"]["branch"]["name"].as_str().unwrap_or_default().to_string(),
+        author: pr["author"]["uuid"].as_str().map(str::to_string),
+    }).collect();
     return Some(pr_list);
 }
 
@@ -88,12 +34,10 @@ pub async fn get_pr_info(workspace_slug: &str,repo_slug: &str,access_token: &str
     log::debug!("[get_pr_info] url: {:?}", &url);
     log::debug!("[get_pr_info] access token: {:?}", access_token);
     let client = get_client();
-    let response_result = client
+    let response_result = send_request(client
         .get(&url)
         .header("Authorization", format!("Bearer {}", access_token))
-        .header("Accept", "application/json")
-        .send()
-        .await;
+        .header("Accept", "application/json")).await;
 
     if response_result.is_err() {
         let res_err = response_result.expect_err("No error in getting Pr response");
@@ -129,3 +73,83 @@ pub async fn get_and_store_pr_info(workspace_slug: &str, repo_slug: &str, access
         );
     }
 }
+
+// Open PRs in the repo where the user is a reviewer, Bitbucket has no cross-repo inbox API
+pub async fn open_review_requests_count(workspace_slug: &str, repo_slug: &str, user_uuid: &str, access_token: &str) -> Option<u64> {
+    let headers_opt = prepare_auth_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[open_review_requests_count] Unable to prepare auth headers: {}", repo_slug);
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let query = format!("reviewers.uuid=\"{}\" AND state=\"OPEN\"", user_uuid);
+    let client = get_client();
+    let response_res = send_request(client
+        .get(format!("{}/repositories/{}/{}/pullrequests", bitbucket_base_url(), workspace_slug, repo_slug))
+        .headers(headers)
+        .query(&[("q", query.as_str()), ("pagelen", "1")])).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[open_review_requests_count] Failed to send the request: {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[open_review_requests_count] Request failed with status: {:?}", response.status());
+        return None;
+    }
+    let parse_res = response.json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[open_review_requests_count] Failed to parse JSON: {:?}", e);
+        return None;
+    }
+    let response_json = parse_res.expect("Uncaught error in parse_res");
+    response_json["size"].as_u64()
+}
+
+// Display name, nickname and uuid of everyone who approved the PR
+pub async fn pr_approvers(workspace_slug: &str, repo_slug: &str, pr_number: &str, access_token: &str) -> Option<Vec<Vec<String>>> {
+    let headers_opt = prepare_auth_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[pr_approvers] Unable to prepare auth headers: {}", repo_slug);
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let client = get_client();
+    let response_res = send_request(client
+        .get(format!("{}/repositories/{}/{}/pullrequests/{}", bitbucket_base_url(), workspace_slug, repo_slug, pr_number))
+        .headers(headers)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[pr_approvers] Failed to send the request: {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[pr_approvers] Request failed with status: {:?}", response.status());
+        return None;
+    }
+    let parse_res = response.json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[pr_approvers] Failed to parse JSON: {:?}", e);
+        return None;
+    }
+    let pr_data = parse_res.expect("Uncaught error in parse_res");
+    let mut approvers = Vec::<Vec<String>>::new();
+    let participants = pr_data["participants"].as_array().cloned().unwrap_or_default();
+    for participant in participants {
+        if !participant["approved"].as_bool().unwrap_or(false) {
+            continue;
+        }
+        let handles: Vec<String> = ["display_name", "nickname", "uuid"].iter()
+            .filter_map(|key| participant["user"][*key].as_str())
+            .map(|handle| handle.to_string())
+            .collect();
+        if !handles.is_empty() {
+            approvers.push(handles);
+        }
+    }
+    Some(approvers)
+}
@@ -5,7 +5,12 @@ use super::config::{bitbucket_base_url, get_api_values};
 
 pub async fn get_workspace_repos(workspace: &str, access_token: &str) -> Option<Vec<Repository>> {
     let repos_url = format!("{}/repositories/{}", bitbucket_base_url(), workspace);
-    let response_json = get_api_values(&repos_url, access_token).await;
+    let response_json_opt = get_api_values(&repos_url, access_token).await;
+    if response_json_opt.is_none() {
+        log::error!("[get_workspace_repos] Unable to get repos of workspace {}", workspace);
+        return None;
+    }
+    let response_json = response_json_opt.expect("Empty response_json_opt");
     let mut repos_data = Vec::new();
     for repo_json in response_json {
         let is_private_res = repo_json["is_private"].as_bool();
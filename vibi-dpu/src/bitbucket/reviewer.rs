@@ -3,7 +3,7 @@ use serde_json::Value;
 
 use crate::utils::review::Review;
 use crate::utils::user::BitbucketUser;
-use crate::utils::reqwest_client::get_client;
+use crate::utils::reqwest_client::{get_client, send_request};
 
 use super::config::prepare_headers;
 
@@ -97,13 +97,12 @@ async fn put_reviewers(url: &str, access_token: &str, put_body_opt: &Option<Valu
     let put_body = put_body_opt.to_owned().expect("Empty put_body_opt");
     // Make the PUT API call
     let client = get_client();
-    let response_res = client
+    let response_res = send_request(client
         .put(url)
         .bearer_auth(&access_token)
         .header("Accept", "application/json")
         .header("Content-Type", "application/json")
-        .json(&put_body)
-        .send().await;
+        .json(&put_body)).await;
 
     // Handle the response_res as necessary
     log::debug!("[put_reviewers] response_res = {:?}", &response_res);
@@ -122,7 +121,7 @@ async fn get_pr_info(url: &str, access_token: &str) -> Option<Response> {
         return None;
     }
     let headers = headers_opt.expect("Empty headers_opt");
-    let get_res = client.get(url).headers(headers).send().await;
+    let get_res = send_request(client.get(url).headers(headers)).await;
     if get_res.is_err() {
         let e = get_res.expect_err("No error in response_res");
         log::error!("[get_pr_info] Error in get request for adding reviewer - {:?}", e);
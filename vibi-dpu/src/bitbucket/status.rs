@@ -0,0 +1,40 @@
+use serde_json::json;
+
+use crate::utils::reqwest_client::{get_client, send_request};
+
+use super::config::{bitbucket_base_url, prepare_headers};
+
+const COVERAGE_STATUS_KEY: &str = "vibinex-relevant-approvals";
+
+// Bitbucket requires a url on every build status
+pub async fn set_build_status(workspace_slug: &str, repo_slug: &str, commit: &str,
+        state: &str, description: &str, url: &str, access_token: &str) {
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[set_build_status] Unable to prepare auth headers for repository: {}", repo_slug);
+        return;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let status_url = format!("{}/repositories/{}/{}/commit/{}/statuses/build",
+        bitbucket_base_url(), workspace_slug, repo_slug, commit);
+    let body = json!({
+        "key": COVERAGE_STATUS_KEY,
+        "name": "Relevant approvals",
+        "state": state,
+        "description": description,
+        "url": url,
+    });
+    let client = get_client();
+    let response_res = send_request(client.post(&status_url).headers(headers).json(&body)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[set_build_status] Failed to send the request: {:?}", e);
+        return;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[set_build_status] Unable to set status on {}, status: {}", commit, response.status());
+        return;
+    }
+    log::debug!("[set_build_status] Status {} set on commit {}", state, commit);
+}
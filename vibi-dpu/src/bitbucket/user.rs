@@ -1,12 +1,23 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use crate::db::bitbucket::auth::bitbucket_auth_info;
 use crate::db::user::add_bitbucket_user_to_workspace_user;
 use crate::utils::user::BitbucketUser;
-use super::config::{bitbucket_base_url, get_api_values, get_api_response};
+use crate::utils::reqwest_client::{get_client, send_request};
+use super::config::{bitbucket_base_url, get_api_values, get_api_response, prepare_headers};
+
+// Set once the 1.0 groups API answers 410 Gone, so it isn't called for every review
+static GROUPS_API_GONE: AtomicBool = AtomicBool::new(false);
 
 pub async fn get_and_save_workspace_users(workspace_id: &str, access_token: &str) {
     let base_url = bitbucket_base_url();
     let members_url = format!("{}/workspaces/{}/members", &base_url, workspace_id);
-    let response_json = get_api_values(&members_url, access_token).await;
+    let response_json_opt = get_api_values(&members_url, access_token).await;
+    if response_json_opt.is_none() {
+        log::error!("[get_and_save_workspace_users] Unable to get members of workspace {}", workspace_id);
+        return;
+    }
+    let response_json = response_json_opt.expect("Empty response_json_opt");
     for user_json in response_json {
         let user_val = user_json.get("user").expect("Empty reviewers_opt");
         let user: BitbucketUser = serde_json::from_value(user_val.to_owned())
@@ -15,6 +26,28 @@ pub async fn get_and_save_workspace_users(workspace_id: &str, access_token: &str
     }
 }
 
+pub async fn workspace_member_by_nickname(workspace_id: &str, nickname: &str, access_token: &str) -> Option<BitbucketUser> {
+    let members_url = format!("{}/workspaces/{}/members", bitbucket_base_url(), workspace_id);
+    let members_opt = get_api_values(&members_url, access_token).await;
+    if members_opt.is_none() {
+        log::error!("[workspace_member_by_nickname] Unable to get members of workspace {}", workspace_id);
+        return None;
+    }
+    for member_json in members_opt.expect("Empty members_opt") {
+        let user_res = serde_json::from_value::<BitbucketUser>(member_json["user"].to_owned());
+        if user_res.is_err() {
+            let e = user_res.expect_err("No error in user_res");
+            log::error!("[workspace_member_by_nickname] Unable to deserialize member: {:?}", e);
+            continue;
+        }
+        let user = user_res.expect("Uncaught error in user_res");
+        if user.nickname().eq_ignore_ascii_case(nickname) {
+            return Some(user);
+        }
+    }
+    None
+}
+
 pub async fn author_from_commit(commit: &str, repo_name: &str, repo_owner: &str) -> Option<BitbucketUser>{
     let base_url = bitbucket_base_url();
     let commits_url = format!("{}/repositories/{}/{}/commit/{}", &base_url, repo_owner, repo_name, commit);
@@ -46,4 +79,89 @@ pub async fn author_from_commit(commit: &str, repo_name: &str, repo_owner: &str)
     }
     let author: BitbucketUser = author_res.expect("Uncaught error in author_res");
     return Some(author);
+}
+
+// None when Bitbucket can't tell us, so callers can decide to keep the user
+pub async fn is_workspace_member(workspace: &str, user_uuid: &str, access_token: &str) -> Option<bool> {
+    let url = format!("{}/workspaces/{}/members/{}", bitbucket_base_url(), workspace, user_uuid);
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[is_workspace_member] Unable to prepare headers for {}", user_uuid);
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let client = get_client();
+    let response_res = send_request(client.get(&url).headers(headers)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[is_workspace_member] Unable to check membership of {}, error: {:?}", user_uuid, e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    let status = response.status();
+    if status.is_success() {
+        return Some(true);
+    }
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Some(false);
+    }
+    log::error!("[is_workspace_member] Unexpected status {} for {}", status, &url);
+    None
+}
+
+// Groups are only exposed through the deprecated 1.0 API, once it's gone groups can't be expanded anymore
+pub async fn get_group_members(workspace: &str, group_slug: &str, access_token: &str) -> Option<Vec<BitbucketUser>> {
+    if GROUPS_API_GONE.load(Ordering::Relaxed) {
+        log::warn!("[get_group_members] Bitbucket 1.0 groups API is unavailable, not expanding group {}/{}", workspace, group_slug);
+        return None;
+    }
+    let base_url = bitbucket_base_url().replace("/2.0", "/1.0");
+    let members_url = format!("{}/groups/{}/{}/members", &base_url, workspace, group_slug);
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[get_group_members] Unable to prepare headers for group {}/{}", workspace, group_slug);
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let client = get_client();
+    let response_res = send_request(client.get(&members_url).headers(headers)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[get_group_members] Unable to get members of group {}/{}, error: {:?}", workspace, group_slug, e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    let status = response.status();
+    if status == reqwest::StatusCode::GONE {
+        GROUPS_API_GONE.store(true, Ordering::Relaxed);
+        log::warn!("[get_group_members] Bitbucket 1.0 groups API has been removed, groups are skipped from now on");
+        return None;
+    }
+    if !status.is_success() {
+        log::error!("[get_group_members] Unable to get members of group {}/{}, status: {}", workspace, group_slug, status);
+        return None;
+    }
+    let parse_res = response.json::<serde_json::Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[get_group_members] Error in deserializing json: {:?}", e);
+        return None;
+    }
+    let members_json = parse_res.expect("Uncaught error in parse_res");
+    let members_opt = members_json.as_array();
+    if members_opt.is_none() {
+        log::error!("[get_group_members] Unexpected members response: {:?}", &members_json);
+        return None;
+    }
+    let mut members = Vec::<BitbucketUser>::new();
+    for member_val in members_opt.expect("Empty members_opt") {
+        let member_res = serde_json::from_value::<BitbucketUser>(member_val.to_owned());
+        if member_res.is_err() {
+            let e = member_res.expect_err("No error in member_res");
+            log::error!("[get_group_members] Unable to deserialize group member: {:?}", e);
+            continue;
+        }
+        members.push(member_res.expect("Uncaught error in member_res"));
+    }
+    Some(members)
 }
\ No newline at end of file
@@ -1,35 +1,42 @@
-use std::env;
-
 use reqwest::{header::HeaderValue, Response, Error};
-use serde_json::json;
+use serde_json::{json, Value};
 
-use crate::{db::webhook::save_webhook_to_db, utils::bitbucket_webhook::{Webhook, WebhookResponse}, bitbucket::config::{bitbucket_base_url, get_api_values}};
-use crate::utils::reqwest_client::get_client;
+use crate::{core::webhooks::DesiredHook, db::webhook::{delete_webhook_from_db, save_webhook_to_db}, utils::bitbucket_webhook::{Webhook, WebhookResponse}, bitbucket::config::{bitbucket_base_url, get_api_values}};
+use crate::utils::reqwest_client::{get_client, send_request};
+use crate::utils::user::ProviderEnum;
 use super::config::prepare_auth_headers;
 
+pub const WEBHOOK_CALLBACK_PATH: &str = "/api/bitbucket/callbacks/webhook";
+pub const WEBHOOK_EVENTS: [&str; 4] = ["pullrequest:created", "pullrequest:updated", "pullrequest:approved", "repo:push"];
 
-pub async fn get_webhooks_in_repo(workspace_slug: &str, repo_slug: &str, access_token: &str) -> Vec<Webhook> {
+pub async fn get_webhooks_in_repo(workspace_slug: &str, repo_slug: &str, access_token: &str) -> Option<Vec<Webhook>> {
 	let url = format!("{}/repositories/{}/{}/hooks", bitbucket_base_url(), workspace_slug, repo_slug);
 	log::debug!("[get_webhooks_in_repo] Getting webhooks from {}", url);
-	let response_json = get_api_values(&url, access_token).await;
+	let response_json_opt = get_api_values(&url, access_token).await;
+	if response_json_opt.is_none() {
+		log::error!("[get_webhooks_in_repo] Unable to get webhooks of {}/{}", workspace_slug, repo_slug);
+		return None;
+	}
+	let response_json = response_json_opt.expect("Empty response_json_opt");
 	let mut webhooks = Vec::new();
 	for webhook_json in response_json {
-		let active = matches!(webhook_json["active"].to_string().trim_matches('"'), "true" | "false");
+		let active = webhook_json["active"].as_bool().unwrap_or(false);
 		let webhook = Webhook::new(
-			webhook_json["uuid"].to_string(),
+			webhook_json["uuid"].to_string().trim_matches('"').to_string(),
 			active,
 			webhook_json["created_at"].to_string().replace('"', ""),
 			webhook_json["events"].as_array().expect("Unable to deserialize events").into_iter()
 				.map(|events| events.as_str().expect("Unable to convert event").to_string()).collect(),
 			webhook_json["links"]["self"]["href"].to_string().replace('"', ""),
 			webhook_json["url"].to_string().replace('"', ""),
+			webhook_json["secret_set"].as_bool().unwrap_or(false),
 		);
 		webhooks.push(webhook);
 	}
-	return webhooks;
+	Some(webhooks)
 }
 
-pub async fn add_webhook(workspace_slug: &str, repo_slug: &str, access_token: &str) {
+pub async fn add_webhook(workspace_slug: &str, repo_slug: &str, access_token: &str, desired: &DesiredHook) {
 	let url = format!(
 		"{}/repositories/{}/{}/hooks", 
 		bitbucket_base_url(), workspace_slug, repo_slug
@@ -41,24 +48,78 @@ pub async fn add_webhook(workspace_slug: &str, repo_slug: &str, access_token: &s
 	}
 	let mut headers_map = headers_map_opt.expect("Empty headers_map_opt");
 	headers_map.insert("Accept", HeaderValue::from_static("application/vnd.github+json"));
-	let callback_url = format!("{}/api/bitbucket/callbacks/webhook", 
-		env::var("SERVER_URL").expect("SERVER_URL must be set"));
-	let payload = json!({
+	let response = send_request(get_client()
+		.post(&url)
+		.headers(headers_map)
+		.json(&webhook_payload(desired))).await;
+	process_add_webhook_response(workspace_slug, repo_slug, response).await;
+}
+
+pub async fn update_webhook(workspace_slug: &str, repo_slug: &str, hook_uuid: &str, access_token: &str, desired: &DesiredHook) {
+	let url = format!("{}/repositories/{}/{}/hooks/{}",
+		bitbucket_base_url(), workspace_slug, repo_slug, hook_uuid);
+	let headers_map_opt = prepare_auth_headers(access_token);
+	if headers_map_opt.is_none() {
+		return;
+	}
+	let headers_map = headers_map_opt.expect("Empty headers_map_opt");
+	let response = send_request(get_client()
+		.put(&url)
+		.headers(headers_map)
+		.json(&webhook_payload(desired))).await;
+	if response.is_err() {
+		let err = response.expect_err("No error in response");
+		log::error!("[update_webhook] Error in api call: {:?}", err);
+		return;
+	}
+	let res = response.expect("Uncaught error in response");
+	if !res.status().is_success() {
+		log::error!("[update_webhook] Failed to update webhook {}. Status code: {}, Text: {:?}",
+			hook_uuid, res.status(), res.text().await);
+		return;
+	}
+	log::info!("Webhook {} updated in {}/{}", hook_uuid, workspace_slug, repo_slug);
+}
+
+pub async fn delete_webhook(workspace_slug: &str, repo_slug: &str, hook_uuid: &str, access_token: &str) {
+	let url = format!("{}/repositories/{}/{}/hooks/{}",
+		bitbucket_base_url(), workspace_slug, repo_slug, hook_uuid);
+	let headers_map_opt = prepare_auth_headers(access_token);
+	if headers_map_opt.is_none() {
+		return;
+	}
+	let headers_map = headers_map_opt.expect("Empty headers_map_opt");
+	let response = send_request(get_client()
+		.delete(&url)
+		.headers(headers_map)).await;
+	if response.is_err() {
+		let err = response.expect_err("No error in response");
+		log::error!("[delete_webhook] Error in api call: {:?}", err);
+		return;
+	}
+	let res = response.expect("Uncaught error in response");
+	if !res.status().is_success() {
+		log::error!("[delete_webhook] Failed to delete webhook {}. Status code: {}", hook_uuid, res.status());
+		return;
+	}
+	delete_webhook_from_db(&ProviderEnum::Bitbucket.to_string(), workspace_slug, repo_slug, hook_uuid);
+	log::info!("Webhook {} deleted from {}/{}", hook_uuid, workspace_slug, repo_slug);
+}
+
+fn webhook_payload(desired: &DesiredHook) -> Value {
+	let mut payload = json!({
 		"description": "Webhook for PRs when raised and when something is pushed to the open PRs",
-		"url": callback_url,
+		"url": desired.url,
 		"active": true,
-		"events": ["pullrequest:created", "pullrequest:updated"] 
+		"events": desired.events,
 	});
-	let response = get_client()
-		.post(&url)
-		.headers(headers_map)
-		.json(&payload)
-		.send()
-		.await;
-	process_add_webhook_response(response).await;
+	if let Some(secret) = &desired.secret {
+		payload["secret"] = json!(secret);
+	}
+	payload
 }
 
-async fn process_add_webhook_response(response: Result<Response, Error>){
+async fn process_add_webhook_response(workspace_slug: &str, repo_slug: &str, response: Result<Response, Error>){
 	if response.is_err() {
 		let err = response.expect_err("No error in response");
 		log::error!("[process_add_webhook_response] Error in api call: {:?}", err);
@@ -84,6 +145,7 @@ async fn process_add_webhook_response(response: Result<Response, Error>){
 		webhook.events().to_owned(),
 		webhook.links()["self"]["href"].clone(),
 		webhook.url().to_string(),
+		webhook.secret_set(),
 	);
-	save_webhook_to_db(&webhook_data); 
+	save_webhook_to_db(&ProviderEnum::Bitbucket.to_string(), workspace_slug, repo_slug, webhook_data.uuid(), &webhook_data);
 }
\ No newline at end of file
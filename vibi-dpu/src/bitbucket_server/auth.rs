@@ -0,0 +1,16 @@
+use std::env;
+
+// HTTP access token of a user, project or repository with admin rights on the repos
+pub fn bitbucket_server_access_token() -> Option<String> {
+    let token_res = env::var("BITBUCKET_SERVER_TOKEN");
+    if token_res.is_err() {
+        log::debug!("[bitbucket_server_access_token] BITBUCKET_SERVER_TOKEN env var must be set");
+        return None;
+    }
+    let token = token_res.expect("Empty BITBUCKET_SERVER_TOKEN env var");
+    if token.is_empty() {
+        log::debug!("[bitbucket_server_access_token] BITBUCKET_SERVER_TOKEN 0 length");
+        return None;
+    }
+    Some(token)
+}
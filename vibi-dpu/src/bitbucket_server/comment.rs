@@ -0,0 +1,78 @@
+use serde_json::{json, Value};
+
+use crate::utils::review::Review;
+use crate::utils::reqwest_client::{get_client, send_request};
+
+use super::config::{get_api_response, prepare_headers};
+use super::prs::pr_url;
+
+// Returns the id of the created comment
+pub async fn add_comment(comment_text: &str, review: &Review, access_token: &str) -> Option<String> {
+    let url = format!("{}/comments", pr_url(review.repo_owner(), review.repo_name(), review.id()));
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[bitbucket_server/add_comment] Unable to prepare headers, empty headers_opt");
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let response_res = send_request(get_client().post(&url)
+        .headers(headers).json(&json!({ "text": comment_text }))).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[bitbucket_server/add_comment] Error in post request for adding comment - {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[bitbucket_server/add_comment] Unable to add comment, status: {}", response.status());
+        return None;
+    }
+    let response_json_res = response.json::<Value>().await;
+    if response_json_res.is_err() {
+        let e = response_json_res.expect_err("No error in response_json_res");
+        log::error!("[bitbucket_server/add_comment] Unable to deserialize comment response: {:?}", e);
+        return None;
+    }
+    let response_json = response_json_res.expect("Uncaught error in response_json_res");
+    response_json["id"].as_u64().map(|comment_id| comment_id.to_string())
+}
+
+// Edits need the comment's current version, which is read first.
+// Some(false) if the comment no longer exists, None if the edit failed for any other reason
+pub async fn edit_comment(comment_id: &str, comment_text: &str, review: &Review, access_token: &str) -> Option<bool> {
+    let url = format!("{}/comments/{}", pr_url(review.repo_owner(), review.repo_name(), review.id()), comment_id);
+    let existing_opt = get_api_response(&url, access_token).await;
+    if existing_opt.is_none() {
+        log::info!("[bitbucket_server/edit_comment] Comment {} not found, a new one will be added", comment_id);
+        return Some(false);
+    }
+    let existing_res = existing_opt.expect("Empty existing_opt").json::<Value>().await;
+    if existing_res.is_err() {
+        let e = existing_res.expect_err("No error in existing_res");
+        log::error!("[bitbucket_server/edit_comment] Unable to deserialize comment {}: {:?}", comment_id, e);
+        return None;
+    }
+    let version = existing_res.expect("Uncaught error in existing_res")["version"].as_u64().unwrap_or_default();
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[bitbucket_server/edit_comment] Unable to prepare headers, empty headers_opt");
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let response_res = send_request(get_client().put(&url)
+        .headers(headers).json(&json!({ "text": comment_text, "version": version }))).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[bitbucket_server/edit_comment] Error in put request for editing comment - {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Some(false);
+    }
+    if !response.status().is_success() {
+        log::error!("[bitbucket_server/edit_comment] Unable to edit comment {}, status: {}", comment_id, response.status());
+        return None;
+    }
+    Some(true)
+}
@@ -0,0 +1,88 @@
+use std::env;
+use reqwest::{Response, header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION}};
+use serde_json::Value;
+
+use crate::utils::reqwest_client::{get_client, send_request};
+
+// Web root of the Data Center instance, e.g. https://bitbucket.example.com
+pub fn bitbucket_server_url() -> String {
+    let server_url = env::var("BITBUCKET_SERVER_URL").expect("BITBUCKET_SERVER_URL must be set");
+    server_url.trim_end_matches('/').to_string()
+}
+
+pub fn bitbucket_server_api_url() -> String {
+    format!("{}/rest/api/1.0", bitbucket_server_url())
+}
+
+// Pull request page shown to users
+pub fn pr_web_url(project_key: &str, repo_slug: &str, pr_id: &str) -> String {
+    format!("{}/projects/{}/repos/{}/pull-requests/{}", bitbucket_server_url(), project_key, repo_slug, pr_id)
+}
+
+// All values of a paged endpoint, Data Center pages with start/isLastPage/nextPageStart
+pub async fn get_api_paginated(url: &str, access_token: &str) -> Option<Vec<Value>> {
+    let separator = if url.contains('?') { "&" } else { "?" };
+    let mut next_start_opt = Some(0);
+    let mut values = Vec::<Value>::new();
+    while let Some(start) = next_start_opt {
+        let get_url = format!("{}{}start={}", url, separator, start);
+        let response_opt = get_api_response(&get_url, access_token).await;
+        if response_opt.is_none() {
+            log::error!("[get_api_paginated] Unable to call get api for: {:?}", &get_url);
+            return None;
+        }
+        let parse_res = response_opt.expect("Empty response_opt").json::<Value>().await;
+        if parse_res.is_err() {
+            let e = parse_res.expect_err("No error in parse_res");
+            log::error!("[get_api_paginated] Unable to deserialize response for {}: {:?}", &get_url, e);
+            return None;
+        }
+        let page_json = parse_res.expect("Uncaught error in parse_res");
+        let page_values_opt = page_json["values"].as_array();
+        if page_values_opt.is_none() {
+            log::error!("[get_api_paginated] Response is not a paged list: {:?}", &page_json);
+            return None;
+        }
+        values.extend(page_values_opt.expect("Empty page_values_opt").to_owned());
+        next_start_opt = None;
+        if !page_json["isLastPage"].as_bool().unwrap_or(true) {
+            next_start_opt = page_json["nextPageStart"].as_u64();
+        }
+    }
+    Some(values)
+}
+
+pub async fn get_api_response(url: &str, access_token: &str) -> Option<Response> {
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[get_api_response] Unable to prepare_headers, empty headers_opt");
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let client = get_client();
+    let get_res = send_request(client.get(url).headers(headers)).await;
+    if get_res.is_err() {
+        let e = get_res.expect_err("No error in get_res");
+        log::error!("[get_api_response] Error sending GET request to {}, error: {}", url, e);
+        return None;
+    }
+    let response = get_res.expect("Uncaught error in get_res");
+    if !response.status().is_success() {
+        log::error!("[get_api_response] Failed to call Bitbucket Data Center API {}, status: {}", url, response.status());
+        return None;
+    }
+    Some(response)
+}
+
+pub fn prepare_headers(access_token: &str) -> Option<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    let auth_header_res = HeaderValue::from_str(&format!("Bearer {}", access_token));
+    if auth_header_res.is_err() {
+        let e = auth_header_res.expect_err("Empty error in auth_header_res");
+        log::error!("[prepare_headers] Invalid auth header: {:?}", e);
+        return None;
+    }
+    headers.insert(AUTHORIZATION, auth_header_res.expect("Uncaught error in auth_header_res"));
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    Some(headers)
+}
@@ -0,0 +1,81 @@
+use serde_json::Value;
+
+use crate::db::prs::update_pr_info_in_db;
+use crate::utils::pr_info::PrInfo;
+use crate::utils::user::ProviderEnum;
+
+use super::config::{bitbucket_server_api_url, get_api_paginated, get_api_response};
+
+pub fn pr_url(project_key: &str, repo_slug: &str, pr_id: &str) -> String {
+    format!("{}/projects/{}/repos/{}/pull-requests/{}", bitbucket_server_api_url(), project_key, repo_slug, pr_id)
+}
+
+// Ids of the repo's pull requests, `state` is one of OPEN, DECLINED, MERGED or ALL
+pub async fn list_prs_bitbucket_server(project_key: &str, repo_slug: &str, access_token: &str, state: &str) -> Option<Vec<String>> {
+    let url = format!("{}/projects/{}/repos/{}/pull-requests?state={}&limit=50",
+        bitbucket_server_api_url(), project_key, repo_slug, state);
+    let prs_opt = get_api_paginated(&url, access_token).await;
+    if prs_opt.is_none() {
+        log::error!("[list_prs_bitbucket_server] Unable to get pull requests for {}/{}", project_key, repo_slug);
+        return None;
+    }
+    let pr_list = prs_opt.expect("Empty prs_opt").iter()
+        .map(|pr| pr["id"].to_string())
+        .collect();
+    Some(pr_list)
+}
+
+pub async fn get_pr_json(project_key: &str, repo_slug: &str, access_token: &str, pr_id: &str) -> Option<Value> {
+    let response_opt = get_api_response(&pr_url(project_key, repo_slug, pr_id), access_token).await;
+    if response_opt.is_none() {
+        log::error!("[get_pr_json] Unable to get pull request {} for {}/{}", pr_id, project_key, repo_slug);
+        return None;
+    }
+    let parse_res = response_opt.expect("Empty response_opt").json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[get_pr_json] Error parsing PR data: {:?}", e);
+        return None;
+    }
+    Some(parse_res.expect("Uncaught error in parse_res"))
+}
+
+// Pull request objects are the same in api responses and webhook payloads
+pub fn parse_pr_info(pr_data: &Value) -> Option<PrInfo> {
+    let pr_info = PrInfo {
+        base_head_commit: pr_data["toRef"]["latestCommit"].as_str()?.to_string(),
+        pr_head_commit: pr_data["fromRef"]["latestCommit"].as_str()?.to_string(),
+        state: pr_data["state"].as_str()?.to_string(),
+        pr_branch: pr_data["fromRef"]["displayId"].as_str()?.to_string(),
+        author: Some(pr_data["author"]["user"]["slug"].as_str()?.to_string()),
+    };
+    Some(pr_info)
+}
+
+pub async fn get_and_store_pr_info(project_key: &str, repo_slug: &str, access_token: &str, pr_id: &str) {
+    let pr_info_opt = get_pr_json(project_key, repo_slug, access_token, pr_id).await
+        .and_then(|pr_data| parse_pr_info(&pr_data));
+    if pr_info_opt.is_none() {
+        log::error!("[get_and_store_pr_info] No PR info available for PR number: {:?} repository: {:?}/{:?}",
+            pr_id, project_key, repo_slug);
+        return;
+    }
+    update_pr_info_in_db(project_key, repo_slug, &pr_info_opt.expect("Empty pr_info_opt"), pr_id,
+        &ProviderEnum::BitbucketServer.to_string()).await;
+}
+
+// Reviewers currently approving as (user slug, last reviewed commit)
+pub async fn pr_approvals(project_key: &str, repo_slug: &str, pr_id: &str, access_token: &str) -> Option<Vec<(String, Option<String>)>> {
+    let pr_data_opt = get_pr_json(project_key, repo_slug, access_token, pr_id).await;
+    if pr_data_opt.is_none() {
+        log::error!("[pr_approvals] Unable to get pull request {}", pr_id);
+        return None;
+    }
+    let pr_data = pr_data_opt.expect("Empty pr_data_opt");
+    let approvals = pr_data["reviewers"].as_array().cloned().unwrap_or_default().iter()
+        .filter(|reviewer| reviewer["status"].as_str() == Some("APPROVED"))
+        .filter_map(|reviewer| reviewer["user"]["slug"].as_str().map(|slug| (slug.to_string(),
+            reviewer["lastReviewedCommit"].as_str().map(|commit| commit.to_string()))))
+        .collect();
+    Some(approvals)
+}
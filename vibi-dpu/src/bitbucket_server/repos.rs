@@ -0,0 +1,47 @@
+use serde_json::Value;
+
+use crate::db::repo::save_repo_to_db;
+use crate::utils::repo::Repository;
+use crate::utils::user::ProviderEnum;
+
+use super::config::{bitbucket_server_api_url, get_api_paginated};
+
+// Repos the token can administer, webhooks can't be added to the rest
+pub async fn get_bitbucket_server_repos(access_token: &str) -> Option<Vec<Repository>> {
+    let repos_url = format!("{}/repos?permission=REPO_ADMIN&limit=100", bitbucket_server_api_url());
+    let repos_opt = get_api_paginated(&repos_url, access_token).await;
+    if repos_opt.is_none() {
+        log::error!("[get_bitbucket_server_repos] Unable to call get api and get all repos");
+        return None;
+    }
+    let mut repos = Vec::<Repository>::new();
+    for repo_json in repos_opt.expect("Empty repos_opt") {
+        let repo = deserialize_repo_object(&repo_json);
+        save_repo_to_db(&repo);
+        repos.push(repo);
+    }
+    log::debug!("[get_bitbucket_server_repos] Fetched {:?} repositories from Bitbucket Data Center", &repos);
+    Some(repos)
+}
+
+// Project key is the owner and the repo slug the name, the http clone link takes the access token
+fn deserialize_repo_object(repo_json: &Value) -> Repository {
+    let project_key = repo_json["project"]["key"].to_string().trim_matches('"').to_string();
+    let clone_url = repo_json["links"]["clone"].as_array().cloned().unwrap_or_default().iter()
+        .find(|link| link["name"].as_str() == Some("http"))
+        .map(|link| link["href"].to_string().trim_matches('"').to_string())
+        .unwrap_or_default();
+    let repo = Repository::new(
+        repo_json["slug"].to_string().trim_matches('"').to_string(),
+        repo_json["id"].to_string(),
+        project_key.clone(),
+        !repo_json["public"].as_bool().unwrap_or(false),
+        clone_url,
+        Some(repo_json["project"]["name"].to_string().trim_matches('"').to_string()),
+        repo_json["project"]["type"].as_str().map(|project_type| project_type.to_string()),
+        project_key,
+        None,
+        ProviderEnum::BitbucketServer.to_string(),
+    );
+    repo
+}
@@ -0,0 +1,33 @@
+use serde_json::json;
+
+use crate::utils::review::Review;
+use crate::utils::reqwest_client::{get_client, send_request};
+
+use super::config::prepare_headers;
+use super::prs::pr_url;
+
+// Data Center takes reviewers one participant at a time, existing reviewers are left as they are
+pub async fn add_reviewers(reviewers: &Vec<String>, review: &Review, access_token: &str) {
+    let url = format!("{}/participants", pr_url(review.repo_owner(), review.repo_name(), review.id()));
+    for reviewer in reviewers {
+        let headers_opt = prepare_headers(access_token);
+        if headers_opt.is_none() {
+            log::error!("[add_reviewers] Unable to prepare headers, empty headers_opt");
+            return;
+        }
+        let headers = headers_opt.expect("Empty headers_opt");
+        let body = json!({ "user": { "name": reviewer }, "role": "REVIEWER" });
+        let response_res = send_request(get_client().post(&url).headers(headers).json(&body)).await;
+        if response_res.is_err() {
+            let e = response_res.expect_err("No error in response_res");
+            log::error!("[add_reviewers] Unable to add reviewer: {:?}, {:?}", e, reviewer);
+            continue;
+        }
+        let response = response_res.expect("Uncaught error in response_res");
+        if !response.status().is_success() {
+            log::error!("[add_reviewers] Unable to add reviewer {}, status: {}", reviewer, response.status());
+            continue;
+        }
+        log::debug!("[add_reviewers] Added reviewer {} to PR {}", reviewer, review.id());
+    }
+}
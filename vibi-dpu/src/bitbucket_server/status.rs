@@ -0,0 +1,38 @@
+use serde_json::json;
+
+use crate::utils::reqwest_client::{get_client, send_request};
+
+use super::config::{bitbucket_server_url, prepare_headers};
+
+const COVERAGE_STATUS_KEY: &str = "vibinex-relevant-approvals";
+
+// Build status on the commit, shown on the pull request and usable in merge checks
+pub async fn set_build_status(commit: &str, state: &str, description: &str,
+        target_url: &str, access_token: &str) {
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[set_build_status] Unable to prepare auth headers for commit: {}", commit);
+        return;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let url = format!("{}/rest/build-status/1.0/commits/{}", bitbucket_server_url(), commit);
+    let body = json!({
+        "state": state,
+        "key": COVERAGE_STATUS_KEY,
+        "name": "Vibinex relevant approvals",
+        "url": target_url,
+        "description": description,
+    });
+    let response_res = send_request(get_client().post(&url).headers(headers).json(&body)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[set_build_status] Failed to send the request: {:?}", e);
+        return;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[set_build_status] Unable to set status on {}, status: {}", commit, response.status());
+        return;
+    }
+    log::debug!("[set_build_status] Status {} set on commit {}", state, commit);
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::env;
+
+use serde_json::{json, Value};
+
+use crate::db::webhook::save_webhook_to_db;
+use crate::utils::github_webhook::Webhook;
+use crate::utils::reqwest_client::{get_client, send_request};
+use crate::utils::user::ProviderEnum;
+
+use super::config::{bitbucket_server_api_url, get_api_paginated, prepare_headers};
+
+pub async fn get_webhooks_in_repo(project_key: &str, repo_slug: &str, access_token: &str) -> Option<Vec<Webhook>> {
+    let url = format!("{}/projects/{}/repos/{}/webhooks", bitbucket_server_api_url(), project_key, repo_slug);
+    log::debug!("[get_webhooks_in_repo] Getting webhooks from {}", url);
+    let hooks_opt = get_api_paginated(&url, access_token).await;
+    if hooks_opt.is_none() {
+        log::error!("[get_webhooks_in_repo] Unable to call get api and get all webhooks");
+        return None;
+    }
+    let webhooks = hooks_opt.expect("Empty hooks_opt").iter()
+        .map(deserialize_webhook_object)
+        .collect();
+    Some(webhooks)
+}
+
+pub async fn add_webhook(project_key: &str, repo_slug: &str, access_token: &str) {
+    let url = format!("{}/projects/{}/repos/{}/webhooks", bitbucket_server_api_url(), project_key, repo_slug);
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[add_webhook] Unable to prepare headers for {}/{}", project_key, repo_slug);
+        return;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let callback_url = format!("{}/api/bitbucket-server/callbacks/webhook",
+        env::var("SERVER_URL").expect("SERVER_URL must be set"));
+    let payload = json!({
+        "name": "Vibinex",
+        "events": ["pr:opened", "pr:from_ref_updated",
+            "pr:reviewer:approved", "pr:reviewer:unapproved", "pr:reviewer:needs_work"],
+        "url": callback_url,
+        "active": true,
+    });
+    let response_res = send_request(get_client().post(&url).headers(headers).json(&payload)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[add_webhook] Error in api call: {:?}", e);
+        return;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[add_webhook] Failed to add webhook. Status code: {}, Text: {:?}",
+            response.status(), response.text().await);
+        return;
+    }
+    let parse_res = response.json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[add_webhook] Unable to deserialize webhook response: {:?}", e);
+        return;
+    }
+    let webhook = deserialize_webhook_object(&parse_res.expect("Uncaught error in parse_res"));
+    log::info!("Webhook added: {:?}", webhook.url());
+    save_webhook_to_db(&ProviderEnum::BitbucketServer.to_string(), project_key, repo_slug, webhook.id(), &webhook);
+}
+
+fn deserialize_webhook_object(webhook_json: &Value) -> Webhook {
+    let config: HashMap<String, Value> = webhook_json["configuration"].as_object()
+        .map(|config_obj| config_obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default();
+    let webhook = Webhook::new(
+        webhook_json["id"].to_string(),
+        webhook_json["active"].as_bool().unwrap_or(false),
+        webhook_json["createdDate"].to_string().replace('"', ""),
+        webhook_json["events"].as_array().cloned().unwrap_or_default().iter()
+            .filter_map(|event| event.as_str())
+            .map(|event| event.to_string())
+            .collect(),
+        String::new(),
+        webhook_json["url"].to_string().replace('"', ""),
+        config,
+    );
+    webhook
+}
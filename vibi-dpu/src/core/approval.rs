@@ -3,11 +3,19 @@ use serde_json::Value;
 use crate::core::utils::get_access_token;
 use crate::db::repo_config::save_repo_config_to_db;
 use crate::utils::coverage::CoverageMap;
-use crate::github;
 use crate::core;
+use crate::core::approval_history::{approval_status_text, approval_statuses, valid_approver_handles, ApprovalEvent, ApprovalStatus};
+use crate::core::comment_template::{default_comment, load_comment_template, render_comment, CommentVars};
+use crate::core::coverage_gate::publish_coverage_status;
+use crate::core::relevance::{deduplicated_relevance_vec_for_comment, reviewer_details};
+use crate::db::hunk::get_hunk_from_db;
+use crate::db::prs::pr_info_from_db;
+use crate::utils::comment_template::CommentTemplateKind;
+use crate::utils::hunk::PrHunkItem;
 use crate::utils::relevance::Relevance;
+use crate::utils::review::Review;
 use crate::utils::repo_config::RepoConfig;
-use crate::{db::review::get_review_from_db, utils::user::ProviderEnum};
+use crate::db::review::get_review_from_db;
 
 
 pub async fn process_approval(deserialised_msg_data: &Value, repo_config_val: &Value,
@@ -23,12 +31,6 @@ pub async fn process_approval(deserialised_msg_data: &Value, repo_config_val: &V
     }
 	log::debug!("[process_approval] repo_config = {:?}", &repo_config);
 	save_repo_config_to_db(&repo_config, repo_name, repo_owner, repo_provider);
-    if !repo_config.comment() {
-        log::info!("Comment setting is turned off, not adding comment...");
-        return;
-    }
-    let pr_head_commit = deserialised_msg_data["review"]["commit_id"]
-        .to_string().trim_matches('"').to_string();
     let review_opt = get_review_from_db(&repo_name,
         &repo_owner, &repo_provider, &pr_number);
     if review_opt.is_none() {
@@ -36,6 +38,10 @@ pub async fn process_approval(deserialised_msg_data: &Value, repo_config_val: &V
         return;
     }
     let review = review_opt.expect("Empty review_opt");
+    if !repo_config.comment() && repo_config.coverage_gate().is_none() {
+        log::info!("Comment setting and coverage gate are turned off, not processing approval...");
+        return;
+    }
 
     let access_token= get_access_token(&Some(review.clone()), &repo_provider).await;
 	if access_token.is_none(){
@@ -44,18 +50,6 @@ pub async fn process_approval(deserialised_msg_data: &Value, repo_config_val: &V
 	}
 	let final_access_token = access_token.expect("Empty final access token opt");
 
-    // get reviewer login array by getting pr all reviewer info from gh/bb
-    let mut reviewer_handles = Vec::<String>::new();
-    if repo_provider == ProviderEnum::Github.to_string().to_lowercase() {
-        let reviewer_handles_opt = github::prs::pr_reviewer_handles(
-            &repo_owner, &repo_name, &pr_number, &pr_head_commit, &final_access_token).await;
-        if reviewer_handles_opt.is_none(){
-            log::error!("[process_approval] no reviewers handles opt");
-            return;
-        }
-        reviewer_handles = reviewer_handles_opt.expect("Empty reviewer_handles_opt");
-
-    } //TODO: create a similar function for bitbucket too
     // get coverage map aliases and their corresponding logins from db/server
     let relevance_vec_opt = review.relevance();
     if relevance_vec_opt.is_none() {
@@ -63,22 +57,95 @@ pub async fn process_approval(deserialised_msg_data: &Value, repo_config_val: &V
         return;
     }
     let relevance_vec = relevance_vec_opt.to_owned().expect("Empty coverage_opt");
+    // get approvals from gh/bb, stale ones don't count towards coverage
+    let hunkmap_opt = get_hunk_from_db(&review);
+    let prhunk_opt = hunkmap_opt.as_ref().and_then(|hunkmap| hunkmap.prhunkvec().first());
+    // head at this approval event, for providers that don't report the approved commit
+    let event_commit = pr_info_from_db(repo_owner, repo_name, pr_number, repo_provider)
+        .map(|pr_info| pr_info.pr_head_commit().to_owned())
+        .unwrap_or_else(|| review.pr_head_commit().to_owned());
+    let event_opt = event_approver(deserialised_msg_data)
+        .map(|approver| ApprovalEvent::new(approver, event_commit));
+    let approval_statuses_opt = approval_statuses(&review, &relevance_vec,
+        prhunk_opt, event_opt.as_ref(), &final_access_token).await;
+    if approval_statuses_opt.is_none(){
+        log::error!("[process_approval] no approval statuses opt");
+        return;
+    }
+    let approval_statuses = approval_statuses_opt.expect("Empty approval_statuses_opt");
+    let reviewer_handles = valid_approver_handles(&approval_statuses);
     let mut coverage_map_obj = CoverageMap::new(repo_provider.to_string());
     coverage_map_obj.calculate_coverage_map(relevance_vec.clone(), reviewer_handles.clone());
+    publish_coverage_status(&review, coverage_map_obj.coverage_total(), &repo_config, &final_access_token).await;
+    if !repo_config.comment() {
+        log::info!("Comment setting is turned off, not adding comment...");
+        return;
+    }
     // add up contribution of aliases
     // add comment
-    let comment_text = approval_comment_text(&coverage_map_obj, relevance_vec, reviewer_handles);
-    // get access token and call add_comment in gh/bb
-    core::github::comment::add_comment(&comment_text, &review, &final_access_token).await;
+    let comment_template_opt = load_comment_template(&review, &repo_config, &CommentTemplateKind::Approval);
+    let comment_text = approval_comment_text(ApprovalComment {
+        coverage_map: &coverage_map_obj,
+        relevance_vec,
+        reviewer_handles,
+        approval_statuses: &approval_statuses,
+        template_opt: &comment_template_opt,
+        repo_config: &repo_config,
+        prhunk_opt,
+        review: &review,
+    });
+    if let Some(provider) = core::provider::repo_provider(repo_provider) {
+        provider.add_comment(&comment_text, &review, &final_access_token).await;
+    }
+}
+
+// Who the approval webhook is for, in the handle the provider's approvals list uses
+fn event_approver(event_payload: &Value) -> Option<String> {
+    let approver_paths: [&[&str]; 5] = [
+        &["approval", "user", "uuid"],     // bitbucket
+        &["participant", "user", "slug"],  // bitbucket server
+        &["review", "user", "login"],      // github
+        &["user", "username"],             // gitlab
+        &["sender", "login"],              // gitea
+    ];
+    approver_paths.iter()
+        .find_map(|path| path.iter()
+            .fold(event_payload, |value, key| &value[*key])
+            .as_str()
+            .map(str::to_string))
+}
+
+// Everything the approval comment is rendered from
+struct ApprovalComment<'a> {
+    coverage_map: &'a CoverageMap,
+    relevance_vec: Vec<Relevance>,
+    reviewer_handles: Vec<String>,
+    approval_statuses: &'a [ApprovalStatus],
+    template_opt: &'a Option<String>,
+    repo_config: &'a RepoConfig,
+    prhunk_opt: Option<&'a PrHunkItem>,
+    review: &'a Review,
 }
 
-fn approval_comment_text(coverage_map: &CoverageMap, relevance_vec: Vec<Relevance>, reviewer_handles: Vec<String>) -> String {
-    let mut comment = "Relevant users for this PR:\n\n".to_string();  // Added two newlines
-    let coverage_text = coverage_map.generate_coverage_table(relevance_vec, reviewer_handles);
-    comment += &coverage_text;
-    comment += "\n\n";
-    comment += "If you are a relevant reviewer, you can use the [Vibinex browser extension](https://chromewebstore.google.com/detail/vibinex-code-review/jafgelpkkkopeaefadkdjcmnicgpcncc) to see parts of the PR relevant to you\n";  // Added a newline at the end
-    comment += "Relevance of the reviewer is calculated based on the git blame information of the PR. To know more, hit us up at contact@vibinex.com.\n\n";  // Added two newlines
-    comment += "To change comment and auto-assign settings, go to [your Vibinex repository settings page.](https://vibinex.com/u)\n";  // Added a newline at the end
-    return comment;
+fn approval_comment_text(approval_comment: ApprovalComment) -> String {
+    let (_, unmapped_aliases) = deduplicated_relevance_vec_for_comment(&approval_comment.relevance_vec);
+    let reviewer_details = approval_comment.prhunk_opt
+        .map(|prhunk| reviewer_details(&approval_comment.relevance_vec, approval_comment.repo_config.relevance_mode(),
+            prhunk, approval_comment.review))
+        .unwrap_or_default();
+    let comment_vars = CommentVars {
+        reviewer_table: approval_comment.coverage_map.generate_coverage_table(approval_comment.relevance_vec,
+            approval_comment.reviewer_handles),
+        reviewer_details,
+        approvals: approval_status_text(approval_comment.approval_statuses),
+        coverage: approval_comment.coverage_map.coverage_total_str(),
+        unmapped_aliases_count: unmapped_aliases.len(),
+        auto_assign: approval_comment.repo_config.auto_assign(),
+    };
+    if let Some(template) = approval_comment.template_opt {
+        if let Some(comment) = render_comment(template, &comment_vars, approval_comment.review) {
+            return comment;
+        }
+    }
+    default_comment(&comment_vars)
 }
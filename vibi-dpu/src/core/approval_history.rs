@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+
+use crate::core::provider::repo_provider;
+use crate::db::approval::{get_approval_records_from_db, save_approval_records_to_db};
+use crate::utils::approval::ApprovalRecord;
+use crate::utils::gitops::{get_diff_hunks, DiffHunk};
+use crate::utils::hunk::PrHunkItem;
+use crate::utils::relevance::Relevance;
+use crate::utils::review::Review;
+
+#[derive(Debug, Clone)]
+pub struct ApprovalStatus {
+	handles: Vec<String>,
+	commit: String,
+	stale: bool,
+}
+
+impl ApprovalStatus {
+	// Public getter methods
+	pub fn handles(&self) -> &Vec<String> {
+		&self.handles
+	}
+
+	pub fn is_stale(&self) -> bool {
+		self.stale
+	}
+}
+
+// An approval webhook: who approved and the PR head when it arrived
+#[derive(Debug, Clone)]
+pub struct ApprovalEvent {
+	approver: String,
+	commit: String,
+}
+
+impl ApprovalEvent {
+	// Constructor
+	pub fn new(approver: String, commit: String) -> Self {
+		Self { approver, commit }
+	}
+}
+
+// Current approvals of the PR, each marked stale if commits pushed after it
+// touch the lines where the approver's relevance comes from.
+pub async fn approval_statuses(review: &Review, relevance_vec: &[Relevance],
+	prhunk_opt: Option<&PrHunkItem>, event_opt: Option<&ApprovalEvent>, access_token: &str) -> Option<Vec<ApprovalStatus>> {
+	let approvers_opt = current_approvers(review, access_token).await;
+	if approvers_opt.is_none() {
+		log::error!("[approval_statuses] Unable to get approvers for PR {}", review.id());
+		return None;
+	}
+	let mut records = get_approval_records_from_db(review).unwrap_or_default();
+	let approved_commits = record_approvals(&mut records, approvers_opt.expect("Empty approvers_opt"),
+		event_opt, review.pr_head_commit());
+	save_approval_records_to_db(review, &records);
+	let statuses = approved_commits.into_iter()
+		.map(|(handles, commit)| {
+			let stale = is_approval_stale(&commit, &handles, relevance_vec, prhunk_opt, review);
+			ApprovalStatus { handles, commit, stale }
+		})
+		.collect();
+	Some(statuses)
+}
+
+// Approved commit of each approver: the one the provider reports, else the head at the approver's
+// own approval event, else the commit recorded when the approval was first seen.
+// Bitbucket and Gitlab don't report it. Records of withdrawn approvals are dropped,
+// so approving again starts from the new head.
+fn record_approvals(records: &mut Vec<ApprovalRecord>, approvers: Vec<(Vec<String>, Option<String>)>,
+	event_opt: Option<&ApprovalEvent>, pr_head_commit: &str) -> Vec<(Vec<String>, String)> {
+	records.retain(|record| approvers.iter()
+		.any(|(handles, _)| record.handles().iter().any(|handle| handles.contains(handle))));
+	let mut approved_commits = Vec::<(Vec<String>, String)>::new();
+	for (handles, commit_opt) in approvers {
+		let event_commit_opt = event_opt
+			.filter(|event| handles.contains(&event.approver))
+			.map(|event| event.commit.to_owned());
+		let record_idx_opt = records.iter()
+			.position(|record| record.handles().iter().any(|handle| handles.contains(handle)));
+		let record_idx = match record_idx_opt {
+			Some(record_idx) => record_idx,
+			None => {
+				records.push(ApprovalRecord::new(handles.clone()));
+				records.len() - 1
+			}
+		};
+		let record = &mut records[record_idx];
+		let commit = commit_opt
+			.or(event_commit_opt)
+			.or_else(|| record.latest_commit().cloned())
+			.unwrap_or_else(|| pr_head_commit.to_owned());
+		record.add_commit(&commit);
+		approved_commits.push((handles, commit));
+	}
+	approved_commits
+}
+
+pub fn valid_approver_handles(statuses: &[ApprovalStatus]) -> Vec<String> {
+	statuses.iter()
+		.filter(|status| !status.is_stale())
+		.flat_map(|status| status.handles().iter().cloned())
+		.collect()
+}
+
+pub fn approval_status_text(statuses: &[ApprovalStatus]) -> String {
+	if statuses.is_empty() {
+		return String::new();
+	}
+	let mut text = "Approvals:\n\n".to_string();
+	for status in statuses {
+		let handle = status.handles().first().cloned().unwrap_or_default();
+		let short_commit: String = status.commit.chars().take(7).collect();
+		let validity = if status.is_stale() { "re-review needed" } else { "still valid" };
+		text += &format!("- {}: approved at {}, {}\n", handle, short_commit, validity);
+	}
+	text
+}
+
+async fn current_approvers(review: &Review, access_token: &str) -> Option<Vec<(Vec<String>, Option<String>)>> {
+	let repo_provider_opt = repo_provider(review.provider());
+	if repo_provider_opt.is_none() {
+		log::error!("[current_approvers] Unsupported repo provider {}", review.provider());
+		return None;
+	}
+	return repo_provider_opt.expect("Empty repo_provider_opt").approvals(review, access_token).await;
+}
+
+fn is_approval_stale(commit: &str, handles: &[String], relevance_vec: &[Relevance],
+	prhunk_opt: Option<&PrHunkItem>, review: &Review) -> bool {
+	if commit == review.pr_head_commit() {
+		return false;
+	}
+	let new_hunks_opt = get_diff_hunks(commit, review.pr_head_commit(), review.clone_dir());
+	if new_hunks_opt.is_none() {
+		// approved commit is gone, e.g. after a force push
+		return true;
+	}
+	let new_hunks = new_hunks_opt.expect("Empty new_hunks_opt");
+	let relevant_lines = relevant_lines(handles, relevance_vec, prhunk_opt);
+	if relevant_lines.is_empty() {
+		// relevance not from changed lines, any change needs another look
+		return !new_hunks.is_empty();
+	}
+	// relevant lines are blamed on the base, the approved commit may have moved them
+	let approved_hunks_opt = get_diff_hunks(review.base_head_commit(), commit, review.clone_dir());
+	if approved_hunks_opt.is_none() {
+		return true;
+	}
+	touches_relevant_lines(&relevant_lines,
+		&approved_hunks_opt.expect("Empty approved_hunks_opt"), &new_hunks)
+}
+
+// Changed lines of the base authored by any alias mapped to the approver, per file
+fn relevant_lines(handles: &[String], relevance_vec: &[Relevance],
+	prhunk_opt: Option<&PrHunkItem>) -> HashMap<String, Vec<(usize, usize)>> {
+	let mut lines = HashMap::<String, Vec<(usize, usize)>>::new();
+	if prhunk_opt.is_none() {
+		return lines;
+	}
+	let aliases: Vec<&String> = relevance_vec.iter()
+		.filter(|relevance_obj| match relevance_obj.handles() {
+			Some(relevance_handles) => relevance_handles.iter().any(|handle| handles.contains(handle)),
+			None => handles.contains(relevance_obj.git_alias()),
+		})
+		.map(|relevance_obj| relevance_obj.git_alias())
+		.collect();
+	for blame in prhunk_opt.expect("Empty prhunk_opt").blamevec() {
+		if !aliases.contains(&blame.author()) {
+			continue;
+		}
+		let line_start_res = blame.line_start().parse::<usize>();
+		let line_end_res = blame.line_end().parse::<usize>();
+		if line_start_res.is_err() || line_end_res.is_err() {
+			log::error!("[relevant_lines] Invalid line range {}-{} in {}",
+				blame.line_start(), blame.line_end(), blame.filepath_raw());
+			continue;
+		}
+		lines.entry(blame.filepath_raw().to_owned()).or_default()
+			.push((line_start_res.expect("Uncaught error in line_start_res"),
+				line_end_res.expect("Uncaught error in line_end_res")));
+	}
+	lines
+}
+
+// Approved hunks over the relevant base lines give the regions the approver reviewed,
+// the approval is stale if a later hunk touches one of them
+fn touches_relevant_lines(relevant_lines: &HashMap<String, Vec<(usize, usize)>>,
+	approved_hunks: &HashMap<String, Vec<DiffHunk>>, new_hunks: &HashMap<String, Vec<DiffHunk>>) -> bool {
+	for (filepath, ranges) in relevant_lines {
+		let file_new_hunks_opt = new_hunks.get(filepath);
+		if file_new_hunks_opt.is_none() {
+			continue;
+		}
+		let reviewed_regions: Vec<(usize, usize)> = approved_hunks.get(filepath)
+			.map(|hunks| hunks.iter()
+				.filter(|hunk| ranges.iter().any(|range| spans_overlap(hunk.old_span(), *range)))
+				.map(|hunk| hunk.new_span())
+				.collect())
+			.unwrap_or_default();
+		let is_touched = file_new_hunks_opt.expect("Empty file_new_hunks_opt").iter()
+			.any(|hunk| reviewed_regions.iter().any(|region| spans_overlap(hunk.old_span(), *region)));
+		if is_touched {
+			return true;
+		}
+	}
+	false
+}
+
+fn spans_overlap(a: (usize, usize), b: (usize, usize)) -> bool {
+	a.0 <= b.1 && b.0 <= a.1
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::review::test_review;
+
+	fn hunks(filepath: &str, hunks: Vec<DiffHunk>) -> HashMap<String, Vec<DiffHunk>> {
+		let mut hunkmap = HashMap::new();
+		hunkmap.insert(filepath.to_string(), hunks);
+		hunkmap
+	}
+
+	fn hunks_lines(filepath: &str, ranges: Vec<(usize, usize)>) -> HashMap<String, Vec<(usize, usize)>> {
+		let mut lines = HashMap::new();
+		lines.insert(filepath.to_string(), ranges);
+		lines
+	}
+
+	fn approver(handle: &str) -> (Vec<String>, Option<String>) {
+		(vec![handle.to_string()], None)
+	}
+
+	#[test]
+	fn reapproval_after_push_moves_to_the_new_head() {
+		let mut records = Vec::<ApprovalRecord>::new();
+		let approved = record_approvals(&mut records, vec![approver("alice")],
+			Some(&ApprovalEvent::new("alice".to_string(), "c1".to_string())), "c1");
+		assert_eq!(approved, vec![(vec!["alice".to_string()], "c1".to_string())]);
+		// push without an approval event keeps the approved commit
+		let approved = record_approvals(&mut records, vec![approver("alice")], None, "c2");
+		assert_eq!(approved[0].1, "c1");
+		// approving again after the push
+		let approved = record_approvals(&mut records, vec![approver("alice")],
+			Some(&ApprovalEvent::new("alice".to_string(), "c2".to_string())), "c2");
+		assert_eq!(approved[0].1, "c2");
+	}
+
+	#[test]
+	fn withdrawn_approval_is_forgotten() {
+		let mut records = Vec::<ApprovalRecord>::new();
+		record_approvals(&mut records, vec![approver("alice")], None, "c1");
+		// unapproval, then approval without an event for it
+		record_approvals(&mut records, Vec::new(), None, "c2");
+		assert!(records.is_empty());
+		let approved = record_approvals(&mut records, vec![approver("alice")], None, "c3");
+		assert_eq!(approved[0].1, "c3");
+	}
+
+	#[test]
+	fn event_only_moves_its_own_approver() {
+		let mut records = Vec::<ApprovalRecord>::new();
+		record_approvals(&mut records, vec![approver("alice")], None, "c1");
+		let approved = record_approvals(&mut records, vec![approver("alice"), approver("bob")],
+			Some(&ApprovalEvent::new("bob".to_string(), "c2".to_string())), "c2");
+		assert_eq!(approved, vec![(vec!["alice".to_string()], "c1".to_string()),
+			(vec!["bob".to_string()], "c2".to_string())]);
+	}
+
+	#[test]
+	fn reported_commit_wins_over_event() {
+		let mut records = Vec::<ApprovalRecord>::new();
+		let approved = record_approvals(&mut records, vec![(vec!["alice".to_string()], Some("c1".to_string()))],
+			Some(&ApprovalEvent::new("alice".to_string(), "c2".to_string())), "c2");
+		assert_eq!(approved[0].1, "c1");
+	}
+
+	#[test]
+	fn approval_at_head_is_not_stale() {
+		let review = test_review("1", "head");
+		assert!(!is_approval_stale("head", &["alice".to_string()], &Vec::new(), None, &review));
+	}
+
+	#[test]
+	fn approval_of_unknown_commit_is_stale() {
+		let review = test_review("1", "head");
+		assert!(is_approval_stale("gone", &["alice".to_string()], &Vec::new(), None, &review));
+	}
+
+	#[test]
+	fn later_change_in_reviewed_lines_is_stale() {
+		let relevant = hunks_lines("src/lib.rs", vec![(10, 12)]);
+		let approved = hunks("src/lib.rs", vec![DiffHunk::new(10, 3, 10, 4)]);
+		let new = hunks("src/lib.rs", vec![DiffHunk::new(12, 1, 12, 1)]);
+		assert!(touches_relevant_lines(&relevant, &approved, &new));
+	}
+
+	#[test]
+	fn later_change_elsewhere_in_file_is_not_stale() {
+		let relevant = hunks_lines("src/lib.rs", vec![(10, 12)]);
+		let approved = hunks("src/lib.rs", vec![DiffHunk::new(10, 3, 10, 4)]);
+		let new = hunks("src/lib.rs", vec![DiffHunk::new(40, 2, 40, 2)]);
+		assert!(!touches_relevant_lines(&relevant, &approved, &new));
+	}
+
+	#[test]
+	fn later_change_in_other_file_is_not_stale() {
+		let relevant = hunks_lines("src/lib.rs", vec![(10, 12)]);
+		let approved = hunks("src/lib.rs", vec![DiffHunk::new(10, 3, 10, 4)]);
+		let new = hunks("src/main.rs", vec![DiffHunk::new(11, 1, 11, 1)]);
+		assert!(!touches_relevant_lines(&relevant, &approved, &new));
+	}
+}
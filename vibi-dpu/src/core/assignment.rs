@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+
+use crate::core::provider::{repo_provider, RepoProvider};
+use crate::core::teams::is_team_entry;
+use crate::utils::relevance::{Relevance, RelevanceSource};
+use crate::utils::repo_config::{RelevanceMode, RepoConfig};
+use crate::utils::review::Review;
+
+// Picks who gets auto assigned from the eligible relevance entries, most relevant first.
+// Providers resolve the picked entries to their own users when requesting reviews.
+pub async fn select_reviewers(relevance_vec: &[Relevance], review: &Review,
+	repo_config: &RepoConfig, access_token: &str) -> Vec<Relevance> {
+	let mut ranked_vec = relevance_vec.to_owned();
+	ranked_vec.sort_by(|a, b| b.relevance_num().partial_cmp(&a.relevance_num()).unwrap_or(std::cmp::Ordering::Equal));
+	let provider_opt = repo_provider(review.provider());
+	let mut selected_vec = Vec::<Relevance>::new();
+	let mut selected_handles = HashSet::<String>::new();
+	// teams are picked by select_teams
+	ranked_vec.retain(|relevance_obj| !is_team_entry(relevance_obj));
+	// required owners skip the threshold and the cap
+	for relevance_obj in &ranked_vec {
+		if is_required(relevance_obj, repo_config) && is_new_handle(relevance_obj, &mut selected_handles) {
+			selected_vec.push(relevance_obj.to_owned());
+		}
+	}
+	for relevance_obj in &ranked_vec {
+		if is_required(relevance_obj, repo_config) {
+			continue;
+		}
+		// entries without a handle can't be requested, so they don't take a slot
+		if relevance_obj.handles().is_none() {
+			continue;
+		}
+		if let Some(max_reviewers) = repo_config.max_reviewers() {
+			if selected_vec.len() >= max_reviewers {
+				break;
+			}
+		}
+		if let Some(min_relevance) = repo_config.min_relevance() {
+			if relevance_obj.relevance_num() < min_relevance {
+				log::debug!("[select_reviewers] {} below minimum relevance {}", relevance_obj.git_alias(), min_relevance);
+				break;
+			}
+		}
+		if !is_new_handle(relevance_obj, &mut selected_handles) {
+			log::debug!("[select_reviewers] {} maps to an already selected handle", relevance_obj.git_alias());
+			continue;
+		}
+		if let Some(provider) = &provider_opt {
+			if repo_config.load_balancing()
+				&& is_handle_overloaded(provider.as_ref(), relevance_obj, review, repo_config, access_token).await {
+				continue;
+			}
+		}
+		selected_vec.push(relevance_obj.to_owned());
+	}
+	selected_vec
+}
+
+// Aliases of one person map to the same handles, they count as one reviewer
+fn is_new_handle(relevance_obj: &Relevance, selected_handles: &mut HashSet<String>) -> bool {
+	let handles = relevance_obj.handles().to_owned().unwrap_or_default();
+	if handles.is_empty() {
+		return true;
+	}
+	let is_new = handles.iter().all(|handle| !selected_handles.contains(&handle.to_lowercase()));
+	for handle in handles {
+		selected_handles.insert(handle.to_lowercase());
+	}
+	is_new
+}
+
+fn is_required(relevance_obj: &Relevance, repo_config: &RepoConfig) -> bool {
+	repo_config.relevance_mode() == &RelevanceMode::OwnersRequired
+		&& relevance_obj.source() == &RelevanceSource::Codeowners
+}
+
+async fn is_handle_overloaded(provider: &dyn RepoProvider, relevance_obj: &Relevance, review: &Review,
+	repo_config: &RepoConfig, access_token: &str) -> bool {
+	let handle_opt = relevance_obj.handles().as_ref()
+		.and_then(|handles| handles.first().cloned());
+	if handle_opt.is_none() {
+		return false;
+	}
+	let handle = handle_opt.expect("Empty handle_opt");
+	let count_opt = provider.open_review_count(&handle, review, access_token).await;
+	if count_opt.is_none() {
+		return false;
+	}
+	let count = count_opt.expect("Empty count_opt");
+	if count >= repo_config.max_open_reviews() {
+		log::info!("Skipping {} for review, {} open review requests...", &handle, count);
+		return true;
+	}
+	false
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::relevance::test_relevance;
+	use crate::utils::repo_config::test_repo_config;
+	use crate::utils::review::test_review;
+
+	fn selected_aliases(selected_vec: &[Relevance]) -> Vec<String> {
+		selected_vec.iter().map(|relevance_obj| relevance_obj.git_alias().to_owned()).collect()
+	}
+
+	#[tokio::test]
+	async fn picks_most_relevant_up_to_the_cap() {
+		let relevance_vec = vec![test_relevance("c", 10.0, &["c"]), test_relevance("a", 50.0, &["a"]),
+			test_relevance("unmapped", 30.0, &[]), test_relevance("b", 20.0, &["b"])];
+		let config = test_repo_config(serde_json::json!({"max_reviewers": 2}));
+		let selected_vec = select_reviewers(&relevance_vec, &test_review("1", "head"), &config, "token").await;
+		assert_eq!(selected_aliases(&selected_vec), vec!["a", "b"]);
+	}
+
+	#[tokio::test]
+	async fn stops_below_minimum_relevance() {
+		let relevance_vec = vec![test_relevance("a", 50.0, &["a"]), test_relevance("b", 9.9, &["b"])];
+		let config = test_repo_config(serde_json::json!({"min_relevance": 10.0}));
+		let selected_vec = select_reviewers(&relevance_vec, &test_review("1", "head"), &config, "token").await;
+		assert_eq!(selected_aliases(&selected_vec), vec!["a"]);
+	}
+
+	#[tokio::test]
+	async fn aliases_of_one_handle_take_one_slot() {
+		let relevance_vec = vec![test_relevance("a@work.com", 40.0, &["Alice"]), test_relevance("a@home.com", 30.0, &["alice"]),
+			test_relevance("b", 20.0, &["bob"])];
+		let config = test_repo_config(serde_json::json!({"max_reviewers": 2}));
+		let selected_vec = select_reviewers(&relevance_vec, &test_review("1", "head"), &config, "token").await;
+		assert_eq!(selected_aliases(&selected_vec), vec!["a@work.com", "b"]);
+	}
+
+	#[tokio::test]
+	async fn required_owners_skip_threshold_and_cap() {
+		let owner = Relevance::new("github".to_string(), "owner".to_string(), "1.00".to_string(), 1.0,
+			Some(vec!["owner".to_string()]), RelevanceSource::Codeowners);
+		let relevance_vec = vec![test_relevance("a", 60.0, &["a"]), owner, test_relevance("b", 39.0, &["b"])];
+		let config = test_repo_config(serde_json::json!({"relevance_mode": "owners_required", "max_reviewers": 2, "min_relevance": 5.0}));
+		let selected_vec = select_reviewers(&relevance_vec, &test_review("1", "head"), &config, "token").await;
+		assert_eq!(selected_aliases(&selected_vec), vec!["owner", "a"]);
+	}
+}
@@ -1 +1,3 @@
-pub mod setup;
\ No newline at end of file
+pub mod setup;
+pub mod provider;
+pub mod reviewer;
\ No newline at end of file
@@ -0,0 +1,204 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::bitbucket;
+use crate::bitbucket::config::bitbucket_web_url;
+use crate::core::approval::process_approval;
+use crate::core::provider::{RepoProvider, ReviewerSelection};
+use crate::core::push::{bitbucket_pushed_branches, process_push};
+use crate::core::review::create_and_save_bitbucket_review_object;
+use crate::db::prs::bitbucket_process_and_update_pr_if_different;
+use crate::db::purge::delete_key;
+use crate::db::user::add_bitbucket_user_to_workspace_user;
+use crate::utils::hunk::BlameItem;
+use crate::utils::pr_info::PrInfo;
+use crate::utils::review::Review;
+use crate::utils::user::ProviderEnum;
+
+use super::{reviewer, setup};
+
+pub struct BitbucketProvider;
+
+#[async_trait]
+impl RepoProvider for BitbucketProvider {
+	async fn access_token(&self, review: &Option<Review>) -> Option<String> {
+		return bitbucket::auth::refresh_git_auth(review).await;
+	}
+
+	fn clone_url(&self, git_url: &str, access_token: &str) -> Option<String> {
+		Some(git_url.to_string()
+			.replace("git@", format!("https://x-token-auth:{{{access_token}}}@").as_str())
+			.replace("bitbucket.org:", "bitbucket.org/"))
+	}
+
+	async fn pr_info(&self, repo_owner: &str, repo_name: &str, pr_number: &str, access_token: &str) -> Option<PrInfo> {
+		return bitbucket::prs::get_pr_info(repo_owner, repo_name, access_token, pr_number).await;
+	}
+
+	async fn reconcile_webhooks(&self, repo_owner: &str, repo_name: &str, access_token: &str) {
+		setup::process_webhooks(repo_owner.to_string(), repo_name.to_string(), access_token.to_string()).await;
+	}
+
+	// Bitbucket handles are display names, the user is saved so the handle maps back to a uuid
+	async fn commit_author_handle(&self, repo_owner: &str, repo_name: &str, commit: &str, _access_token: &str) -> Option<String> {
+		let author_opt = bitbucket::user::author_from_commit(commit, repo_name, repo_owner).await;
+		if author_opt.is_none() {
+			log::debug!("[commit_author_handle] No bitbucket user found for commit {}", commit);
+			return None;
+		}
+		let author = author_opt.expect("Empty author_opt");
+		let handle = author.display_name().to_owned();
+		add_bitbucket_user_to_workspace_user(author);
+		return Some(handle);
+	}
+
+	// CODEOWNERS name users by nickname, relevance goes by display name
+	async fn owner_handle(&self, username: &str, review: &Review) -> Option<String> {
+		let access_token_opt = self.repo_access_token(review.repo_owner(), review.repo_name()).await;
+		if access_token_opt.is_none() {
+			log::error!("[owner_handle] No access token for {}/{}", review.repo_owner(), review.repo_name());
+			return None;
+		}
+		let access_token = access_token_opt.expect("Empty access_token_opt");
+		let member_opt = bitbucket::user::workspace_member_by_nickname(review.repo_owner(), username, &access_token).await;
+		if member_opt.is_none() {
+			log::debug!("[owner_handle] No member of {} with nickname {}", review.repo_owner(), username);
+			return None;
+		}
+		let member = member_opt.expect("Empty member_opt");
+		let handle = member.display_name().to_owned();
+		add_bitbucket_user_to_workspace_user(member);
+		return Some(handle);
+	}
+
+	async fn remove_webhooks(&self, repo_owner: &str, repo_name: &str, access_token: &str) {
+		setup::remove_webhooks(repo_owner, repo_name, access_token).await;
+	}
+
+	async fn handle_install(&self, installation_code: &str) {
+		setup::handle_install_bitbucket(installation_code).await;
+	}
+
+	fn purge_auth(&self) {
+		delete_key("bitbucket_auth_info");
+	}
+
+	async fn process_webhook(&self, deserialised_msg_data: &Value) -> bool {
+		let repo_provider = ProviderEnum::Bitbucket.to_string();
+		let workspace_slug = deserialised_msg_data["eventPayload"]["repository"]["workspace"]["slug"].to_string().trim_matches('"').to_string();
+		let repo_slug = deserialised_msg_data["eventPayload"]["repository"]["name"].to_string().trim_matches('"').to_string();
+		let pr_number = deserialised_msg_data["eventPayload"]["pullrequest"]["id"].to_string().trim_matches('"').to_string();
+		let event_type = deserialised_msg_data["eventType"].to_string().trim_matches('"').to_string();
+		if event_type == "repo:push" {
+			let pushed_branches = bitbucket_pushed_branches(&deserialised_msg_data["eventPayload"]);
+			process_push(&repo_provider, &workspace_slug, &repo_slug, pushed_branches);
+			return false;
+		}
+		let if_process_pr = bitbucket_process_and_update_pr_if_different(&deserialised_msg_data["eventPayload"],
+			&workspace_slug, &repo_slug, &pr_number, &repo_provider).await;
+		if event_type == "pullrequest:approved" {
+			log::info!("Processing Approved PR event...");
+			process_approval(&deserialised_msg_data["eventPayload"], &deserialised_msg_data["repoConfig"],
+				&workspace_slug, &repo_slug, &pr_number, &repo_provider).await;
+			return false;
+		}
+		return if_process_pr && (event_type == "pullrequest:created" || event_type == "pullrequest:updated");
+	}
+
+	fn review_from_webhook(&self, deserialized_data: &Value) -> (Option<Review>, Option<Review>) {
+		create_and_save_bitbucket_review_object(deserialized_data)
+	}
+
+	async fn process_pat_repos(&self, _message_data: &[u8]) {
+		log::error!("[process_pat_repos] PAT setup is not supported on Bitbucket Cloud, install the OAuth app instead");
+	}
+
+	async fn add_comment(&self, comment_text: &str, review: &Review, access_token: &str) -> Option<String> {
+		return bitbucket::comment::add_comment(comment_text, review, access_token).await;
+	}
+
+	async fn edit_comment(&self, comment_id: &str, comment_text: &str, review: &Review, access_token: &str) -> Option<bool> {
+		return bitbucket::comment::edit_comment(comment_id, comment_text, review, access_token).await;
+	}
+
+	async fn has_repo_access(&self, handle: &str, review: &Review, access_token: &str) -> Option<bool> {
+		let mut access_opt = None;
+		for user in reviewer::workspace_users(handle) {
+			let member_opt = bitbucket::user::is_workspace_member(review.repo_owner(), user.uuid(), access_token).await;
+			if member_opt.is_some() {
+				access_opt = member_opt;
+			}
+			if member_opt == Some(true) {
+				break;
+			}
+		}
+		return access_opt;
+	}
+
+	// Groups live in the workspace, `teams/group` and `group` both resolve to it
+	// Display names aren't unique, members are matched on the uuids of a handle's workspace users
+	fn member_ids(&self, handle: &str) -> Vec<String> {
+		reviewer::workspace_users(handle).iter()
+			.map(|user| user.uuid().to_owned())
+			.collect()
+	}
+
+	async fn team_members(&self, _team_owner: &str, team_slug: &str, review: &Review, access_token: &str) -> Option<Vec<String>> {
+		let members_opt = bitbucket::user::get_group_members(review.repo_owner(), team_slug, access_token).await;
+		if members_opt.is_none() {
+			log::error!("[team_members] Unable to get members of group {} in {}", team_slug, review.repo_owner());
+			return None;
+		}
+		let member_ids = members_opt.expect("Empty members_opt").iter()
+			.map(|user| user.uuid().to_owned())
+			.collect();
+		return Some(member_ids);
+	}
+
+	// A handle can map to several accounts, it counts as available if any of them is
+	async fn open_review_count(&self, handle: &str, review: &Review, access_token: &str) -> Option<u64> {
+		let mut count_opt: Option<u64> = None;
+		for user in reviewer::workspace_users(handle) {
+			let user_count_opt = bitbucket::prs::open_review_requests_count(review.repo_owner(),
+				review.repo_name(), user.uuid(), access_token).await;
+			if let Some(user_count) = user_count_opt {
+				count_opt = Some(count_opt.map_or(user_count, |count| count.min(user_count)));
+			}
+		}
+		return count_opt;
+	}
+
+	async fn add_reviewers(&self, selection: &ReviewerSelection<'_>, review: &Review, access_token: &str) {
+		reviewer::add_reviewers(selection, review, access_token).await;
+	}
+
+	async fn approvals(&self, review: &Review, access_token: &str) -> Option<Vec<(Vec<String>, Option<String>)>> {
+		let approvers_opt = bitbucket::prs::pr_approvers(review.repo_owner(), review.repo_name(),
+			review.id(), access_token).await;
+		return approvers_opt.map(|approvers| approvers.into_iter()
+			.map(|handles| (handles, None))
+			.collect());
+	}
+
+	async fn set_coverage_status(&self, review: &Review, passed: bool, description: &str, access_token: &str) {
+		let state = if passed { "SUCCESSFUL" } else { "FAILED" };
+		let pr_url = format!("{}/{}/{}/pull-requests/{}", bitbucket_web_url(),
+			review.repo_owner(), review.repo_name(), review.id());
+		bitbucket::status::set_build_status(review.repo_owner(), review.repo_name(),
+			review.pr_head_commit(), state, description, &pr_url, access_token).await;
+	}
+
+	fn diff_url(&self, review: &Review, filepath: &str, _blame: &BlameItem, range_opt: Option<(usize, usize)>) -> String {
+		let diff_url = format!("{}/{}/{}/pull-requests/{}/diff", bitbucket_web_url(),
+			review.repo_owner(), review.repo_name(), review.id());
+		if let Some((start, _)) = range_opt {
+			return format!("{}#L{}F{}", diff_url, filepath, start);
+		}
+		format!("{}#chg-{}", diff_url, filepath)
+	}
+
+	// Bitbucket strips html, comments get a plain list instead
+	fn renders_html(&self) -> bool {
+		false
+	}
+}
@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+
+use crate::bitbucket;
+use crate::bitbucket::user::{author_from_commit, get_group_members, is_workspace_member};
+use crate::core::eligibility::exclusion_reason;
+use crate::core::provider::ReviewerSelection;
+use crate::db::user::get_workspace_user_from_db;
+use crate::utils::relevance::{Relevance, RelevanceSource};
+use crate::utils::review::Review;
+use crate::utils::user::BitbucketUser;
+
+// Bitbucket can't request a group, so groups are expanded to their members
+pub async fn add_reviewers(selection: &ReviewerSelection<'_>, review: &Review, access_token: &str) {
+	let mut uuid_set = HashSet::<String>::new();
+	uuid_set.insert(selection.prhunk.author().to_owned());
+	for team_reviewer in selection.teams {
+		log::info!("Assigning members of group {} with {:.2}% relevance...",
+			team_reviewer.team(), team_reviewer.relevance_num());
+		let members_opt = get_group_members(review.repo_owner(), team_reviewer.slug(), access_token).await;
+		if members_opt.is_none() {
+			continue;
+		}
+		for user in members_opt.expect("Empty members_opt") {
+			add_reviewer_once(&user, &mut uuid_set, selection, review, access_token).await;
+		}
+	}
+	for relevance_obj in selection.relevance_vec {
+		for user in relevance_users(relevance_obj, selection, review).await {
+			add_reviewer_once(&user, &mut uuid_set, selection, review, access_token).await;
+		}
+	}
+}
+
+async fn add_reviewer_once(user: &BitbucketUser, uuid_set: &mut HashSet<String>,
+	selection: &ReviewerSelection<'_>, review: &Review, access_token: &str) {
+	if !uuid_set.insert(user.uuid().to_owned()) {
+		return;
+	}
+	if !is_eligible_user(user, selection, review, access_token).await {
+		return;
+	}
+	bitbucket::reviewer::add_reviewers(user, review, access_token).await;
+}
+
+async fn is_eligible_user(user: &BitbucketUser, selection: &ReviewerSelection<'_>,
+	review: &Review, access_token: &str) -> bool {
+	let identities = vec![user.uuid().to_owned(), user.display_name().to_owned(), user.nickname().to_owned()];
+	if let Some(reason) = exclusion_reason(&identities, review, selection.repo_config) {
+		log::info!("Not assigning {}: {}", user.display_name(), reason);
+		return false;
+	}
+	let member_opt = is_workspace_member(review.repo_owner(), user.uuid(), access_token).await;
+	if member_opt == Some(false) {
+		log::info!("Not assigning {}: no access to {}", user.display_name(), review.repo_owner());
+		return false;
+	}
+	true
+}
+
+// Blame authors are resolved from one of their commits, everyone else from their handles
+async fn relevance_users(relevance_obj: &Relevance, selection: &ReviewerSelection<'_>, review: &Review) -> Vec<BitbucketUser> {
+	let mut users = Vec::<BitbucketUser>::new();
+	if relevance_obj.source() == &RelevanceSource::Blame {
+		let blame_opt = selection.prhunk.blamevec().iter()
+			.find(|blame| blame.author() == relevance_obj.git_alias());
+		if let Some(blame) = blame_opt {
+			let blame_author_opt = author_from_commit(blame.commit(),
+				review.repo_name(), review.repo_owner()).await;
+			if let Some(blame_author) = blame_author_opt {
+				users.push(blame_author);
+			} else {
+				log::error!("[relevance_users] Unable to get blame author from bb for commit: {}", &blame.commit());
+			}
+		}
+	} else if let Some(handles) = relevance_obj.handles() {
+		for handle in handles {
+			users.extend(workspace_users(handle));
+		}
+	}
+	users
+}
+
+pub fn workspace_users(handle: &str) -> Vec<BitbucketUser> {
+	let workspace_user_opt = get_workspace_user_from_db(handle);
+	if workspace_user_opt.is_none() {
+		log::debug!("[workspace_users] No workspace user found for {}", handle);
+		return Vec::new();
+	}
+	let workspace_user = workspace_user_opt.expect("Empty workspace_user_opt");
+	workspace_user.users().iter().cloned().collect()
+}
@@ -1,17 +1,18 @@
-use std::env;
 use std::str;
 use tokio::task;
 
 use crate::bitbucket::auth::get_access_token_from_bitbucket;
 use crate::bitbucket::repo::get_workspace_repos;
 use crate::bitbucket::workspace::get_bitbucket_workspaces;
-use crate::bitbucket::webhook::{get_webhooks_in_repo, add_webhook};
+use crate::bitbucket::webhook::{add_webhook, delete_webhook, get_webhooks_in_repo, update_webhook, WEBHOOK_CALLBACK_PATH, WEBHOOK_EVENTS};
+use crate::core::webhooks::{our_hook_ids, plan_reconcile, DesiredHook, HookAction, HookState};
+use crate::utils::bitbucket_webhook::Webhook;
 use crate::bitbucket::user::get_and_save_workspace_users;
 use crate::bitbucket::prs::{list_prs_bitbucket, get_and_store_pr_info};
+use crate::core::ownership::index_repo_ownership;
 use crate::core::utils::send_aliases;
 use crate::core::utils::user_selected_repos;
 use crate::core::utils::UserSelectedRepo;
-use crate::db::webhook::save_webhook_to_db;
 use crate::utils::gitops::get_git_aliases;
 use crate::utils::repo::Repository;
 use crate::utils::setup_info::SetupInfo;
@@ -50,6 +51,7 @@ pub async fn handle_install_bitbucket(installation_code: &str) {
 			let token_copy = access_token.clone();
 			let mut repo_copy = repo.clone();
 			clone_git_repo(&mut repo_copy, &token_copy, &repo_provider).await;
+			index_repo_ownership(&repo_copy);
 			let aliases_opt = get_git_aliases(&repo_copy);
 			if aliases_opt.is_none() {
 				log::error!("[handle_install_bitbucket] No aliases for repo {}", repo.name());
@@ -72,16 +74,16 @@ pub async fn handle_install_bitbucket(installation_code: &str) {
 			task::spawn(async move {
 				let pr_list_opt = list_prs_bitbucket(&workspace_slug_async, &repo_name_async, &access_token_async, "OPEN").await;
 				if pr_list_opt.is_none() {
-					log::debug!("[handle_install_bitbucket] No open pull requests found for processing.");
+					log::error!("[handle_install_bitbucket] Unable to list open pull requests of {}/{}", &workspace_slug_async, &repo_name_async);
 					return;
 				}
 				let pr_list = pr_list_opt.expect("Empty pr_list_opt");
 				// We can concurrently process each PR with tokio::spawn.
-				for pr_id in pr_list.iter() {
+				for pr in pr_list.iter() {
 					let workspace_slug_async = workspace_slug_async.clone(); //Instead of cloning each time, I could have used ARC but not sure what is the best way.
 					let repo_name_async = repo_name_async.clone();
 					let access_token_async = access_token_async.clone();
-					let pr_id_async = pr_id.clone();
+					let pr_id_async = pr.number.clone();
 					task::spawn(async move {
 						get_and_store_pr_info(&workspace_slug_async, &repo_name_async, &access_token_async, &pr_id_async).await;
 					});
@@ -112,42 +114,46 @@ fn filter_user_selected_repos(workspace_slug: String, user_selected_repos_opt: O
 	return Some(filtered_repos);
 }
 
-async fn process_webhooks(workspace_slug: String, repo_name: String, access_token: String) {
+pub async fn process_webhooks(workspace_slug: String, repo_name: String, access_token: String) {
 	log::info!("Setting up webhooks for {}/{}...", &workspace_slug, repo_name);
-	let webhooks_data = get_webhooks_in_repo(&workspace_slug, &repo_name, &access_token).await;
-	let webhook_callback_url = format!("{}/api/bitbucket/callbacks/webhook", 
-		env::var("SERVER_URL").expect("SERVER_URL must be set"));
-	if webhooks_data.is_empty() {
-		let repo_name_async = repo_name.clone();
-		let workspace_slug_async = workspace_slug.clone();
-		let access_token_async = access_token.clone();
-		task::spawn(async move {
-			log::info!("Adding new webhook for {}/{}...",
-				&workspace_slug_async, access_token_async);
-			add_webhook(
-				&workspace_slug_async, 
-				&repo_name_async, 
-				&access_token_async).await;
-		});
+	let webhooks_data_opt = get_webhooks_in_repo(&workspace_slug, &repo_name, &access_token).await;
+	if webhooks_data_opt.is_none() {
+		log::error!("[process_webhooks] Unable to get webhooks for repo: {}/{}, skipping reconciliation",
+			&workspace_slug, &repo_name);
 		return;
 	}
-	let matching_webhook = webhooks_data.into_iter()
-		.find(|w| w.url().to_string() == webhook_callback_url);
-	if matching_webhook.is_none() {
-		let repo_name_async = repo_name.clone();
-		let workspace_slug_async = workspace_slug.clone();
-		let access_token_async = access_token.clone();
-		task::spawn(async move {
-			log::info!("Adding new webhook for {}/{}...",
-				&workspace_slug_async, access_token_async);
-			add_webhook(
-				&workspace_slug_async, 
-				&repo_name_async, 
-				&access_token_async).await;
-		});
+	let webhooks_data = webhooks_data_opt.expect("Empty webhooks_data_opt");
+	let desired = DesiredHook::new(WEBHOOK_CALLBACK_PATH, &WEBHOOK_EVENTS);
+	let hook_states = hook_states(&webhooks_data);
+	let actions = plan_reconcile(&desired, &hook_states);
+	log::debug!("[process_webhooks] actions for {}/{} = {:?}", &workspace_slug, &repo_name, &actions);
+	for action in actions {
+		match action {
+			HookAction::Create => add_webhook(&workspace_slug, &repo_name, &access_token, &desired).await,
+			HookAction::Update(hook_uuid) => update_webhook(&workspace_slug, &repo_name, &hook_uuid, &access_token, &desired).await,
+			HookAction::Delete(hook_uuid) => delete_webhook(&workspace_slug, &repo_name, &hook_uuid, &access_token).await,
+		}
+	}
+}
+
+pub async fn remove_webhooks(workspace_slug: &str, repo_name: &str, access_token: &str) {
+	let webhooks_data_opt = get_webhooks_in_repo(workspace_slug, repo_name, access_token).await;
+	if webhooks_data_opt.is_none() {
+		log::error!("[remove_webhooks] Unable to get webhooks for repo: {}/{}", workspace_slug, repo_name);
 		return;
 	}
-	let webhook = matching_webhook.expect("no matching webhook");
-	log::info!("Not adding webhook, already exists: {:?}...", webhook.url());
-	save_webhook_to_db(&webhook);
+	let hook_states = hook_states(&webhooks_data_opt.expect("Empty webhooks_data_opt"));
+	for hook_uuid in our_hook_ids(WEBHOOK_CALLBACK_PATH, &hook_states) {
+		delete_webhook(workspace_slug, repo_name, &hook_uuid, access_token).await;
+	}
+}
+
+fn hook_states(webhooks_data: &[Webhook]) -> Vec<HookState> {
+	webhooks_data.iter().map(|webhook| HookState {
+		id: webhook.uuid().to_string(),
+		url: webhook.url().to_string(),
+		events: webhook.events().to_owned(),
+		active: webhook.active(),
+		has_secret: webhook.secret_set(),
+	}).collect()
 }
\ No newline at end of file
@@ -0,0 +1,2 @@
+pub mod setup;
+pub mod provider;
\ No newline at end of file
@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::bitbucket_server;
+use crate::bitbucket_server::config::pr_web_url;
+use crate::core::provider::{first_handles, RepoProvider, ReviewerSelection};
+use crate::core::review::create_and_save_bitbucket_server_review_object;
+use crate::db::prs::bitbucket_server_process_and_update_pr_if_different;
+use crate::utils::hunk::BlameItem;
+use crate::utils::pr_info::PrInfo;
+use crate::utils::review::Review;
+use crate::utils::user::ProviderEnum;
+
+use super::setup;
+
+pub struct BitbucketServerProvider;
+
+#[async_trait]
+impl RepoProvider for BitbucketServerProvider {
+	async fn access_token(&self, _review: &Option<Review>) -> Option<String> {
+		return bitbucket_server::auth::bitbucket_server_access_token();
+	}
+
+	// http clone links, repository and project tokens authenticate as x-token-auth
+	fn clone_url(&self, git_url: &str, access_token: &str) -> Option<String> {
+		git_url.split_once("://")
+			.map(|(scheme, host_path)| format!("{scheme}://x-token-auth:{access_token}@{host_path}"))
+	}
+
+	async fn pr_info(&self, repo_owner: &str, repo_name: &str, pr_number: &str, access_token: &str) -> Option<PrInfo> {
+		return bitbucket_server::prs::get_pr_json(repo_owner, repo_name, access_token, pr_number).await
+			.and_then(|pr_data| bitbucket_server::prs::parse_pr_info(&pr_data));
+	}
+
+	async fn process_webhook(&self, deserialised_msg_data: &Value) -> bool {
+		// project key and repo slug identify the repo, as in the rest api
+		let project_key = deserialised_msg_data["eventPayload"]["pullRequest"]["toRef"]["repository"]["project"]["key"].to_string().trim_matches('"').to_string();
+		let repo_slug = deserialised_msg_data["eventPayload"]["pullRequest"]["toRef"]["repository"]["slug"].to_string().trim_matches('"').to_string();
+		let pr_id = deserialised_msg_data["eventPayload"]["pullRequest"]["id"].to_string().trim_matches('"').to_string();
+		log::debug!("[process_webhook] {}, {}, {}", project_key, repo_slug, pr_id);
+		return bitbucket_server_process_and_update_pr_if_different(&deserialised_msg_data["eventPayload"],
+			&deserialised_msg_data["repoConfig"], &project_key, &repo_slug, &pr_id,
+			&ProviderEnum::BitbucketServer.to_string()).await;
+	}
+
+	fn review_from_webhook(&self, deserialized_data: &Value) -> (Option<Review>, Option<Review>) {
+		create_and_save_bitbucket_server_review_object(deserialized_data)
+	}
+
+	async fn process_pat_repos(&self, message_data: &[u8]) {
+		setup::process_pat_repos(message_data).await;
+	}
+
+	async fn add_comment(&self, comment_text: &str, review: &Review, access_token: &str) -> Option<String> {
+		return bitbucket_server::comment::add_comment(comment_text, review, access_token).await;
+	}
+
+	async fn edit_comment(&self, comment_id: &str, comment_text: &str, review: &Review, access_token: &str) -> Option<bool> {
+		return bitbucket_server::comment::edit_comment(comment_id, comment_text, review, access_token).await;
+	}
+
+	async fn add_reviewers(&self, selection: &ReviewerSelection<'_>, review: &Review, access_token: &str) {
+		let user_slugs = first_handles(selection.relevance_vec);
+		if user_slugs.is_empty() {
+			return;
+		}
+		bitbucket_server::reviewer::add_reviewers(&user_slugs, review, access_token).await;
+	}
+
+	async fn approvals(&self, review: &Review, access_token: &str) -> Option<Vec<(Vec<String>, Option<String>)>> {
+		let approvals_opt = bitbucket_server::prs::pr_approvals(review.repo_owner(), review.repo_name(),
+			review.id(), access_token).await;
+		return approvals_opt.map(|approvals| approvals.into_iter()
+			.map(|(user_slug, commit_opt)| (vec![user_slug], commit_opt))
+			.collect());
+	}
+
+	async fn set_coverage_status(&self, review: &Review, passed: bool, description: &str, access_token: &str) {
+		let state = if passed { "SUCCESSFUL" } else { "FAILED" };
+		let pr_url = pr_web_url(review.repo_owner(), review.repo_name(), review.id());
+		bitbucket_server::status::set_build_status(review.pr_head_commit(), state,
+			description, &pr_url, access_token).await;
+	}
+
+	// file anchors only, line anchors aren't stable across Data Center versions
+	fn diff_url(&self, review: &Review, filepath: &str, _blame: &BlameItem, _range_opt: Option<(usize, usize)>) -> String {
+		format!("{}/diff#{}", pr_web_url(review.repo_owner(), review.repo_name(), review.id()), filepath)
+	}
+
+	fn renders_html(&self) -> bool {
+		false
+	}
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::env;
+use tokio::task;
+
+use crate::core::github::setup::parse_pat_repos;
+use crate::core::ownership::index_repo_ownership;
+use crate::core::utils::{send_aliases, send_setup_info};
+use crate::db::webhook::save_webhook_to_db;
+use crate::bitbucket_server::auth::bitbucket_server_access_token;
+use crate::bitbucket_server::prs::{get_and_store_pr_info, list_prs_bitbucket_server};
+use crate::bitbucket_server::repos::get_bitbucket_server_repos;
+use crate::bitbucket_server::webhook::{add_webhook, get_webhooks_in_repo};
+use crate::utils::gitops::{clone_git_repo, get_git_aliases};
+use crate::utils::repo::Repository;
+use crate::utils::setup_info::SetupInfo;
+use crate::utils::user::ProviderEnum;
+
+// Repos picked by the user on Vibinex after setup_self_host_user_repos_bitbucket_server
+pub async fn process_pat_repos(message_data: &[u8]) {
+	let repos_opt = parse_pat_repos(message_data);
+	if repos_opt.is_none() {
+		log::error!("[process_pat_repos] Failed to parse PAT repos data");
+		return;
+	}
+	let access_token_opt = bitbucket_server_access_token();
+	if access_token_opt.is_none() {
+		log::error!("[process_pat_repos] No Bitbucket Data Center access token");
+		return;
+	}
+	let access_token = access_token_opt.expect("Empty access_token_opt");
+	let repos = repos_opt.expect("Empty repos_opt");
+	for repo in repos {
+		process_repo(&repo, &access_token).await;
+	}
+}
+
+async fn process_repo(repo: &Repository, access_token: &str) {
+	let mut repo_copy = repo.clone();
+	clone_git_repo(&mut repo_copy, access_token, &ProviderEnum::BitbucketServer.to_string()).await;
+	index_repo_ownership(&repo_copy);
+	let aliases_opt = get_git_aliases(&repo_copy);
+	if aliases_opt.is_none() {
+		log::error!("[process_repo] Unable to get aliases for repo: {}", repo.name());
+		return;
+	}
+	send_aliases(repo, &aliases_opt.expect("Empty aliases_opt")).await;
+	process_webhooks(repo.owner(), repo.name(), access_token).await;
+	let repo_owner_async = repo.owner().clone();
+	let repo_name_async = repo.name().clone();
+	let access_token_async = access_token.to_string();
+	task::spawn(async move {
+		process_prs(&repo_owner_async, &repo_name_async, &access_token_async).await;
+	});
+}
+
+pub async fn process_webhooks(repo_owner: &str, repo_name: &str, access_token: &str) {
+	log::info!("Processing webhooks for : {}/{}...", repo_owner, repo_name);
+	let webhooks_opt = get_webhooks_in_repo(repo_owner, repo_name, access_token).await;
+	if webhooks_opt.is_none() {
+		log::error!("[process_webhooks] Unable to get webhooks for repo: {}/{}", repo_owner, repo_name);
+		return;
+	}
+	let webhook_callback_url = format!("{}/api/bitbucket-server/callbacks/webhook",
+		env::var("SERVER_URL").expect("SERVER_URL must be set"));
+	let matching_webhook = webhooks_opt.expect("Empty webhooks_opt").into_iter()
+		.find(|w| *w.url() == webhook_callback_url);
+	if matching_webhook.is_none() {
+		add_webhook(repo_owner, repo_name, access_token).await;
+		return;
+	}
+	let webhook = matching_webhook.expect("no matching webhook");
+	log::debug!("[process_webhooks] Webhook already exists: {:?}", &webhook);
+	save_webhook_to_db(&ProviderEnum::BitbucketServer.to_string(), repo_owner, repo_name, webhook.id(), &webhook);
+}
+
+async fn process_prs(repo_owner: &str, repo_name: &str, access_token: &str) {
+	log::info!("Processing all open pull requests...");
+	let pr_list_opt = list_prs_bitbucket_server(repo_owner, repo_name, access_token, "OPEN").await;
+	if pr_list_opt.is_none() {
+		log::warn!("Unable to get any open pull requests for processing.");
+		return;
+	}
+	for pr_number in pr_list_opt.expect("Empty pr_list_opt") {
+		let repo_owner_async = repo_owner.to_string();
+		let repo_name_async = repo_name.to_string();
+		let access_token_async = access_token.to_string();
+		task::spawn(async move {
+			get_and_store_pr_info(&repo_owner_async, &repo_name_async, &access_token_async, &pr_number).await;
+		});
+	}
+}
+
+pub async fn setup_self_host_user_repos_bitbucket_server(access_token: &str) {
+	log::info!("Getting all user's repositories...");
+	let repos_opt = get_bitbucket_server_repos(access_token).await;
+	if repos_opt.is_none() {
+		log::error!("[setup_self_host_user_repos_bitbucket_server] No repositories found for the user");
+		return;
+	}
+	let mut repo_owner_map = HashMap::<String, Vec<String>>::new();
+	for repo in repos_opt.expect("Empty repos_opt") {
+		repo_owner_map.entry(repo.owner().to_string())
+			.or_default()
+			.push(repo.name().to_string());
+	}
+	let pubreqs: Vec<SetupInfo> = repo_owner_map.into_iter()
+		.map(|(owner, repos)| SetupInfo {
+			provider: ProviderEnum::BitbucketServer.to_string(),
+			owner,
+			repos,
+		})
+		.collect();
+	send_setup_info(&pubreqs).await;
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use crate::core::provider::repo_provider;
+use crate::utils::aliases::get_login_handles;
+use crate::utils::codeowners::{is_team_owner, owner_handle, Codeowners, CODEOWNERS_PATHS};
+use crate::utils::gitops::{get_excluded_files, get_file_at_commit};
+use crate::utils::relevance::{Relevance, RelevanceSource};
+use crate::utils::review::Review;
+
+pub fn get_codeowners(review: &Review) -> Option<Codeowners> {
+	for path in CODEOWNERS_PATHS {
+		let content_opt = get_file_at_commit(review.base_head_commit(), path, review.clone_dir());
+		if content_opt.is_none() {
+			continue;
+		}
+		log::debug!("[get_codeowners] Using {} at {}", path, review.base_head_commit());
+		let content = content_opt.expect("Empty content_opt");
+		return Some(Codeowners::parse(&content));
+	}
+	log::debug!("[get_codeowners] No CODEOWNERS file in {} at {}", review.repo_name(), review.base_head_commit());
+	None
+}
+
+// Share of the PR's changed lines covered by each CODEOWNERS owner
+pub async fn codeowners_relevance(review: &Review) -> Vec<Relevance> {
+	let mut relevance_vec = Vec::<Relevance>::new();
+	let codeowners_opt = get_codeowners(review);
+	if codeowners_opt.is_none() {
+		return relevance_vec;
+	}
+	let codeowners = codeowners_opt.expect("Empty codeowners_opt");
+	let fileopt = get_excluded_files(review);
+	if fileopt.is_none() {
+		log::error!("[codeowners_relevance] Unable to get changed files for PR {}", review.id());
+		return relevance_vec;
+	}
+	let (excluded_files, smallfiles) = fileopt.expect("Empty fileopt");
+	let mut owner_lines = HashMap::<String, f32>::new();
+	let mut total = 0.0;
+	for item in excluded_files.iter().chain(smallfiles.iter()) {
+		let owners = codeowners.owners_for_path(item.filepath());
+		let changed_lines = (item.additions() + item.deletions()) as f32;
+		if owners.is_empty() || changed_lines <= 0.0 {
+			continue;
+		}
+		total += changed_lines;
+		// owners of the same file split its lines between them
+		let share = changed_lines / owners.len() as f32;
+		for owner in owners {
+			*owner_lines.entry(owner).or_insert(0.0) += share;
+		}
+	}
+	if total <= 0.0 {
+		return relevance_vec;
+	}
+	for (owner, lines) in owner_lines {
+		let relevance = lines / total * 100.0;
+		let handles_opt = owner_login_handles(&owner, review).await;
+		relevance_vec.push(Relevance::new(
+			review.provider().to_owned(),
+			owner,
+			format!("{:.2}", relevance),
+			relevance,
+			handles_opt,
+			RelevanceSource::Codeowners,
+		));
+	}
+	relevance_vec
+}
+
+async fn owner_login_handles(owner: &str, review: &Review) -> Option<Vec<String>> {
+	if is_team_owner(owner) {
+		let team_opt = owner_handle(owner);
+		return team_opt.map(|team| vec![team.trim_start_matches('@').to_string()]);
+	}
+	if let Some(handle) = owner_handle(owner) {
+		return provider_owner_handle(&handle, review).await
+			.map(|handle| vec![handle]);
+	}
+	// email owners are looked up like git aliases
+	return get_login_handles(owner, review).await;
+}
+
+async fn provider_owner_handle(username: &str, review: &Review) -> Option<String> {
+	let provider = repo_provider(review.provider())?;
+	provider.owner_handle(username, review).await
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use crate::utils::comment_template::{render_template, CommentTemplateKind};
+use crate::utils::gitops::{get_excluded_files, get_file_at_commit};
+use crate::utils::repo_config::RepoConfig;
+use crate::utils::review::Review;
+
+// Template from the repo config, else from the repo at the base commit so a PR can't change its own comment
+pub fn load_comment_template(review: &Review, repo_config: &RepoConfig, kind: &CommentTemplateKind) -> Option<String> {
+	let config_template_opt = match kind {
+		CommentTemplateKind::Relevance => repo_config.relevance_comment_template(),
+		CommentTemplateKind::Approval => repo_config.approval_comment_template(),
+	};
+	if let Some(config_template) = config_template_opt {
+		return Some(config_template.to_owned());
+	}
+	get_file_at_commit(review.base_head_commit(), kind.file_path(), review.clone_dir())
+}
+
+// Same variables for both comments, so one template can render either
+pub struct CommentVars {
+	pub reviewer_table: String,
+	pub reviewer_details: String,
+	pub approvals: String,
+	pub coverage: String,
+	pub unmapped_aliases_count: usize,
+	pub auto_assign: bool,
+}
+
+pub fn unmapped_aliases_text(unmapped_aliases_count: usize) -> String {
+	if unmapped_aliases_count == 0 {
+		return String::new();
+	}
+	format!("Missing profile handles for {} aliases. [Go to your Vibinex settings page](https://vibinex.com/settings) to map aliases to profile handles.", unmapped_aliases_count)
+}
+
+pub fn auto_assign_text(auto_assign: bool) -> String {
+	if !auto_assign {
+		return String::new();
+	}
+	"Auto assigning to relevant reviewers.".to_string()
+}
+
+// None if the template renders to nothing, callers then post the default comment
+pub fn render_comment(template: &str, comment_vars: &CommentVars, review: &Review) -> Option<String> {
+	let mut vars = HashMap::<String, String>::new();
+	vars.insert("reviewer_table".to_string(), comment_vars.reviewer_table.to_owned());
+	vars.insert("reviewer_details".to_string(), comment_vars.reviewer_details.to_owned());
+	vars.insert("approvals".to_string(), comment_vars.approvals.to_owned());
+	vars.insert("coverage".to_string(), comment_vars.coverage.to_owned());
+	vars.insert("unmapped_aliases".to_string(), unmapped_aliases_text(comment_vars.unmapped_aliases_count));
+	vars.insert("unmapped_aliases_count".to_string(), comment_vars.unmapped_aliases_count.to_string());
+	vars.insert("auto_assign".to_string(), auto_assign_text(comment_vars.auto_assign));
+	vars.insert("commit".to_string(), review.pr_head_commit().to_owned());
+	vars.insert("pr_number".to_string(), review.id().to_owned());
+	// needs another git diff, only computed when asked for
+	if template.contains("excluded_files") {
+		vars.insert("excluded_files".to_string(), excluded_files_text(review));
+	}
+	let rendered = render_template(template, &vars);
+	if rendered.trim().is_empty() {
+		log::warn!("[render_comment] Comment template for PR {} rendered empty, using the default comment", review.id());
+		return None;
+	}
+	Some(rendered)
+}
+
+// Comment posted without a template, the same for relevance and approval updates
+pub fn default_comment(comment_vars: &CommentVars) -> String {
+	let mut comment = "Relevant users for this PR:\n\n".to_string();  // Added two newlines
+	comment += &comment_vars.reviewer_table;
+	if !comment_vars.reviewer_details.is_empty() {
+		comment += "\n\nWhy these reviewers:\n\n";
+		comment += &comment_vars.reviewer_details;
+	}
+	if !comment_vars.approvals.is_empty() {
+		comment += "\n";
+		comment += &comment_vars.approvals;
+	}
+	let unmapped_aliases_text = unmapped_aliases_text(comment_vars.unmapped_aliases_count);
+	if !unmapped_aliases_text.is_empty() {
+		comment += "\n\n";
+		comment += &unmapped_aliases_text;
+	}
+	let auto_assign_text = auto_assign_text(comment_vars.auto_assign);
+	if !auto_assign_text.is_empty() {
+		comment += "\n\n";
+		comment += &auto_assign_text;
+	}
+	comment += "\n\n";
+	comment += "If you are a relevant reviewer, you can use the [Vibinex browser extension](https://chromewebstore.google.com/detail/vibinex-code-review/jafgelpkkkopeaefadkdjcmnicgpcncc) to see parts of the PR relevant to you\n";  // Added a newline at the end
+	comment += "Relevance of the reviewer is calculated based on the git blame information of the PR, along with file and directory ownership for new files and small changes. To know more, hit us up at contact@vibinex.com.\n\n";  // Added two newlines
+	comment += "To change comment and auto-assign settings, go to [your Vibinex settings page.](https://vibinex.com/u)\n";  // Added a newline at the end
+	comment
+}
+
+fn excluded_files_text(review: &Review) -> String {
+	let fileopt = get_excluded_files(review);
+	if fileopt.is_none() {
+		log::error!("[excluded_files_text] Unable to get excluded files for PR {}", review.id());
+		return String::new();
+	}
+	let (excluded_files, _) = fileopt.expect("Empty fileopt");
+	excluded_files.iter()
+		.map(|item| format!("- `{}`\n", item.filepath()))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::review::test_review;
+
+	fn test_vars() -> CommentVars {
+		CommentVars {
+			reviewer_table: "| a | 100% |\n".to_string(),
+			reviewer_details: String::new(),
+			approvals: String::new(),
+			coverage: "75.00%".to_string(),
+			unmapped_aliases_count: 2,
+			auto_assign: false,
+		}
+	}
+
+	#[test]
+	fn renders_shared_variables() {
+		let rendered = render_comment("PR {{pr_number}} at {{ commit }}: {{coverage}}, {{unmapped_aliases_count}} unmapped",
+			&test_vars(), &test_review("42", "abc1234"));
+		assert_eq!(rendered, Some("PR 42 at abc1234: 75.00%, 2 unmapped".to_string()));
+	}
+
+	#[test]
+	fn empty_render_falls_back_to_default() {
+		assert_eq!(render_comment("{{ approvals }}\n{{auto_assign}}  \n", &test_vars(), &test_review("42", "abc1234")), None);
+		assert_eq!(render_comment("", &test_vars(), &test_review("42", "abc1234")), None);
+	}
+
+	#[test]
+	fn default_comment_skips_empty_sections() {
+		let comment = default_comment(&test_vars());
+		assert!(comment.starts_with("Relevant users for this PR:\n\n| a | 100% |\n"));
+		assert!(!comment.contains("Why these reviewers"));
+		assert!(comment.contains("Missing profile handles for 2 aliases."));
+		assert!(!comment.contains("Auto assigning"));
+		assert!(comment.ends_with("[your Vibinex settings page.](https://vibinex.com/u)\n"));
+	}
+
+	#[test]
+	fn default_comment_adds_details_and_approvals() {
+		let mut comment_vars = test_vars();
+		comment_vars.reviewer_details = "a: authored 10 lines\n".to_string();
+		comment_vars.approvals = "Approved by a at abc1234\n".to_string();
+		comment_vars.auto_assign = true;
+		let comment = default_comment(&comment_vars);
+		assert!(comment.contains("| a | 100% |\n\n\nWhy these reviewers:\n\na: authored 10 lines\n\nApproved by a at abc1234\n"));
+		assert!(comment.contains("Auto assigning to relevant reviewers."));
+	}
+}
@@ -0,0 +1,71 @@
+use crate::core::provider::repo_provider;
+use crate::utils::coverage::CoverageMap;
+use crate::utils::relevance::Relevance;
+use crate::utils::repo_config::RepoConfig;
+use crate::utils::review::Review;
+
+// Recomputes approved coverage for a freshly calculated relevance vector, e.g. after a push
+pub async fn update_coverage_gate(review: &Review, relevance_vec: &Vec<Relevance>,
+	approver_handles: &Vec<String>, repo_config: &RepoConfig, access_token: &str) {
+	if repo_config.coverage_gate().is_none() {
+		return;
+	}
+	let mut coverage_map = CoverageMap::new(review.provider().to_string());
+	coverage_map.calculate_coverage_map(relevance_vec.to_owned(), approver_handles.to_owned());
+	publish_coverage_status(review, coverage_map.coverage_total(), repo_config, access_token).await;
+}
+
+pub async fn publish_coverage_status(review: &Review, coverage_total: f32,
+	repo_config: &RepoConfig, access_token: &str) {
+	let threshold_opt = repo_config.coverage_gate();
+	if threshold_opt.is_none() {
+		return;
+	}
+	let threshold = threshold_opt.expect("Empty threshold_opt");
+	let (passed, description) = coverage_status(coverage_total, threshold);
+	log::info!("Coverage gate for PR {}: {}...", review.id(), &description);
+	if let Some(provider) = repo_provider(review.provider()) {
+		provider.set_coverage_status(review, passed, &description, access_token).await;
+	}
+}
+
+fn coverage_status(coverage_total: f32, threshold: f32) -> (bool, String) {
+	let description = format!("{:.2}% of relevant code approved, {:.2}% required", coverage_total, threshold);
+	(coverage_total >= threshold, description)
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+	use crate::utils::relevance::test_relevance;
+	use crate::utils::repo_config::test_repo_config;
+
+	#[test]
+	fn gate_passes_once_threshold_is_reached() {
+		assert_eq!(coverage_status(49.99, 50.0),
+			(false, "49.99% of relevant code approved, 50.00% required".to_string()));
+		assert!(coverage_status(50.0, 50.0).0);
+		assert!(coverage_status(80.0, 50.0).0);
+	}
+
+	#[test]
+	fn gate_is_off_unless_configured() {
+		assert_eq!(test_repo_config(json!({})).coverage_gate(), None);
+		assert_eq!(test_repo_config(json!({"coverage_gate": 60.0})).coverage_gate(), Some(60.0));
+	}
+
+	#[test]
+	fn only_approvers_count_towards_coverage() {
+		let relevance_vec = vec![
+			test_relevance("alice", 50.0, &["alice-gh"]),
+			test_relevance("bob", 30.0, &["bob-gh"]),
+			test_relevance("carol", 20.0, &[]),
+		];
+		let mut coverage_map = CoverageMap::new("github".to_string());
+		coverage_map.calculate_coverage_map(relevance_vec, vec!["alice-gh".to_string(), "carol".to_string()]);
+		assert_eq!(coverage_map.coverage_total(), 50.0);
+		assert!(!coverage_status(coverage_map.coverage_total(), 60.0).0);
+	}
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use crate::core::provider::{repo_provider, RepoProvider};
+use crate::core::teams::is_team_entry;
+use crate::utils::relevance::Relevance;
+use crate::utils::repo_config::RepoConfig;
+use crate::utils::review::Review;
+
+// Automation accounts that show up in blame but never review
+const BOT_IDENTITIES: [&str; 12] = [
+	"dependabot",
+	"dependabot-preview",
+	"renovate",
+	"renovate-bot",
+	"github-actions",
+	"snyk-bot",
+	"greenkeeper",
+	"mergify",
+	"pre-commit-ci",
+	"imgbot",
+	"allcontributors",
+	"semantic-release-bot",
+];
+
+// Drops the PR author, bots, denylisted users and users without repo access,
+// and spreads their relevance over the remaining candidates
+pub async fn filter_eligible_relevance(relevance_vec: &Vec<Relevance>, review: &Review,
+	repo_config: &RepoConfig, access_token: &str) -> Vec<Relevance> {
+	let provider_opt = repo_provider(review.provider());
+	let mut eligible_vec = Vec::<Relevance>::new();
+	let mut access_cache = HashMap::<String, bool>::new();
+	for relevance_obj in relevance_vec {
+		let identities = relevance_identities(relevance_obj);
+		if let Some(reason) = exclusion_reason(&identities, review, repo_config) {
+			log::info!("Excluding {} from reviewers: {}", relevance_obj.git_alias(), reason);
+			continue;
+		}
+		if let Some(provider) = &provider_opt {
+			if !has_repo_access(provider.as_ref(), relevance_obj, review, access_token, &mut access_cache).await {
+				log::info!("Excluding {} from reviewers: no access to {}", relevance_obj.git_alias(), review.repo_name());
+				continue;
+			}
+		}
+		eligible_vec.push(relevance_obj.to_owned());
+	}
+	redistribute_relevance(&mut eligible_vec);
+	eligible_vec
+}
+
+pub fn is_bot_identity(identity: &str) -> bool {
+	let lower = identity.to_lowercase();
+	// GitHub noreply emails look like 49699333+dependabot[bot]@users.noreply.github.com
+	let local_part = lower.split('@').next().unwrap_or("");
+	let name = local_part.rsplit('+').next().unwrap_or(local_part);
+	if name.ends_with("[bot]") {
+		return true;
+	}
+	BOT_IDENTITIES.contains(&name)
+}
+
+fn relevance_identities(relevance_obj: &Relevance) -> Vec<String> {
+	let mut identities = vec![relevance_obj.git_alias().to_owned()];
+	if let Some(handles) = relevance_obj.handles() {
+		identities.extend(handles.iter().cloned());
+	}
+	identities
+}
+
+pub fn exclusion_reason(identities: &[String], review: &Review, repo_config: &RepoConfig) -> Option<String> {
+	let denylist: Vec<String> = repo_config.reviewer_denylist().iter()
+		.map(|denied| denied.trim_start_matches('@').to_lowercase())
+		.collect();
+	let mut author_identities = vec![review.author().to_lowercase()];
+	if let Some(author_handle) = review.author_handle() {
+		author_identities.push(author_handle.to_lowercase());
+	}
+	for identity in identities {
+		let identity_lower = identity.trim_start_matches('@').to_lowercase();
+		if author_identities.contains(&identity_lower) {
+			return Some("PR author".to_string());
+		}
+		if is_bot_identity(&identity_lower) {
+			return Some("bot account".to_string());
+		}
+		if denylist.contains(&identity_lower) {
+			return Some("denylisted in repo config".to_string());
+		}
+	}
+	None
+}
+
+// Unknown access is treated as access, we only drop people the provider says can't see the repo
+async fn has_repo_access(provider: &dyn RepoProvider, relevance_obj: &Relevance, review: &Review, access_token: &str,
+	access_cache: &mut HashMap<String, bool>) -> bool {
+	let handles_opt = relevance_obj.handles();
+	if handles_opt.is_none() {
+		return true;
+	}
+	// teams are checked when they are expanded, not here
+	if is_team_entry(relevance_obj) {
+		return true;
+	}
+	let handles = handles_opt.to_owned().expect("Empty handles_opt");
+	let mut checked_any = false;
+	for handle in handles {
+		let access_opt = handle_access(provider, &handle, review, access_token, access_cache).await;
+		if access_opt.is_none() {
+			continue;
+		}
+		checked_any = true;
+		if access_opt.expect("Empty access_opt") {
+			return true;
+		}
+	}
+	!checked_any
+}
+
+async fn handle_access(provider: &dyn RepoProvider, handle: &str, review: &Review, access_token: &str,
+	access_cache: &mut HashMap<String, bool>) -> Option<bool> {
+	if let Some(access) = access_cache.get(handle) {
+		return Some(*access);
+	}
+	let access_opt = provider.has_repo_access(handle, review, access_token).await;
+	if let Some(access) = access_opt {
+		access_cache.insert(handle.to_string(), access);
+	}
+	access_opt
+}
+
+fn redistribute_relevance(relevance_vec: &mut [Relevance]) {
+	let total: f32 = relevance_vec.iter().map(|relevance_obj| relevance_obj.relevance_num()).sum();
+	if total <= 0.0 {
+		return;
+	}
+	for relevance_obj in relevance_vec.iter_mut() {
+		relevance_obj.set_relevance_num(relevance_obj.relevance_num() / total * 100.0);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::relevance::test_relevance;
+	use crate::utils::repo_config::test_repo_config;
+	use crate::utils::review::test_review;
+
+	fn identities(identities: &[&str]) -> Vec<String> {
+		identities.iter().map(|identity| identity.to_string()).collect()
+	}
+
+	#[test]
+	fn recognises_bots() {
+		assert!(is_bot_identity("dependabot"));
+		assert!(is_bot_identity("Renovate-Bot"));
+		assert!(is_bot_identity("49699333+dependabot[bot]@users.noreply.github.com"));
+		assert!(is_bot_identity("some-app[bot]"));
+		assert!(!is_bot_identity("octocat"));
+		assert!(!is_bot_identity("robot@example.com"));
+	}
+
+	#[test]
+	fn excludes_author_by_id_or_handle() {
+		let mut review = test_review("1", "head");
+		review.set_author_handle(Some("OctoCat".to_string()));
+		let config = test_repo_config(serde_json::json!({}));
+		assert_eq!(exclusion_reason(&identities(&["author"]), &review, &config), Some("PR author".to_string()));
+		assert_eq!(exclusion_reason(&identities(&["octocat@example.com", "@octocat"]), &review, &config),
+			Some("PR author".to_string()));
+		assert_eq!(exclusion_reason(&identities(&["jane@example.com", "jane"]), &review, &config), None);
+	}
+
+	#[test]
+	fn excludes_bots_and_denylisted_users() {
+		let review = test_review("1", "head");
+		let config = test_repo_config(serde_json::json!({"reviewer_denylist": ["@Jane", "ci@example.com"]}));
+		assert_eq!(exclusion_reason(&identities(&["bot@example.com", "github-actions"]), &review, &config),
+			Some("bot account".to_string()));
+		assert_eq!(exclusion_reason(&identities(&["jane@example.com", "jane"]), &review, &config),
+			Some("denylisted in repo config".to_string()));
+		assert_eq!(exclusion_reason(&identities(&["CI@example.com"]), &review, &config),
+			Some("denylisted in repo config".to_string()));
+		assert_eq!(exclusion_reason(&identities(&["john@example.com", "john"]), &review, &config), None);
+	}
+
+	#[test]
+	fn relevance_of_excluded_people_goes_to_the_rest() {
+		let mut relevance_vec = vec![test_relevance("a", 30.0, &["a"]), test_relevance("b", 10.0, &[])];
+		redistribute_relevance(&mut relevance_vec);
+		assert_eq!(relevance_vec[0].relevance_num(), 75.0);
+		assert_eq!(relevance_vec[1].relevance_num(), 25.0);
+		assert_eq!(relevance_vec[1].relevance_str(), "25.00");
+		let mut empty_vec = vec![test_relevance("c", 0.0, &[])];
+		redistribute_relevance(&mut empty_vec);
+		assert_eq!(empty_vec[0].relevance_num(), 0.0);
+	}
+}
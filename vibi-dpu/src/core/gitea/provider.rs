@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::core::provider::{first_handles, RepoProvider, ReviewerSelection};
+use crate::core::review::create_and_save_github_review_object;
+use crate::db::prs::gitea_process_and_update_pr_if_different;
+use crate::gitea;
+use crate::gitea::config::gitea_web_url;
+use crate::utils::hunk::BlameItem;
+use crate::utils::pr_info::PrInfo;
+use crate::utils::review::Review;
+use crate::utils::user::ProviderEnum;
+
+use super::setup;
+
+pub struct GiteaProvider;
+
+#[async_trait]
+impl RepoProvider for GiteaProvider {
+	async fn access_token(&self, _review: &Option<Review>) -> Option<String> {
+		return gitea::auth::gitea_access_token();
+	}
+
+	// stored url is the http one, which may be plain http on local instances
+	fn clone_url(&self, git_url: &str, access_token: &str) -> Option<String> {
+		git_url.split_once("://")
+			.map(|(scheme, host_path)| format!("{scheme}://oauth2:{access_token}@{host_path}"))
+	}
+
+	async fn pr_info(&self, repo_owner: &str, repo_name: &str, pr_number: &str, access_token: &str) -> Option<PrInfo> {
+		return gitea::prs::get_pr_info_gitea(repo_owner, repo_name, access_token, pr_number).await;
+	}
+
+	async fn process_webhook(&self, deserialised_msg_data: &Value) -> bool {
+		let repo_owner = deserialised_msg_data["eventPayload"]["repository"]["owner"]["login"].to_string().trim_matches('"').to_string();
+		let repo_name = deserialised_msg_data["eventPayload"]["repository"]["name"].to_string().trim_matches('"').to_string();
+		let pr_number = deserialised_msg_data["eventPayload"]["pull_request"]["number"].to_string().trim_matches('"').to_string();
+		log::debug!("[process_webhook] {}, {}, {}", repo_owner, repo_name, pr_number);
+		return gitea_process_and_update_pr_if_different(&deserialised_msg_data["eventPayload"],
+			&deserialised_msg_data["repoConfig"], &repo_owner, &repo_name, &pr_number,
+			&ProviderEnum::Gitea.to_string()).await;
+	}
+
+	// pull request payloads follow GitHub's shape
+	fn review_from_webhook(&self, deserialized_data: &Value) -> (Option<Review>, Option<Review>) {
+		create_and_save_github_review_object(deserialized_data, &ProviderEnum::Gitea.to_string())
+	}
+
+	async fn process_pat_repos(&self, message_data: &[u8]) {
+		setup::process_pat_repos(message_data).await;
+	}
+
+	async fn add_comment(&self, comment_text: &str, review: &Review, access_token: &str) -> Option<String> {
+		return gitea::comment::add_comment(comment_text, review, access_token).await;
+	}
+
+	async fn edit_comment(&self, comment_id: &str, comment_text: &str, review: &Review, access_token: &str) -> Option<bool> {
+		return gitea::comment::edit_comment(comment_id, comment_text, review, access_token).await;
+	}
+
+	async fn add_reviewers(&self, selection: &ReviewerSelection<'_>, review: &Review, access_token: &str) {
+		let logins = first_handles(selection.relevance_vec);
+		if logins.is_empty() {
+			return;
+		}
+		gitea::reviewer::add_reviewers(&logins, review, access_token).await;
+	}
+
+	async fn approvals(&self, review: &Review, access_token: &str) -> Option<Vec<(Vec<String>, Option<String>)>> {
+		let approvals_opt = gitea::prs::pr_approvals(review.repo_owner(), review.repo_name(),
+			review.id(), access_token).await;
+		return approvals_opt.map(|approvals| approvals.into_iter()
+			.map(|(login, commit)| (vec![login], Some(commit)))
+			.collect());
+	}
+
+	async fn set_coverage_status(&self, review: &Review, passed: bool, description: &str, access_token: &str) {
+		let state = if passed { "success" } else { "failure" };
+		let pr_url = format!("{}/{}/{}/pulls/{}", gitea_web_url(),
+			review.repo_owner(), review.repo_name(), review.id());
+		gitea::status::set_commit_status(review.repo_owner(), review.repo_name(),
+			review.pr_head_commit(), state, description, &pr_url, access_token).await;
+	}
+
+	// gitea anchors on a hash of the path, link the diff as a whole
+	fn diff_url(&self, review: &Review, _filepath: &str, _blame: &BlameItem, _range_opt: Option<(usize, usize)>) -> String {
+		format!("{}/{}/{}/pulls/{}/files", gitea_web_url(),
+			review.repo_owner(), review.repo_name(), review.id())
+	}
+}
@@ -1,26 +1,75 @@
 use serde_json::{json, Value};
 
-use crate::{github::config::{github_base_url, prepare_headers}, utils::{review::Review, reqwest_client::get_client}};
+use crate::{github::config::{github_base_url, prepare_headers}, utils::{review::Review, reqwest_client::{get_client, send_request}}};
 
-pub async fn add_comment(comment_text: &str, review: &Review, access_token: &str) {
+// Returns the id of the created comment
+pub async fn add_comment(comment_text: &str, review: &Review, access_token: &str) -> Option<String> {
     let url = prepare_add_comment_url(review);
     let comment_payload = prepare_body(comment_text);
     let client = get_client();
     let headers_opt = prepare_headers(&access_token);
     if headers_opt.is_none() {
         log::error!("[github/add_comment] Unable to prepare_headers_comment, empty headers_opt");
-        return;
+        return None;
     }
     let headers = headers_opt.expect("Empty headers_opt");
-    let response_res = client.post(&url).
-        headers(headers).json(&comment_payload).send().await;
+    let response_res = send_request(client.post(&url).
+        headers(headers).json(&comment_payload)).await;
     if response_res.is_err() {
         let e = response_res.expect_err("No error in response_res");
         log::error!("[github/add_comment] Error in post request for adding comment - {:?}", e);
-        return;
+        return None;
     }
     let response = response_res.expect("Error in getting response");
     log::debug!("[github/add_comment] response from comment post request = {:?}", &response);
+    if !response.status().is_success() {
+        log::error!("[github/add_comment] Unable to add comment, status: {}", response.status());
+        return None;
+    }
+    let response_json_res = response.json::<Value>().await;
+    if response_json_res.is_err() {
+        let e = response_json_res.expect_err("No error in response_json_res");
+        log::error!("[github/add_comment] Unable to deserialize comment response: {:?}", e);
+        return None;
+    }
+    let response_json = response_json_res.expect("Uncaught error in response_json_res");
+    response_json["id"].as_u64().map(|comment_id| comment_id.to_string())
+}
+
+// Some(false) if the comment no longer exists, None if the edit failed for any other reason
+pub async fn edit_comment(comment_id: &str, comment_text: &str, review: &Review, access_token: &str) -> Option<bool> {
+    let url = format!(
+        "{}/repos/{}/{}/issues/comments/{}",
+        github_base_url(),
+        review.repo_owner(),
+        review.repo_name(),
+        comment_id
+    );
+    let comment_payload = prepare_body(comment_text);
+    let client = get_client();
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[github/edit_comment] Unable to prepare_headers_comment, empty headers_opt");
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let response_res = send_request(client.patch(&url).
+        headers(headers).json(&comment_payload)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[github/edit_comment] Error in patch request for editing comment - {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Error in getting response");
+    log::debug!("[github/edit_comment] response from comment patch request = {:?}", &response);
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Some(false);
+    }
+    if !response.status().is_success() {
+        log::error!("[github/edit_comment] Unable to edit comment {}, status: {}", comment_id, response.status());
+        return None;
+    }
+    Some(true)
 }
 
 fn prepare_add_comment_url(review: &Review) -> String {
@@ -39,4 +88,4 @@ fn prepare_body(comment_text: &str) -> Value {
     return json!({
         "body": comment_text
     });
-}
\ No newline at end of file
+}
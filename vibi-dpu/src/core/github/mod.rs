@@ -1,3 +1,4 @@
 pub mod setup;
 pub mod comment;
-pub mod reviewer;
\ No newline at end of file
+pub mod reviewer;
+pub mod provider;
\ No newline at end of file
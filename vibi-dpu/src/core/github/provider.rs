@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::core::provider::{RepoProvider, ReviewerSelection};
+use crate::core::push::{github_pushed_branches, process_push};
+use crate::core::review::create_and_save_github_review_object;
+use crate::core::teams::is_team_member;
+use crate::core::uninstall::{process_installation_event, remove_github_installation};
+use crate::db::github::auth::{delete_github_auth_info_from_db, get_github_installations_from_db};
+use crate::db::prs::github_process_and_update_pr_if_different;
+use crate::github::config::{github_git_host, github_web_url};
+use crate::github;
+use crate::utils::github_auth_info::GithubAuthInfo;
+use crate::utils::hunk::BlameItem;
+use crate::utils::pr_info::PrInfo;
+use crate::utils::review::Review;
+use crate::utils::user::ProviderEnum;
+
+use super::{comment, reviewer, setup};
+
+pub struct GithubProvider;
+
+#[async_trait]
+impl RepoProvider for GithubProvider {
+	async fn access_token(&self, review: &Option<Review>) -> Option<String> {
+		return github::auth::gh_access_token(review).await;
+	}
+
+	async fn repo_access_token(&self, repo_owner: &str, repo_name: &str) -> Option<String> {
+		return github::auth::repo_access_token(repo_owner, repo_name).await;
+	}
+
+	fn clone_url(&self, git_url: &str, access_token: &str) -> Option<String> {
+		let git_host = github_git_host();
+		Some(git_url.to_string()
+			.replace("git@", format!("https://x-access-token:{access_token}@").as_str())
+			.replace(&format!("{git_host}:"), &format!("{git_host}/")))
+	}
+
+	async fn pr_info(&self, repo_owner: &str, repo_name: &str, pr_number: &str, access_token: &str) -> Option<PrInfo> {
+		return github::prs::get_pr_info_github(repo_owner, repo_name, access_token, pr_number).await;
+	}
+
+	async fn reconcile_webhooks(&self, repo_owner: &str, repo_name: &str, access_token: &str) {
+		setup::process_webhooks(repo_owner.to_string(), repo_name.to_string(), access_token.to_string()).await;
+	}
+
+	async fn commit_author_handle(&self, repo_owner: &str, repo_name: &str, commit: &str, access_token: &str) -> Option<String> {
+		return github::user::commit_author_login(repo_owner, repo_name, commit, access_token).await;
+	}
+
+	async fn remove_webhooks(&self, repo_owner: &str, repo_name: &str, access_token: &str) {
+		setup::remove_webhooks(repo_owner, repo_name, access_token).await;
+	}
+
+	async fn handle_install(&self, installation_code: &str) {
+		setup::handle_install_github(installation_code).await;
+	}
+
+	fn remove_installation(&self, installation_id: &str) {
+		remove_github_installation(installation_id);
+	}
+
+	fn purge_auth(&self) {
+		for auth_info in get_github_installations_from_db() {
+			if let Some(installation_id) = auth_info.installation_id() {
+				delete_github_auth_info_from_db(installation_id);
+			}
+		}
+		GithubAuthInfo::remove_file();
+	}
+
+	async fn process_webhook(&self, deserialised_msg_data: &Value) -> bool {
+		let repo_owner = deserialised_msg_data["eventPayload"]["repository"]["owner"]["login"].to_string().trim_matches('"').to_string();
+		let repo_name = deserialised_msg_data["eventPayload"]["repository"]["name"].to_string().trim_matches('"').to_string();
+		let pr_number = deserialised_msg_data["eventPayload"]["pull_request"]["number"].to_string().trim_matches('"').to_string();
+		let event_type = deserialised_msg_data["eventType"].to_string().trim_matches('"').to_string();
+		log::debug!("[process_webhook] {}, {}, {}, {}", event_type, repo_owner, repo_name, pr_number);
+		if event_type == "installation" || event_type == "installation_repositories" {
+			process_installation_event(&event_type, &deserialised_msg_data["eventPayload"]).await;
+			return false;
+		}
+		if event_type == "push" {
+			let pushed_branches = github_pushed_branches(&deserialised_msg_data["eventPayload"]);
+			if !pushed_branches.is_empty() {
+				process_push(&ProviderEnum::Github.to_string(), &repo_owner, &repo_name, pushed_branches);
+			}
+			return false;
+		}
+		if event_type != "pull_request_review" && event_type != "pull_request" {
+			return false;
+		}
+		log::info!("Parsed Github {} event...", &event_type);
+		return github_process_and_update_pr_if_different(&deserialised_msg_data["eventPayload"],
+			&deserialised_msg_data["repoConfig"], &repo_owner, &repo_name, &pr_number,
+			&ProviderEnum::Github.to_string()).await;
+	}
+
+	fn review_from_webhook(&self, deserialized_data: &Value) -> (Option<Review>, Option<Review>) {
+		create_and_save_github_review_object(deserialized_data, &ProviderEnum::Github.to_string())
+	}
+
+	async fn process_pat_repos(&self, message_data: &[u8]) {
+		setup::process_pat_repos(message_data).await;
+	}
+
+	async fn add_comment(&self, comment_text: &str, review: &Review, access_token: &str) -> Option<String> {
+		return comment::add_comment(comment_text, review, access_token).await;
+	}
+
+	async fn edit_comment(&self, comment_id: &str, comment_text: &str, review: &Review, access_token: &str) -> Option<bool> {
+		return comment::edit_comment(comment_id, comment_text, review, access_token).await;
+	}
+
+	async fn has_repo_access(&self, handle: &str, review: &Review, access_token: &str) -> Option<bool> {
+		return github::user::is_repo_collaborator(review.repo_owner(), review.repo_name(), handle, access_token).await;
+	}
+
+	async fn team_members(&self, team_owner: &str, team_slug: &str, review: &Review, access_token: &str) -> Option<Vec<String>> {
+		if team_owner != review.repo_owner() {
+			log::error!("[team_members] Team {}/{} is outside of {}, can't request it", team_owner, team_slug, review.repo_owner());
+			return None;
+		}
+		return github::teams::get_team_members(team_owner, team_slug, access_token).await;
+	}
+
+	async fn open_review_count(&self, handle: &str, review: &Review, access_token: &str) -> Option<u64> {
+		return github::prs::open_review_requests_count(review.repo_owner(), handle, access_token).await;
+	}
+
+	async fn add_reviewers(&self, selection: &ReviewerSelection<'_>, review: &Review, access_token: &str) {
+		let mut reviewers: HashSet<String> = HashSet::new();
+		let team_reviewers: Vec<String> = selection.teams.iter()
+			.map(|team_reviewer| team_reviewer.slug().to_owned())
+			.collect();
+		for relevance_obj in selection.relevance_vec {
+			let provider_ids_opt = relevance_obj.handles();
+			if provider_ids_opt.is_none() {
+				continue;
+			}
+			let provider_ids = provider_ids_opt.to_owned().expect("Empty provider_ids_opt");
+			// members are covered by their team's request
+			if selection.teams.iter().any(|team_reviewer| is_team_member(team_reviewer, &provider_ids)) {
+				continue;
+			}
+			let provider_id_opt = provider_ids.first();
+			if provider_id_opt.is_none() {
+				continue;
+			}
+			let provider_id = provider_id_opt.expect("Empty provider_id_opt");
+			// teams go in team_reviewers, only when they pass the team threshold
+			if provider_id.contains('/') {
+				continue;
+			}
+			reviewers.insert(provider_id.to_owned());
+		}
+		if reviewers.is_empty() && team_reviewers.is_empty() {
+			return;
+		}
+		let reviewers_vec: Vec<String> = reviewers.into_iter().collect();
+		reviewer::add_reviewers(&reviewers_vec, &team_reviewers, review, access_token).await;
+	}
+
+	async fn approvals(&self, review: &Review, access_token: &str) -> Option<Vec<(Vec<String>, Option<String>)>> {
+		let approvals_opt = github::prs::pr_approvals(review.repo_owner(), review.repo_name(),
+			review.id(), access_token).await;
+		return approvals_opt.map(|approvals| approvals.into_iter()
+			.map(|(login, commit)| (vec![login], Some(commit)))
+			.collect());
+	}
+
+	async fn set_coverage_status(&self, review: &Review, passed: bool, description: &str, access_token: &str) {
+		let state = if passed { "success" } else { "failure" };
+		let pr_url = format!("{}/{}/{}/pull/{}", github_web_url(),
+			review.repo_owner(), review.repo_name(), review.id());
+		github::status::set_commit_status(review.repo_owner(), review.repo_name(),
+			review.pr_head_commit(), state, description, &pr_url, access_token).await;
+	}
+
+	fn diff_url(&self, review: &Review, _filepath: &str, blame: &BlameItem, range_opt: Option<(usize, usize)>) -> String {
+		let files_url = format!("{}/{}/{}/pull/{}/files#diff-{}", github_web_url(),
+			review.repo_owner(), review.repo_name(), review.id(), blame.filepath());
+		if let Some((start, end)) = range_opt {
+			return format!("{}L{}-L{}", files_url, start, end);
+		}
+		files_url
+	}
+}
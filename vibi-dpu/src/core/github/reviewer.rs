@@ -1,8 +1,8 @@
 use serde_json::json;
 
-use crate::{utils::{review::Review, reqwest_client::get_client}, github::config::{github_base_url, prepare_headers}};
+use crate::{utils::{review::Review, reqwest_client::{get_client, send_request}}, github::config::{github_base_url, prepare_headers}};
 
-pub async fn add_reviewers(reviewers: &Vec<String>, review: &Review, access_token: &str) {
+pub async fn add_reviewers(reviewers: &Vec<String>, team_reviewers: &Vec<String>, review: &Review, access_token: &str) {
     let url = format!("{}/repos/{}/{}/pulls/{}/requested_reviewers",
         github_base_url(),
         review.repo_owner(),
@@ -14,14 +14,15 @@ pub async fn add_reviewers(reviewers: &Vec<String>, review: &Review, access_toke
     }
     let headers = headers_opt.expect("Empty headers_opt");
     let body_json = json!({
-        "reviewers": reviewers
+        "reviewers": reviewers,
+        "team_reviewers": team_reviewers
     });
     let body = body_json.to_string();
     let client = get_client();
-    let response_res = client.post(url).headers(headers).body(body).send().await;
+    let response_res = send_request(client.post(url).headers(headers).body(body)).await;
     if response_res.is_err() {
         let e = response_res.expect_err("No error in response_res");
-        log::error!("[add_reviewers] Unable to add reviewers: {:?}, {:?}", e, &reviewers);
+        log::error!("[add_reviewers] Unable to add reviewers: {:?}, {:?}, {:?}", e, &reviewers, &team_reviewers);
         return;
     }
     let response = response_res.expect("Uncaught error in response_res");
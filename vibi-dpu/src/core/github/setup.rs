@@ -4,7 +4,9 @@ use std::str;
 use serde_json::Value;
 use tokio::task;
 
+use crate::core::ownership::index_repo_ownership;
 use crate::core::utils::send_aliases;
+use crate::db::github::auth::save_repo_installation_to_db;
 use crate::db::repo::get_repo_from_db;
 use crate::github::auth::fetch_access_token; use crate::github::prs::{list_prs_github, get_and_store_pr_info};
 use crate::github::repos::get_user_github_repos_using_graphql_api;
@@ -15,8 +17,9 @@ use crate::utils::repo::Repository;
 use crate::utils::setup_info::SetupInfo;
 use crate::github::repos::get_github_app_installed_repos;
 use crate::utils::gitops::clone_git_repo;
-use crate::github::webhook::{get_webhooks_in_repo, add_webhook};
-use crate::db::webhook::save_webhook_to_db;
+use crate::github::webhook::{add_webhook, delete_webhook, get_webhooks_in_repo, update_webhook, WEBHOOK_CALLBACK_PATH, WEBHOOK_EVENTS};
+use crate::core::webhooks::{our_hook_ids, plan_reconcile, DesiredHook, HookAction, HookState};
+use crate::utils::github_webhook::Webhook;
 use crate::core::utils::send_setup_info;
 
 pub async fn handle_install_github(installation_code: &str) {
@@ -30,10 +33,10 @@ pub async fn handle_install_github(installation_code: &str) {
 	}
 	let auth_info = auth_info_opt.expect("Empty authinfo_opt");
 	let access_token = auth_info.token().clone();
-	process_repos(&access_token, repo_provider).await;
+	process_repos(&access_token, repo_provider, installation_code).await;
 }
 
-pub async fn process_repos(access_token: &str, repo_provider: &str) {
+pub async fn process_repos(access_token: &str, repo_provider: &str, installation_id: &str) {
 	log::info!("Processing repos...");
 	let mut pubreqs: Vec<SetupInfo> = Vec::new();
 	let repos_opt = get_github_app_installed_repos(&access_token).await;
@@ -50,6 +53,7 @@ pub async fn process_repos(access_token: &str, repo_provider: &str) {
 		let token_copy = access_token.to_owned().clone();
 		let mut repo_copy = repo.clone();
 		clone_git_repo(&mut repo_copy, &token_copy, repo_provider).await;
+		index_repo_ownership(&repo_copy);
 		let aliases_opt = get_git_aliases(&repo_copy);
 		if aliases_opt.is_none() {
 			log::error!("[handle_install_github] Unable to get aliases for repo: {}", repo.name());
@@ -59,6 +63,7 @@ pub async fn process_repos(access_token: &str, repo_provider: &str) {
 		send_aliases(&repo, &aliases).await;
 		let repo_name = repo.name();
 		repo_names.push(repo_name.clone());
+		save_repo_installation_to_db(repo.owner(), repo_name, installation_id);
 		log::debug!("[handle_install_github] Repo url git = {:?}", &repo.clone_ssh_url());
 		log::debug!("[handle_install_github] Repo name = {:?}", repo_name);
 		let repo_owner = repo.owner();
@@ -79,7 +84,7 @@ pub async fn process_repos(access_token: &str, repo_provider: &str) {
 }
 
 
-async fn process_webhooks(repo_owner: String, repo_name: String, access_token: String) {
+pub async fn process_webhooks(repo_owner: String, repo_name: String, access_token: String) {
 	log::info!("Processing webhooks for : {}...", &repo_name);
 	let webhooks_data_opt = get_webhooks_in_repo(&repo_owner, &repo_name, &access_token).await;
 	if webhooks_data_opt.is_none() {
@@ -88,27 +93,40 @@ async fn process_webhooks(repo_owner: String, repo_name: String, access_token: S
 		return;
 	}
 	let webhooks_data = webhooks_data_opt.expect("Empty webhooks_data_opt");
-	let webhook_callback_url = format!("{}/api/github/callbacks/webhook", 
-		env::var("SERVER_URL").expect("SERVER_URL must be set"));
 	log::debug!("[process_webhooks] webhooks_data = {:?}", &webhooks_data);
-	let matching_webhook = webhooks_data.into_iter()
-		.find(|w| w.url().to_string() == webhook_callback_url);
-	log::debug!("[process_webhooks] matching_webhook = {:?}", &matching_webhook);
-	if matching_webhook.is_none() {
-		let repo_name_async = repo_name.clone();
-		let workspace_slug_async = repo_owner.clone();
-		let access_token_async = access_token.clone();
-		task::spawn(async move {
-			add_webhook(
-				&workspace_slug_async, 
-				&repo_name_async, 
-				&access_token_async).await;
-		});
+	let desired = DesiredHook::new(WEBHOOK_CALLBACK_PATH, &WEBHOOK_EVENTS);
+	let hook_states = hook_states(&webhooks_data);
+	let actions = plan_reconcile(&desired, &hook_states);
+	log::debug!("[process_webhooks] actions for {}/{} = {:?}", &repo_owner, &repo_name, &actions);
+	for action in actions {
+		match action {
+			HookAction::Create => add_webhook(&repo_owner, &repo_name, &access_token, &desired).await,
+			HookAction::Update(hook_id) => update_webhook(&repo_owner, &repo_name, &hook_id, &access_token, &desired).await,
+			HookAction::Delete(hook_id) => delete_webhook(&repo_owner, &repo_name, &hook_id, &access_token).await,
+		}
+	}
+}
+
+pub async fn remove_webhooks(repo_owner: &str, repo_name: &str, access_token: &str) {
+	let webhooks_data_opt = get_webhooks_in_repo(repo_owner, repo_name, access_token).await;
+	if webhooks_data_opt.is_none() {
+		log::error!("[remove_webhooks] Unable to get webhooks for repo: {}/{}", repo_owner, repo_name);
 		return;
 	}
-	let webhook = matching_webhook.expect("no matching webhook");
-	log::debug!("[process_webhooks] Webhook already exists: {:?}", &webhook);
-	save_webhook_to_db(&webhook);
+	let hook_states = hook_states(&webhooks_data_opt.expect("Empty webhooks_data_opt"));
+	for hook_id in our_hook_ids(WEBHOOK_CALLBACK_PATH, &hook_states) {
+		delete_webhook(repo_owner, repo_name, &hook_id, access_token).await;
+	}
+}
+
+fn hook_states(webhooks_data: &[Webhook]) -> Vec<HookState> {
+	webhooks_data.iter().map(|webhook| HookState {
+		id: webhook.id().to_string(),
+		url: webhook.url().to_string(),
+		events: webhook.events().to_owned(),
+		active: webhook.active(),
+		has_secret: webhook.config().contains_key("secret"),
+	}).collect()
 }
 
 async fn process_prs(repo_owner_async: &String, repo_name_async: &String, access_token_async: &String) {
@@ -120,11 +138,11 @@ async fn process_prs(repo_owner_async: &String, repo_name_async: &String, access
 	}
 	let pr_list = pr_list_opt.expect("Empty pr_list_opt");
 
-	for pr_id in &pr_list {
+	for pr in &pr_list {
 		let repo_owner = repo_owner_async.clone(); //Instead of cloning each time, I could have used ARC but not sure what is the best way.
 		let repo_name = repo_name_async.clone();
 		let access_token = access_token_async.clone();
-		let pr_id_async = pr_id.clone();
+		let pr_id_async = pr.number.clone();
 		task::spawn(async move {
 			get_and_store_pr_info(&repo_owner, &repo_name, &access_token, &pr_id_async).await;
 		});
@@ -144,6 +162,7 @@ pub async fn process_pat_repos(message_data: &[u8]) {
 	for repo in repos {
 		let mut repo_copy = repo.clone();
 		clone_git_repo(&mut repo_copy, &access_token, &repo_provider).await;
+		index_repo_ownership(&repo_copy);
 		let repo_name = repo.name();
 		let repo_owner = repo.owner();
 		process_webhooks(repo_owner.to_string(), 
@@ -196,7 +215,7 @@ pub async fn setup_self_host_user_repos_github(access_token: &str) {
 	send_setup_info(&pubreq_vec).await;
 }
 
-fn parse_pat_repos(message_data: &[u8]) -> Option<Vec<Repository>> {
+pub fn parse_pat_repos(message_data: &[u8]) -> Option<Vec<Repository>> {
 	let data_res = serde_json::from_slice::<Vec<Value>>(&message_data);
 	if data_res.is_err() {
 		let e = data_res.expect_err("No error in data_res");
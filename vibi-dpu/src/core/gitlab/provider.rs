@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::core::provider::{first_handles, RepoProvider, ReviewerSelection};
+use crate::core::review::create_and_save_gitlab_review_object;
+use crate::db::prs::gitlab_process_and_update_pr_if_different;
+use crate::db::purge::delete_key;
+use crate::gitlab;
+use crate::gitlab::config::gitlab_web_url;
+use crate::utils::hunk::BlameItem;
+use crate::utils::pr_info::PrInfo;
+use crate::utils::review::Review;
+use crate::utils::user::ProviderEnum;
+
+use super::setup;
+
+pub struct GitlabProvider;
+
+#[async_trait]
+impl RepoProvider for GitlabProvider {
+	async fn access_token(&self, review: &Option<Review>) -> Option<String> {
+		return gitlab::auth::gitlab_access_token(review).await;
+	}
+
+	// self-managed hosts vary, so swap the first `:` of git@host:group/project.git
+	fn clone_url(&self, git_url: &str, access_token: &str) -> Option<String> {
+		git_url.strip_prefix("git@")
+			.and_then(|host_path| host_path.split_once(':'))
+			.map(|(host, path)| format!("https://oauth2:{access_token}@{host}/{path}"))
+	}
+
+	async fn pr_info(&self, repo_owner: &str, repo_name: &str, pr_number: &str, access_token: &str) -> Option<PrInfo> {
+		return gitlab::prs::get_mr_info_gitlab(repo_owner, repo_name, access_token, pr_number).await;
+	}
+
+	async fn handle_install(&self, installation_code: &str) {
+		setup::handle_install_gitlab(installation_code).await;
+	}
+
+	fn purge_auth(&self) {
+		delete_key("gitlab_auth_info");
+	}
+
+	async fn process_webhook(&self, deserialised_msg_data: &Value) -> bool {
+		// gitlab sends the full namespace path, owner keeps any subgroups
+		let path_with_namespace = deserialised_msg_data["eventPayload"]["project"]["path_with_namespace"].to_string().trim_matches('"').to_string();
+		let (repo_owner, repo_name) = path_with_namespace.rsplit_once('/').unwrap_or_default();
+		let mr_iid = deserialised_msg_data["eventPayload"]["object_attributes"]["iid"].to_string().trim_matches('"').to_string();
+		let object_kind = deserialised_msg_data["eventPayload"]["object_kind"].to_string().trim_matches('"').to_string();
+		log::debug!("[process_webhook] {}, {}, {}, {}", object_kind, repo_owner, repo_name, mr_iid);
+		if object_kind != "merge_request" {
+			return false;
+		}
+		log::info!("Parsed Gitlab MR event...");
+		return gitlab_process_and_update_pr_if_different(&deserialised_msg_data["eventPayload"],
+			&deserialised_msg_data["repoConfig"], repo_owner, repo_name, &mr_iid,
+			&ProviderEnum::Gitlab.to_string()).await;
+	}
+
+	fn review_from_webhook(&self, deserialized_data: &Value) -> (Option<Review>, Option<Review>) {
+		create_and_save_gitlab_review_object(deserialized_data)
+	}
+
+	async fn process_pat_repos(&self, message_data: &[u8]) {
+		setup::process_pat_repos(message_data).await;
+	}
+
+	async fn add_comment(&self, comment_text: &str, review: &Review, access_token: &str) -> Option<String> {
+		return gitlab::comment::add_comment(comment_text, review, access_token).await;
+	}
+
+	async fn edit_comment(&self, comment_id: &str, comment_text: &str, review: &Review, access_token: &str) -> Option<bool> {
+		return gitlab::comment::edit_comment(comment_id, comment_text, review, access_token).await;
+	}
+
+	async fn add_reviewers(&self, selection: &ReviewerSelection<'_>, review: &Review, access_token: &str) {
+		let usernames = first_handles(selection.relevance_vec);
+		if usernames.is_empty() {
+			return;
+		}
+		gitlab::reviewer::add_reviewers(&usernames, review, access_token).await;
+	}
+
+	async fn approvals(&self, review: &Review, access_token: &str) -> Option<Vec<(Vec<String>, Option<String>)>> {
+		let approvers_opt = gitlab::prs::mr_approvers(review.repo_owner(), review.repo_name(),
+			review.id(), access_token).await;
+		return approvers_opt.map(|approvers| approvers.into_iter()
+			.map(|username| (vec![username], None))
+			.collect());
+	}
+
+	async fn set_coverage_status(&self, review: &Review, passed: bool, description: &str, access_token: &str) {
+		let state = if passed { "success" } else { "failed" };
+		let mr_url = format!("{}/{}/{}/-/merge_requests/{}", gitlab_web_url(),
+			review.repo_owner(), review.repo_name(), review.id());
+		gitlab::status::set_commit_status(review.repo_owner(), review.repo_name(),
+			review.pr_head_commit(), state, description, &mr_url, access_token).await;
+	}
+
+	// gitlab anchors on a hash of the path, link the MR diff as a whole
+	fn diff_url(&self, review: &Review, _filepath: &str, _blame: &BlameItem, _range_opt: Option<(usize, usize)>) -> String {
+		format!("{}/{}/{}/-/merge_requests/{}/diffs", gitlab_web_url(),
+			review.repo_owner(), review.repo_name(), review.id())
+	}
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::env;
+use tokio::task;
+
+use crate::core::github::setup::parse_pat_repos;
+use crate::core::ownership::index_repo_ownership;
+use crate::core::utils::{send_aliases, send_setup_info, user_selected_repos};
+use crate::db::webhook::save_webhook_to_db;
+use crate::gitlab::auth::get_access_token_from_gitlab;
+use crate::gitlab::prs::{get_and_store_pr_info, list_mrs_gitlab};
+use crate::gitlab::repos::get_gitlab_projects;
+use crate::gitlab::webhook::{add_webhook, get_webhooks_in_repo};
+use crate::utils::gitops::{clone_git_repo, get_git_aliases};
+use crate::utils::repo::Repository;
+use crate::utils::setup_info::SetupInfo;
+use crate::utils::user::ProviderEnum;
+
+pub async fn handle_install_gitlab(installation_code: &str) {
+	let authinfo_opt = get_access_token_from_gitlab(installation_code).await;
+	if authinfo_opt.is_none() {
+		log::error!("[handle_install_gitlab] Unable to get authinfo in get_access_token_from_gitlab");
+		return;
+	}
+	let authinfo = authinfo_opt.expect("Empty authinfo_opt");
+	let access_token = authinfo.access_token().clone();
+	let projects_opt = get_gitlab_projects(&access_token).await;
+	if projects_opt.is_none() {
+		log::error!("[handle_install_gitlab] No projects found for gitlab user");
+		return;
+	}
+	// the oauth token sees every project of the user, only set up the ones picked on Vibinex
+	let user_selected_repos = user_selected_repos(&ProviderEnum::Gitlab.to_string()).await.unwrap_or_default();
+	let selected_projects: Vec<Repository> = projects_opt.expect("Empty projects_opt").into_iter()
+		.filter(|project| user_selected_repos.iter()
+			.any(|repo| repo.owner() == project.owner() && repo.name() == project.name()))
+		.collect();
+	if selected_projects.is_empty() {
+		log::error!("[handle_install_gitlab] No user selected projects found");
+		return;
+	}
+	process_repos(&selected_projects, &access_token).await;
+}
+
+pub async fn process_repos(repos: &Vec<Repository>, access_token: &str) {
+	log::info!("Processing repos...");
+	let repo_provider = ProviderEnum::Gitlab.to_string();
+	let mut repo_owner_map = HashMap::<String, Vec<String>>::new();
+	for repo in repos {
+		let mut repo_copy = repo.clone();
+		clone_git_repo(&mut repo_copy, access_token, &repo_provider).await;
+		index_repo_ownership(&repo_copy);
+		let aliases_opt = get_git_aliases(&repo_copy);
+		if aliases_opt.is_none() {
+			log::error!("[process_repos] Unable to get aliases for repo: {}", repo.name());
+			continue;
+		}
+		send_aliases(repo, &aliases_opt.expect("Empty aliases_opt")).await;
+		repo_owner_map.entry(repo.owner().to_string())
+			.or_default()
+			.push(repo.name().to_string());
+		process_webhooks(repo.owner(), repo.name(), access_token).await;
+		let repo_owner_async = repo.owner().clone();
+		let repo_name_async = repo.name().clone();
+		let access_token_async = access_token.to_string();
+		task::spawn(async move {
+			process_mrs(&repo_owner_async, &repo_name_async, &access_token_async).await;
+		});
+	}
+	send_setup_info(&setup_info_vec(repo_owner_map)).await;
+}
+
+pub async fn process_webhooks(repo_owner: &str, repo_name: &str, access_token: &str) {
+	log::info!("Processing webhooks for : {}/{}...", repo_owner, repo_name);
+	let webhooks_opt = get_webhooks_in_repo(repo_owner, repo_name, access_token).await;
+	if webhooks_opt.is_none() {
+		log::error!("[process_webhooks] Unable to get webhooks for repo: {}/{}", repo_owner, repo_name);
+		return;
+	}
+	let webhook_callback_url = format!("{}/api/gitlab/callbacks/webhook",
+		env::var("SERVER_URL").expect("SERVER_URL must be set"));
+	let matching_webhook = webhooks_opt.expect("Empty webhooks_opt").into_iter()
+		.find(|w| *w.url() == webhook_callback_url);
+	if matching_webhook.is_none() {
+		add_webhook(repo_owner, repo_name, access_token).await;
+		return;
+	}
+	let webhook = matching_webhook.expect("no matching webhook");
+	log::debug!("[process_webhooks] Webhook already exists: {:?}", &webhook);
+	save_webhook_to_db(&ProviderEnum::Gitlab.to_string(), repo_owner, repo_name, webhook.id(), &webhook);
+}
+
+async fn process_mrs(repo_owner: &str, repo_name: &str, access_token: &str) {
+	log::info!("Processing all open merge requests...");
+	let mr_list_opt = list_mrs_gitlab(repo_owner, repo_name, access_token, "opened").await;
+	if mr_list_opt.is_none() {
+		log::warn!("Unable to get any open merge requests for processing.");
+		return;
+	}
+	for mr_iid in mr_list_opt.expect("Empty mr_list_opt") {
+		let repo_owner_async = repo_owner.to_string();
+		let repo_name_async = repo_name.to_string();
+		let access_token_async = access_token.to_string();
+		task::spawn(async move {
+			get_and_store_pr_info(&repo_owner_async, &repo_name_async, &access_token_async, &mr_iid).await;
+		});
+	}
+}
+
+// Repos picked by the user on Vibinex after setup_self_host_user_repos_gitlab
+pub async fn process_pat_repos(message_data: &[u8]) {
+	let repos_opt = parse_pat_repos(message_data);
+	if repos_opt.is_none() {
+		log::error!("[process_pat_repos] Failed to parse PAT repos data");
+		return;
+	}
+	let access_token_res = env::var("GITLAB_PAT");
+	if access_token_res.is_err() {
+		let e = access_token_res.expect_err("No error in access_token_res");
+		log::error!("[process_pat_repos] GITLAB_PAT env var must be set: {:?}", e);
+		return;
+	}
+	let access_token = access_token_res.expect("Uncaught error in access_token_res");
+	process_repos(&repos_opt.expect("Empty repos_opt"), &access_token).await;
+}
+
+pub async fn setup_self_host_user_repos_gitlab(access_token: &str) {
+	log::info!("Getting all user's projects...");
+	let projects_opt = get_gitlab_projects(access_token).await;
+	if projects_opt.is_none() {
+		log::error!("[setup_self_host_user_repos_gitlab] No projects found for the user");
+		return;
+	}
+	let mut repo_owner_map = HashMap::<String, Vec<String>>::new();
+	for project in projects_opt.expect("Empty projects_opt") {
+		repo_owner_map.entry(project.owner().to_string())
+			.or_default()
+			.push(project.name().to_string());
+	}
+	send_setup_info(&setup_info_vec(repo_owner_map)).await;
+}
+
+fn setup_info_vec(repo_owner_map: HashMap<String, Vec<String>>) -> Vec<SetupInfo> {
+	repo_owner_map.into_iter()
+		.map(|(owner, repos)| SetupInfo {
+			provider: ProviderEnum::Gitlab.to_string(),
+			owner,
+			repos,
+		})
+		.collect()
+}
@@ -4,4 +4,20 @@ pub mod utils;
 pub mod approval;
 pub mod bitbucket;
 pub mod github;
-pub mod trigger;
\ No newline at end of file
+pub mod gitlab;
+pub mod gitea;
+pub mod trigger;
+pub mod ownership;
+pub mod codeowners;
+pub mod eligibility;
+pub mod assignment;
+pub mod teams;
+pub mod relevance_details;
+pub mod coverage_gate;
+pub mod approval_history;
+pub mod comment_template;
+pub mod bitbucket_server;
+pub mod provider;
+pub mod webhooks;
+pub mod uninstall;
+pub mod push;
\ No newline at end of file
@@ -0,0 +1,307 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::task;
+
+use crate::db::ownership::{delete_repo_ownership_from_db, get_ownership_meta_from_db, get_path_ownership_from_db, save_ownership_meta_to_db, save_path_ownership_to_db};
+use crate::utils::gitops::{default_branch_head, get_excluded_files, get_numstat_history, is_ancestor_commit, EXCLUDED_FILE_LINES};
+use crate::utils::hunk::PrHunkItem;
+use crate::utils::ownership::{OwnershipMeta, PathOwnership};
+use crate::utils::parsing::parse_string_field_pubsub;
+use crate::utils::repo::Repository;
+use crate::utils::reqwest_client::get_client;
+use crate::utils::review::Review;
+
+// Repos whose index is being rebuilt right now, to avoid counting the same commits twice.
+// The flag is set when another refresh was asked for meanwhile, it runs once the current one is done.
+static INDEXING_REPOS: Mutex<Option<HashMap<String, bool>>> = Mutex::new(None);
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct OwnershipQueryResponse {
+	repo_provider: String,
+	repo_owner: String,
+	repo_name: String,
+	path: String,
+	ownership: Option<PathOwnership>,
+}
+
+pub fn index_repo_ownership(repo: &Repository) {
+	let local_dir_opt = repo.local_dir().to_owned();
+	if local_dir_opt.is_none() {
+		log::error!("[index_repo_ownership] Unable to index ownership as local_dir is not set for {}", repo.name());
+		return;
+	}
+	let local_dir = local_dir_opt.expect("Empty local_dir_opt");
+	spawn_ownership_refresh(repo.provider(), repo.owner(), repo.name(), &local_dir);
+}
+
+pub fn spawn_ownership_refresh(repo_provider: &str, repo_owner: &str, repo_name: &str, clone_dir: &str) {
+	let repo_provider_async = repo_provider.to_string();
+	let repo_owner_async = repo_owner.to_string();
+	let repo_name_async = repo_name.to_string();
+	let clone_dir_async = clone_dir.to_string();
+	task::spawn_blocking(move || {
+		refresh_ownership_index(&repo_provider_async, &repo_owner_async,
+			&repo_name_async, &clone_dir_async);
+	});
+}
+
+pub fn refresh_ownership_index(repo_provider: &str, repo_owner: &str, repo_name: &str, clone_dir: &str) {
+	let repo_key = format!("{}/{}/{}", repo_provider, repo_owner, repo_name);
+	if !start_indexing(&repo_key) {
+		log::debug!("[refresh_ownership_index] Index refresh already running for {}, queued another", &repo_key);
+		return;
+	}
+	loop {
+		update_ownership_index(repo_provider, repo_owner, repo_name, clone_dir);
+		if !finish_indexing(&repo_key) {
+			return;
+		}
+		log::debug!("[refresh_ownership_index] Refreshing {} again for a queued request", &repo_key);
+	}
+}
+
+fn update_ownership_index(repo_provider: &str, repo_owner: &str, repo_name: &str, clone_dir: &str) {
+	let head_opt = default_branch_head(clone_dir);
+	if head_opt.is_none() {
+		log::error!("[update_ownership_index] Unable to find default branch head for {}/{}",
+			repo_owner, repo_name);
+		return;
+	}
+	let head = head_opt.expect("Empty head_opt");
+	let meta_opt = get_ownership_meta_from_db(repo_provider, repo_owner, repo_name);
+	let mut commit_range = head.clone();
+	if let Some(meta) = meta_opt {
+		let last_commit = meta.last_indexed_commit();
+		if last_commit == &head {
+			log::debug!("[update_ownership_index] Ownership index up to date for {}/{}", repo_owner, repo_name);
+			return;
+		}
+		if is_ancestor_commit(last_commit, &head, clone_dir) {
+			commit_range = format!("{}..{}", last_commit, &head);
+		} else {
+			// default branch was rewritten, the stored counts can't be trusted anymore
+			log::info!("Rebuilding ownership index for {}...", repo_name);
+			delete_repo_ownership_from_db(repo_provider, repo_owner, repo_name);
+		}
+	}
+	log::info!("Updating ownership index for {}...", repo_name);
+	let history_opt = get_numstat_history(&commit_range, clone_dir);
+	if history_opt.is_none() {
+		log::error!("[update_ownership_index] Unable to read git history for {}/{}", repo_owner, repo_name);
+		return;
+	}
+	let history = history_opt.expect("Empty history_opt");
+	let mut dirmap = HashMap::<String, HashMap<String, u64>>::new();
+	for (filepath, authors) in &history {
+		merge_path_ownership(repo_provider, repo_owner, repo_name, filepath, false, authors);
+		for dir in parent_dirs(filepath) {
+			let dir_authors = dirmap.entry(dir).or_default();
+			for (author, lines) in authors {
+				*dir_authors.entry(author.to_owned()).or_insert(0) += lines;
+			}
+		}
+	}
+	for (dir, authors) in &dirmap {
+		merge_path_ownership(repo_provider, repo_owner, repo_name, dir, true, authors);
+	}
+	let updated_at = SystemTime::now().duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs()).unwrap_or(0);
+	let meta = OwnershipMeta::new(head, updated_at);
+	save_ownership_meta_to_db(&meta, repo_provider, repo_owner, repo_name);
+	log::info!("Ownership index updated for {}, {} files changed!", repo_name, history.len());
+}
+
+fn merge_path_ownership(repo_provider: &str, repo_owner: &str, repo_name: &str,
+	path: &str, is_dir: bool, authors: &HashMap<String, u64>) {
+	let mut path_ownership = get_path_ownership_from_db(repo_provider, repo_owner, repo_name, path, is_dir)
+		.unwrap_or(PathOwnership::new(path.to_string(), is_dir, HashMap::new()));
+	path_ownership.merge_authors(authors);
+	save_path_ownership_to_db(&path_ownership, repo_provider, repo_owner, repo_name);
+}
+
+fn start_indexing(repo_key: &str) -> bool {
+	let lock_res = INDEXING_REPOS.lock();
+	if lock_res.is_err() {
+		log::error!("[start_indexing] Unable to lock indexing repos map");
+		return false;
+	}
+	let mut indexing_repos = lock_res.expect("Uncaught error in lock_res");
+	let repo_map = indexing_repos.get_or_insert(HashMap::new());
+	if let Some(rerun) = repo_map.get_mut(repo_key) {
+		*rerun = true;
+		return false;
+	}
+	repo_map.insert(repo_key.to_string(), false);
+	true
+}
+
+// True if a refresh was queued meanwhile, the repo then stays marked as indexing
+fn finish_indexing(repo_key: &str) -> bool {
+	let lock_res = INDEXING_REPOS.lock();
+	if lock_res.is_err() {
+		log::error!("[finish_indexing] Unable to lock indexing repos map");
+		return false;
+	}
+	let mut indexing_repos = lock_res.expect("Uncaught error in lock_res");
+	let repo_map_opt = indexing_repos.as_mut();
+	if repo_map_opt.is_none() {
+		return false;
+	}
+	let repo_map = repo_map_opt.expect("Empty repo_map_opt");
+	if repo_map.get(repo_key) == Some(&true) {
+		repo_map.insert(repo_key.to_string(), false);
+		return true;
+	}
+	repo_map.remove(repo_key);
+	false
+}
+
+// All ancestor directories of a file, closest first, ending with the repo root ""
+fn parent_dirs(filepath: &str) -> Vec<String> {
+	let mut dirs = Vec::<String>::new();
+	let mut current = filepath;
+	while let Some(idx) = current.rfind('/') {
+		current = &current[..idx];
+		dirs.push(current.to_string());
+	}
+	dirs.push("".to_string());
+	dirs
+}
+
+// Ownership of the file itself, or of its closest indexed directory
+pub fn nearest_path_ownership(repo_provider: &str, repo_owner: &str, repo_name: &str,
+	filepath: &str) -> Option<PathOwnership> {
+	let file_ownership_opt = get_path_ownership_from_db(repo_provider, repo_owner, repo_name, filepath, false);
+	if file_ownership_opt.is_some() {
+		return file_ownership_opt;
+	}
+	for dir in parent_dirs(filepath) {
+		let dir_ownership_opt = get_path_ownership_from_db(repo_provider, repo_owner, repo_name, &dir, true);
+		if dir_ownership_opt.is_some() {
+			return dir_ownership_opt;
+		}
+	}
+	None
+}
+
+// Changed lines of the PR that blame can't cover, attributed to the owners of their files and directories.
+// Files left out for their size stay out, and files with blamed lines are already counted by blame.
+pub fn ownership_line_map(review: &Review, prhunk: &PrHunkItem) -> HashMap<String, f32> {
+	let mut linemap = HashMap::<String, f32>::new();
+	let fileopt = get_excluded_files(review);
+	if fileopt.is_none() {
+		log::error!("[ownership_line_map] Unable to get changed files for PR {}", review.id());
+		return linemap;
+	}
+	let (excluded_files, smallfiles) = fileopt.expect("Empty fileopt");
+	let blamed_files: HashSet<&String> = prhunk.blamevec().iter()
+		.map(|blame| blame.filepath_raw())
+		.collect();
+	for item in excluded_files.iter().chain(smallfiles.iter()) {
+		let changed_lines = (item.additions() + item.deletions()) as f32;
+		if changed_lines <= 0.0 || blamed_files.contains(item.filepath()) {
+			continue;
+		}
+		if item.additions() + item.deletions() > EXCLUDED_FILE_LINES {
+			continue;
+		}
+		let ownership_opt = nearest_path_ownership(review.provider(),
+			review.repo_owner(), review.repo_name(), item.filepath());
+		if ownership_opt.is_none() {
+			log::debug!("[ownership_line_map] No owners found for {}", item.filepath());
+			continue;
+		}
+		let ownership = ownership_opt.expect("Empty ownership_opt");
+		for (owner, share) in ownership.ranked_owners() {
+			*linemap.entry(owner).or_insert(0.0) += changed_lines * share / 100.0;
+		}
+	}
+	linemap
+}
+
+pub async fn process_ownership_query(message_data: &[u8]) {
+	let data_res = serde_json::from_slice::<Value>(message_data);
+	if data_res.is_err() {
+		let e = data_res.expect_err("No error in data_res");
+		log::error!("[process_ownership_query] Unable to deserialize ownership query: {:?}", e);
+		return;
+	}
+	let data = data_res.expect("Uncaught error in data_res");
+	let repo_provider_opt = parse_string_field_pubsub("repo_provider", &data);
+	let repo_owner_opt = parse_string_field_pubsub("repo_owner", &data);
+	let repo_name_opt = parse_string_field_pubsub("repo_name", &data);
+	if repo_provider_opt.is_none() || repo_owner_opt.is_none() || repo_name_opt.is_none() {
+		log::error!("[process_ownership_query] Missing repo fields in ownership query: {:?}", &data);
+		return;
+	}
+	let repo_provider = repo_provider_opt.expect("Empty repo_provider_opt");
+	let repo_owner = repo_owner_opt.expect("Empty repo_owner_opt");
+	let repo_name = repo_name_opt.expect("Empty repo_name_opt");
+	// no path means ownership of the whole repository
+	let path = data["path"].as_str().unwrap_or("").trim_matches('/').to_string();
+	let ownership = if path.is_empty() {
+		get_path_ownership_from_db(&repo_provider, &repo_owner, &repo_name, "", true)
+	} else {
+		get_path_ownership_from_db(&repo_provider, &repo_owner, &repo_name, &path, true)
+			.or(nearest_path_ownership(&repo_provider, &repo_owner, &repo_name, &path))
+	};
+	let response = OwnershipQueryResponse {
+		repo_provider,
+		repo_owner,
+		repo_name,
+		path,
+		ownership,
+	};
+	send_ownership(&response).await;
+}
+
+async fn send_ownership(response: &OwnershipQueryResponse) {
+	let base_url = env::var("SERVER_URL").expect("SERVER_URL must be set");
+	let ownership_url = format!("{base_url}/api/dpu/ownership");
+	let client = get_client();
+	let post_res = client
+		.post(&ownership_url)
+		.json(response)
+		.send()
+		.await;
+	if post_res.is_err() {
+		let e = post_res.expect_err("No error in post_res");
+		log::error!("[send_ownership] Unable to send ownership to server: {:?}", e);
+		return;
+	}
+	let resp = post_res.expect("Uncaught error in post_res");
+	log::debug!("[send_ownership] Response: {:?}", resp.text().await);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parent_dirs_go_from_closest_to_root() {
+		assert_eq!(parent_dirs("src/core/ownership.rs"),
+			vec!["src/core".to_string(), "src".to_string(), "".to_string()]);
+	}
+
+	#[test]
+	fn top_level_files_only_have_the_root() {
+		assert_eq!(parent_dirs("README.md"), vec!["".to_string()]);
+	}
+
+	#[test]
+	fn queued_refresh_runs_once_after_the_current_one() {
+		let repo_key = "github/owner/queued_refresh";
+		assert!(start_indexing(repo_key));
+		assert!(!start_indexing(repo_key));
+		assert!(!start_indexing(repo_key));
+		assert!(finish_indexing(repo_key));
+		assert!(!finish_indexing(repo_key));
+		assert!(start_indexing(repo_key));
+		assert!(!finish_indexing(repo_key));
+	}
+}
@@ -0,0 +1,112 @@
+use serde_json::Value;
+use tokio::task;
+
+use crate::core::ownership::spawn_ownership_refresh;
+use crate::core::provider::repo_provider;
+use crate::core::utils::send_aliases;
+use crate::db::repo::get_repo_from_db;
+use crate::utils::gitops::{default_branch_name, get_git_aliases, git_fetch};
+
+// Fetches default-branch pushes into the clone so the next review starts from a current repo
+pub fn process_push(repo_provider_str: &str, repo_owner: &str, repo_name: &str, pushed_branches: Vec<String>) {
+	let repo_provider_async = repo_provider_str.to_string();
+	let repo_owner_async = repo_owner.to_string();
+	let repo_name_async = repo_name.to_string();
+	task::spawn(async move {
+		refresh_clone(&repo_provider_async, &repo_owner_async, &repo_name_async, &pushed_branches).await;
+	});
+}
+
+// Branch of a GitHub push event, tag pushes have no branch
+pub fn github_pushed_branches(event_payload: &Value) -> Vec<String> {
+	event_payload["ref"].as_str()
+		.and_then(|pushed_ref| pushed_ref.strip_prefix("refs/heads/"))
+		.map(|branch| vec![branch.to_string()])
+		.unwrap_or_default()
+}
+
+// Branches updated by a Bitbucket repo:push event, deleted branches and tags are skipped
+pub fn bitbucket_pushed_branches(event_payload: &Value) -> Vec<String> {
+	event_payload["push"]["changes"].as_array()
+		.map(|changes| changes.iter()
+			.filter(|change| change["new"]["type"] == "branch")
+			.filter_map(|change| change["new"]["name"].as_str().map(|name| name.to_string()))
+			.collect())
+		.unwrap_or_default()
+}
+
+async fn refresh_clone(repo_provider_str: &str, repo_owner: &str, repo_name: &str, pushed_branches: &Vec<String>) {
+	let repo_opt = get_repo_from_db(repo_provider_str, repo_owner, repo_name);
+	if repo_opt.is_none() {
+		log::debug!("[refresh_clone] Repo {}/{} is not set up, ignoring push", repo_owner, repo_name);
+		return;
+	}
+	let repo = repo_opt.expect("Empty repo_opt");
+	let local_dir_opt = repo.local_dir().to_owned();
+	if local_dir_opt.is_none() {
+		log::error!("[refresh_clone] No clone of {}/{} to refresh", repo_owner, repo_name);
+		return;
+	}
+	let local_dir = local_dir_opt.expect("Empty local_dir_opt");
+	let default_branch_opt = default_branch_name(&local_dir);
+	if default_branch_opt.is_none() {
+		log::error!("[refresh_clone] Unable to get default branch of {}/{}", repo_owner, repo_name);
+		return;
+	}
+	let default_branch = default_branch_opt.expect("Empty default_branch_opt");
+	if !pushed_branches.contains(&default_branch) {
+		log::debug!("[refresh_clone] Push to {:?} in {}/{} is not on default branch {}",
+			pushed_branches, repo_owner, repo_name, &default_branch);
+		return;
+	}
+	let provider_opt = repo_provider(repo_provider_str);
+	if provider_opt.is_none() {
+		log::error!("[refresh_clone] Unsupported repo provider: {}", repo_provider_str);
+		return;
+	}
+	let provider = provider_opt.expect("Empty provider_opt");
+	let access_token_opt = provider.repo_access_token(repo_owner, repo_name).await;
+	if access_token_opt.is_none() {
+		log::error!("[refresh_clone] No access token for {}/{}", repo_owner, repo_name);
+		return;
+	}
+	let access_token = access_token_opt.expect("Empty access_token_opt");
+	log::info!("Fetching push to {} into {}/{}...", &default_branch, repo_owner, repo_name);
+	if !git_fetch(repo.clone_ssh_url(), &local_dir, &access_token, repo_provider_str) {
+		return;
+	}
+	spawn_ownership_refresh(repo_provider_str, repo.owner(), repo.name(), &local_dir);
+	let aliases_opt = get_git_aliases(&repo);
+	if aliases_opt.is_none() {
+		log::error!("[refresh_clone] Unable to get aliases for repo: {}", repo.name());
+		return;
+	}
+	send_aliases(&repo, &aliases_opt.expect("Empty aliases_opt")).await;
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	#[test]
+	fn github_push_names_its_branch() {
+		assert_eq!(github_pushed_branches(&json!({"ref": "refs/heads/main"})), vec!["main".to_string()]);
+		assert_eq!(github_pushed_branches(&json!({"ref": "refs/heads/feature/x"})), vec!["feature/x".to_string()]);
+		assert!(github_pushed_branches(&json!({"ref": "refs/tags/v1.0"})).is_empty());
+		assert!(github_pushed_branches(&json!({})).is_empty());
+	}
+
+	#[test]
+	fn bitbucket_push_names_updated_branches() {
+		let event_payload = json!({"push": {"changes": [
+			{"new": {"type": "branch", "name": "main"}},
+			{"new": {"type": "tag", "name": "v1.0"}},
+			{"new": null, "old": {"type": "branch", "name": "removed"}},
+			{"new": {"type": "branch", "name": "develop"}},
+		]}});
+		assert_eq!(bitbucket_pushed_branches(&event_payload), vec!["main".to_string(), "develop".to_string()]);
+		assert!(bitbucket_pushed_branches(&json!({})).is_empty());
+	}
+}
\ No newline at end of file
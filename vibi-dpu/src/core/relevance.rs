@@ -1,8 +1,11 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::{bitbucket::{self, user::author_from_commit}, core::github, db::review::save_review_to_db, utils::{aliases::get_login_handles, relevance::Relevance, hunk::{HunkMap, PrHunkItem}, user::ProviderEnum}};
+use crate::{core::{approval_history::{approval_status_text, approval_statuses, valid_approver_handles, ApprovalStatus}, assignment::select_reviewers, codeowners::codeowners_relevance, comment_template::{default_comment, load_comment_template, render_comment, CommentVars}, coverage_gate::update_coverage_gate, eligibility::filter_eligible_relevance, ownership::ownership_line_map, provider::{repo_provider, ReviewerSelection}, relevance_details::reviewer_details_text, teams::select_teams}, db::{comment::{get_comment_id_from_db, save_comment_id_to_db}, review::save_review_to_db}, utils::{coverage::CoverageMap, aliases::{cluster_aliases, get_login_handles}, gitops::git_author_names, comment_template::CommentTemplateKind, relevance::{Relevance, RelevanceSource}, hunk::{HunkMap, PrHunkItem}}};
 use crate::utils::review::Review;
-use crate::utils::repo_config::RepoConfig;
+use crate::utils::repo_config::{RelevanceMode, RepoConfig};
+
+// Below this many blamed lines, file and directory owners are also counted
+const OWNERSHIP_FALLBACK_LINES: f32 = 20.0;
 
 pub async fn process_relevance(hunkmap: &HunkMap, review: &Review,
 	repo_config: &mut RepoConfig, access_token: &str, old_review_opt: &Option<Review>,
@@ -12,44 +15,137 @@ pub async fn process_relevance(hunkmap: &HunkMap, review: &Review,
 	for prhunk in hunkmap.prhunkvec() {
 		// calculate number of hunks for each userid
 		let mut review_mut = review.clone();
-		// old review needs to be accesed before calculate_relevance,
-		// which changes relevance vector in db
-		let relevance_vec_opt = calculate_relevance(prhunk, &mut review_mut).await;
+		let relevance_vec_opt = relevance_for_mode(prhunk, &review_mut,
+			repo_config.relevance_mode()).await;
 		if relevance_vec_opt.is_none() {
 			log::error!("[process_relevance] Unable to calculate coverage obj");
 			continue;
 		}
-		let relevance_vec = relevance_vec_opt.expect("Empty coverage_obj_opt");
+		let all_relevance_vec = relevance_vec_opt.expect("Empty coverage_obj_opt");
+		let relevance_vec = filter_eligible_relevance(&all_relevance_vec, review,
+			repo_config, access_token).await;
+		if relevance_vec.is_empty() {
+			log::info!("No eligible reviewers found for PR {}...", review.id());
+			continue;
+		}
+		// old review needs to be accesed before saving,
+		// which changes relevance vector in db
+		review_mut.set_relevance(Some(relevance_vec.clone()));
+		save_review_to_db(&review_mut);
+		let approval_statuses = approval_statuses(review, &relevance_vec, Some(prhunk),
+			None, access_token).await.unwrap_or_default();
+		update_coverage_gate(review, &relevance_vec, &valid_approver_handles(&approval_statuses),
+			repo_config, access_token).await;
 		if repo_config.comment() {
 			// create comment text
-			let comment = comment_text(&relevance_vec, repo_config.auto_assign());
-			// add comment
-			if review.provider().to_string() == ProviderEnum::Bitbucket.to_string() {
-				// TODO - add feature flag check
-				// TODO - add comment change check
-				if did_comment_change(&relevance_vec, &old_review_opt) {
-                    log::info!("Inserting comment on repo {}...", review.repo_name());
-					bitbucket::comment::add_comment(&comment, review, &access_token).await;
-				} else { log::info!("No changes in author relevance, not adding comment...");}
-			}
-			if review.provider().to_string() == ProviderEnum::Github.to_string() {
-                log::info!("Inserting comment on repo {}...", review.repo_name());
-				github::comment::add_comment(&comment, review, &access_token).await;
-			}
+			let comment_template_opt = load_comment_template(review, repo_config,
+				&CommentTemplateKind::Relevance);
+			let comment = comment_text(&relevance_vec, repo_config.auto_assign(),
+				repo_config.relevance_mode(), prhunk, review, &approval_statuses, &comment_template_opt);
+			// the footer names the head commit, so a push always needs an edit
+			let comment_changed = did_comment_change(&relevance_vec, old_review_opt)
+				|| did_head_change(review, old_review_opt);
+			publish_comment(&comment, comment_changed, review, access_token).await;
 		}
 		if repo_config.auto_assign() {
 			log::info!("Auto assigning reviewers for repo {}...", review.repo_name());
 			log::debug!("[process_relevance] review.provider() = {:?}", review.provider());
-			if review.provider().to_string() == ProviderEnum::Bitbucket.to_string() {
-				add_bitbucket_reviewers(&prhunk, hunkmap, review, &access_token).await;
-			}
-			if review.provider().to_string() == ProviderEnum::Github.to_string() {
-				add_github_reviewers(review, &relevance_vec, &access_token).await;
+			let selected_vec = select_reviewers(&relevance_vec, review, repo_config, access_token).await;
+			let selected_teams = select_teams(&relevance_vec, review, repo_config, access_token).await;
+			log::debug!("[process_relevance] selected reviewers = {:?}, teams = {:?}", &selected_vec, &selected_teams);
+			let selection = ReviewerSelection {
+				relevance_vec: &selected_vec,
+				teams: &selected_teams,
+				prhunk,
+				repo_config,
+			};
+			if let Some(provider) = repo_provider(review.provider()) {
+				provider.add_reviewers(&selection, review, access_token).await;
 			}
 		}
 	}
 }
 
+async fn relevance_for_mode(prhunk: &PrHunkItem, review: &Review, relevance_mode: &RelevanceMode) -> Option<Vec<Relevance>> {
+	if relevance_mode == &RelevanceMode::BlameOnly {
+		return calculate_relevance(prhunk, review).await;
+	}
+	let owners_vec = codeowners_relevance(review).await;
+	if owners_vec.is_empty() {
+		log::info!("No CODEOWNERS entries for changed files, using blame relevance...");
+		return calculate_relevance(prhunk, review).await;
+	}
+	if relevance_mode == &RelevanceMode::OwnersOnly {
+		return Some(owners_vec);
+	}
+	let blame_vec_opt = calculate_relevance(prhunk, review).await;
+	if blame_vec_opt.is_none() {
+		return Some(owners_vec);
+	}
+	let blame_vec = blame_vec_opt.expect("Empty blame_vec_opt");
+	if relevance_mode == &RelevanceMode::OwnersRequired {
+		return Some(required_owners_and_blame(owners_vec, blame_vec));
+	}
+	// both sides add up to 100%, weigh them equally
+	let mut relevance_vec = Vec::<Relevance>::new();
+	for mut relevance_obj in blame_vec.into_iter().chain(owners_vec) {
+		relevance_obj.set_relevance_num(relevance_obj.relevance_num() / 2.0);
+		relevance_vec.push(relevance_obj);
+	}
+	Some(relevance_vec)
+}
+
+// Owners are requested no matter their share of the lines, so only blame ranks.
+// Owners keep no relevance of their own and don't dilute the blame percentages.
+fn required_owners_and_blame(owners_vec: Vec<Relevance>, blame_vec: Vec<Relevance>) -> Vec<Relevance> {
+	let mut relevance_vec = blame_vec;
+	for mut owner_obj in owners_vec {
+		owner_obj.set_relevance_num(0.0);
+		relevance_vec.push(owner_obj);
+	}
+	relevance_vec
+}
+
+// Edits the comment posted on an earlier run, a new one is only added the first time
+// or when the earlier one was deleted
+async fn publish_comment(comment_text: &str, comment_changed: bool, review: &Review, access_token: &str) {
+	let repo_provider_opt = repo_provider(review.provider());
+	if repo_provider_opt.is_none() {
+		log::error!("[publish_comment] Unsupported repo provider {}", review.provider());
+		return;
+	}
+	let provider = repo_provider_opt.expect("Empty repo_provider_opt");
+	let comment = comment_with_footer(comment_text, review);
+	let comment_id_opt = get_comment_id_from_db(review);
+	if let Some(comment_id) = comment_id_opt {
+		if !comment_changed {
+			log::info!("No changes in author relevance or head commit, not updating comment...");
+			return;
+		}
+		log::info!("Updating comment {} on repo {}...", &comment_id, review.repo_name());
+		let edited_opt = provider.edit_comment(&comment_id, &comment, review, access_token).await;
+		if edited_opt.is_none() {
+			log::error!("[publish_comment] Unable to update comment {} for PR {}", &comment_id, review.id());
+			return;
+		}
+		if edited_opt.expect("Empty edited_opt") {
+			return;
+		}
+		log::info!("Comment {} was deleted, adding a new one...", &comment_id);
+	}
+	log::info!("Inserting comment on repo {}...", review.repo_name());
+	let new_comment_id_opt = provider.add_comment(&comment, review, access_token).await;
+	if new_comment_id_opt.is_none() {
+		log::error!("[publish_comment] No comment id returned for PR {}", review.id());
+		return;
+	}
+	save_comment_id_to_db(review, &new_comment_id_opt.expect("Empty new_comment_id_opt"));
+}
+
+fn comment_with_footer(comment_text: &str, review: &Review) -> String {
+	format!("{}\n_Last updated for commit {}_\n", comment_text, review.pr_head_commit())
+}
+
 fn did_comment_change(relevance_vec: &Vec<Relevance>, old_review_opt: &Option<Review>) -> bool {
 	if old_review_opt.is_none() {
 		log::debug!("[did_comment_change] No review record found in db, inserting comment...");
@@ -66,6 +162,11 @@ fn did_comment_change(relevance_vec: &Vec<Relevance>, old_review_opt: &Option<Re
 	return did_relevance_change(relevance_vec, &old_relevance_vec);
 }
 
+fn did_head_change(review: &Review, old_review_opt: &Option<Review>) -> bool {
+	old_review_opt.as_ref()
+		.is_none_or(|old_review| old_review.pr_head_commit() != review.pr_head_commit())
+}
+
 fn did_relevance_change(relevance_new: &[Relevance], relevance_old: &[Relevance]) -> bool {
     // Ensure both vectors have the same length
     if relevance_new.len() != relevance_old.len() {
@@ -103,49 +204,7 @@ fn did_relevance_change(relevance_new: &[Relevance], relevance_old: &[Relevance]
     return false;
 }
 
-async fn add_github_reviewers(review: &Review, relevance_vec: &Vec<Relevance>, access_token: &str) {
-    let mut reviewers: HashSet<String> = HashSet::new();
-    for relevance_obj in relevance_vec {
-        let provider_ids_opt = relevance_obj.handles();
-        if provider_ids_opt.is_none() {
-            continue;
-        }
-        let provider_ids = provider_ids_opt.to_owned().expect("Empty provider_ids_opt");
-        let provider_id_opt = provider_ids.first();
-        if provider_id_opt.is_none() {
-            continue;
-        }
-        let provider_id = provider_id_opt.expect("Empty provider_id_opt");
-        reviewers.insert(provider_id.to_owned());
-    }
-    if reviewers.is_empty() {
-        return;
-    }
-    let reviewers_vec: Vec<String> = reviewers.into_iter().collect();
-    github::reviewer::add_reviewers(&reviewers_vec, review, access_token).await;
-}
-
-async fn add_bitbucket_reviewers(prhunk: &PrHunkItem, hunkmap: &HunkMap, review: &Review, access_token: &str) {
-    let mut author_set: HashSet<String> = HashSet::new();
-    author_set.insert(prhunk.author().to_string());
-    for blame in prhunk.blamevec() {
-        let blame_author_opt = author_from_commit(blame.commit(),
-            hunkmap.repo_name(), hunkmap.repo_owner()).await;
-        if blame_author_opt.is_none() {
-            log::error!("[process_relevance] Unable to get blame author from bb for commit: {}", &blame.commit());
-            continue;
-        }
-        let blame_author = blame_author_opt.expect("Empty blame_author_opt");
-        let author_uuid = blame_author.uuid();
-        if author_set.contains(author_uuid) {
-            continue;
-        }
-        bitbucket::reviewer::add_reviewers(&blame_author, review, &access_token).await;
-        author_set.insert(author_uuid.to_string());
-    }
-}
-
-async fn calculate_relevance(prhunk: &PrHunkItem, review: &mut Review) -> Option<Vec<Relevance>>{
+async fn calculate_relevance(prhunk: &PrHunkItem, review: &Review) -> Option<Vec<Relevance>>{
     let mut relevance_floatmap = HashMap::<String, f32>::new();
     let mut total = 0.0;
     for blame in prhunk.blamevec() {
@@ -163,31 +222,64 @@ async fn calculate_relevance(prhunk: &PrHunkItem, review: &mut Review) -> Option
             relevance_floatmap.insert(author_id, num_lines);
         }
     }
+    let mut ownership_authors = HashSet::<String>::new();
+    if total < OWNERSHIP_FALLBACK_LINES {
+        // new files and small changes have little or no blame to go on
+        log::debug!("[calculate_relevance] Only {} blamed lines, adding ownership index", total);
+        let blame_authors: HashSet<String> = relevance_floatmap.keys().cloned().collect();
+        for (owner, num_lines) in ownership_line_map(review, prhunk) {
+            total += num_lines;
+            if !blame_authors.contains(&owner) {
+                ownership_authors.insert(owner.to_owned());
+            }
+            *relevance_floatmap.entry(owner).or_insert(0.0) += num_lines;
+        }
+    }
     let mut relevance_vec = Vec::<Relevance>::new();
     if total <= 0.0 {
         return None;
     } 
-    for (blame_author, relevance) in relevance_floatmap.iter_mut() {
-        *relevance = *relevance / total * 100.0;
-        let formatted_value = format!("{:.2}", *relevance);
-        let provider_ids = get_login_handles(blame_author, review).await;
-        let relevance_obj = Relevance::new(
+    // aliases of one person are merged, the one with most lines stands for them
+    let aliases: Vec<String> = relevance_floatmap.keys().cloned().collect();
+    let author_names = git_author_names(review.clone_dir()).unwrap_or_default();
+    for mut alias_cluster in cluster_aliases(&aliases, review.provider(), &author_names) {
+        let alias_lines = |alias: &String| relevance_floatmap.get(alias).copied().unwrap_or(0.0);
+        alias_cluster.sort_by(|a, b| alias_lines(b).partial_cmp(&alias_lines(a))
+            .unwrap_or(std::cmp::Ordering::Equal).then(a.cmp(b)));
+        let relevance = alias_cluster.iter().map(alias_lines).sum::<f32>() / total * 100.0;
+        let formatted_value = format!("{:.2}", relevance);
+        let mut provider_ids = Vec::<String>::new();
+        for alias in &alias_cluster {
+            for handle in get_login_handles(alias, review).await.unwrap_or_default() {
+                if !provider_ids.contains(&handle) {
+                    provider_ids.push(handle);
+                }
+            }
+        }
+        let source = if alias_cluster.iter().all(|alias| ownership_authors.contains(alias)) {
+            RelevanceSource::Ownership
+        } else {
+            RelevanceSource::Blame
+        };
+        let blame_author = alias_cluster.remove(0);
+        let mut relevance_obj = Relevance::new(
             review.provider().to_owned(),
-            blame_author.to_owned(), 
-            formatted_value.to_owned(), 
-            *relevance, 
-            provider_ids);
+            blame_author,
+            formatted_value,
+            relevance,
+            if provider_ids.is_empty() { None } else { Some(provider_ids) },
+            source);
+        relevance_obj.set_merged_aliases(alias_cluster);
         relevance_vec.push(relevance_obj);
     }
-    review.set_relevance(Some(relevance_vec.clone()));
-    save_review_to_db(review);
     return Some(relevance_vec);
 }
 
-fn comment_text(relevance_vec: &Vec<Relevance>, auto_assign: bool) -> String {
-    let mut comment = "Relevant users for this PR:\n\n".to_string();  // Added two newlines
-    comment += "| Contributor Name/Alias  | Relevance |\n";  // Added a newline at the end
-    comment += "| -------------- | --------------- |\n";  // Added a newline at the end
+fn comment_text(relevance_vec: &Vec<Relevance>, auto_assign: bool, relevance_mode: &RelevanceMode,
+    prhunk: &PrHunkItem, review: &Review, approval_statuses: &[ApprovalStatus],
+    template_opt: &Option<String>) -> String {
+    let mut reviewer_table = "| Contributor Name/Alias  | Relevance | Source |\n".to_string();  // Added a newline at the end
+    reviewer_table += "| -------------- | --------------- | --------------- |\n";  // Added a newline at the end
 
     let (deduplicated_relevance_map, unmapped_aliases) = deduplicated_relevance_vec_for_comment(relevance_vec);
     let mut deduplicated_relevance_vec: Vec<(&Vec<String>, &f32)> = deduplicated_relevance_map.iter().collect();
@@ -199,25 +291,77 @@ fn comment_text(relevance_vec: &Vec<Relevance>, auto_assign: bool) -> String {
             let provider_id = provider_id_opt.expect("Empty provider_id_opt");
             log::debug!("[comment-text] provider_id: {:?}", provider_id);
             let formatted_relevance_value = format!("{:.2}", *relevance);
-            comment += &format!("| {} | {}% |\n", provider_id, formatted_relevance_value);
+            let row_relevance = row_relevance_vec(relevance_vec, provider_ids);
+            let sources = relevance_sources_text(&row_relevance, relevance_mode);
+            reviewer_table += &format!("| {} | {}% | {} |\n", provider_id, formatted_relevance_value, sources);
         }
     }
-
-    if !&unmapped_aliases.is_empty() {
-        comment += "\n\n";
-        comment += &format!("Missing profile handles for {} aliases. [Go to your Vibinex settings page](https://vibinex.com/settings) to map aliases to profile handles.", unmapped_aliases.len());
+    let mut coverage_map_obj = CoverageMap::new(review.provider().to_owned());
+    coverage_map_obj.calculate_coverage_map(relevance_vec.to_owned(), valid_approver_handles(approval_statuses));
+    let comment_vars = CommentVars {
+        reviewer_table,
+        reviewer_details: reviewer_details(relevance_vec, relevance_mode, prhunk, review),
+        approvals: approval_status_text(approval_statuses),
+        coverage: coverage_map_obj.coverage_total_str(),
+        unmapped_aliases_count: unmapped_aliases.len(),
+        auto_assign,
+    };
+    if let Some(template) = template_opt {
+        if let Some(comment) = render_comment(template, &comment_vars, review) {
+            return comment;
+        }
     }
+    default_comment(&comment_vars)
+}
 
-    if auto_assign {
-        comment += "\n\n";
-        comment += "Auto assigning to relevant reviewers.";
+// Why each comment row is relevant, rows in the same order as the reviewer table
+pub fn reviewer_details(relevance_vec: &Vec<Relevance>, relevance_mode: &RelevanceMode,
+    prhunk: &PrHunkItem, review: &Review) -> String {
+    let (deduplicated_relevance_map, _) = deduplicated_relevance_vec_for_comment(relevance_vec);
+    let mut deduplicated_relevance_vec: Vec<(&Vec<String>, &f32)> = deduplicated_relevance_map.iter().collect();
+    deduplicated_relevance_vec.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    let mut details = String::new();
+    for (provider_ids, _) in &deduplicated_relevance_vec {
+        let provider_id_opt = provider_ids.iter().next();
+        if provider_id_opt.is_none() {
+            continue;
+        }
+        let provider_id = provider_id_opt.expect("Empty provider_id_opt");
+        let row_relevance = row_relevance_vec(relevance_vec, provider_ids);
+        let row_details = reviewer_details_text(provider_id, &row_relevance, prhunk, review);
+        if row_details.is_empty() {
+            let sources = relevance_sources_text(&row_relevance, relevance_mode);
+            details += &format!("{}: no changed lines authored, suggested from {}\n\n", provider_id, sources);
+        } else {
+            details += &row_details;
+        }
     }
-    comment += "\n\n";
-    comment += "If you are a relevant reviewer, you can use the [Vibinex browser extension](https://chromewebstore.google.com/detail/vibinex-code-review/jafgelpkkkopeaefadkdjcmnicgpcncc) to see parts of the PR relevant to you\n";  // Added a newline at the end
-    comment += "Relevance of the reviewer is calculated based on the git blame information of the PR. To know more, hit us up at contact@vibinex.com.\n\n";  // Added two newlines
-    comment += "To change comment and auto-assign settings, go to [your Vibinex settings page.](https://vibinex.com/u)\n";  // Added a newline at the end
+    details
+}
 
-    return comment;
+// All relevance entries merged into one comment row
+fn row_relevance_vec<'a>(relevance_vec: &'a [Relevance], row_ids: &[String]) -> Vec<&'a Relevance> {
+    relevance_vec.iter()
+        .filter(|relevance_obj| match relevance_obj.handles() {
+            Some(handles) => handles.iter().any(|handle| row_ids.contains(handle)),
+            None => row_ids.contains(relevance_obj.git_alias()),
+        })
+        .collect()
+}
+
+fn relevance_sources_text(row_relevance: &Vec<&Relevance>, relevance_mode: &RelevanceMode) -> String {
+    let mut sources = Vec::<String>::new();
+    for relevance_obj in row_relevance {
+        let mut label = relevance_obj.source().label().to_string();
+        if relevance_obj.source() == &RelevanceSource::Codeowners
+            && relevance_mode == &RelevanceMode::OwnersRequired {
+            label += " (required)";
+        }
+        if !sources.contains(&label) {
+            sources.push(label);
+        }
+    }
+    sources.join(", ")
 }
 
 pub fn deduplicated_relevance_vec_for_comment(relevance_vec: &Vec<Relevance>) -> (HashMap<Vec<String>, f32>, Vec<String>) {
@@ -255,3 +399,52 @@ pub fn deduplicated_relevance_vec_for_comment(relevance_vec: &Vec<Relevance>) ->
 
     (combined_relevance_map, unmapped_aliases)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::relevance::test_relevance;
+	use crate::utils::review::test_review;
+
+	fn review_with_relevance(pr_head_commit: &str, relevance_vec: Vec<Relevance>) -> Review {
+		let mut review = test_review("1", pr_head_commit);
+		review.set_relevance(Some(relevance_vec));
+		review
+	}
+
+	#[test]
+	fn footer_names_the_head_commit() {
+		let review = test_review("1", "abc123");
+		assert_eq!(comment_with_footer("Relevant users", &review),
+			"Relevant users\n_Last updated for commit abc123_\n");
+	}
+
+	#[test]
+	fn head_change_is_detected_against_the_stored_review() {
+		let review = test_review("1", "new");
+		assert!(did_head_change(&review, &None));
+		assert!(did_head_change(&review, &Some(test_review("1", "old"))));
+		assert!(!did_head_change(&review, &Some(test_review("1", "new"))));
+	}
+
+	#[test]
+	fn comment_changes_without_stored_relevance() {
+		let relevance_vec = vec![test_relevance("alice", 60.0, &[])];
+		assert!(did_comment_change(&relevance_vec, &None));
+		assert!(did_comment_change(&relevance_vec, &Some(test_review("1", "abc"))));
+	}
+
+	#[test]
+	fn comment_changes_only_with_integer_relevance_or_aliases() {
+		let old_review = Some(review_with_relevance("abc", vec![
+			test_relevance("alice", 60.2, &[]), test_relevance("bob", 39.8, &[])]));
+		let same_integers = vec![test_relevance("bob", 39.1, &[]), test_relevance("alice", 60.9, &[])];
+		assert!(!did_comment_change(&same_integers, &old_review));
+		let new_integers = vec![test_relevance("alice", 61.0, &[]), test_relevance("bob", 39.0, &[])];
+		assert!(did_comment_change(&new_integers, &old_review));
+		let new_alias = vec![test_relevance("alice", 60.0, &[]), test_relevance("carol", 40.0, &[])];
+		assert!(did_comment_change(&new_alias, &old_review));
+		let fewer_aliases = vec![test_relevance("alice", 100.0, &[])];
+		assert!(did_comment_change(&fewer_aliases, &old_review));
+	}
+}
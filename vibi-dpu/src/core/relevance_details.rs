@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use crate::core::provider::repo_provider;
+use crate::utils::hunk::{BlameItem, PrHunkItem};
+use crate::utils::relevance::Relevance;
+use crate::utils::review::Review;
+
+// Changed lines each reviewer authored, grouped per file, with links into the PR diff.
+// Providers rendering html get a collapsible <details> block, Bitbucket strips html so it gets a plain list.
+pub fn reviewer_details_text(provider_id: &str, row_relevance: &[&Relevance],
+	prhunk: &PrHunkItem, review: &Review) -> String {
+	let aliases: Vec<&String> = row_relevance.iter().flat_map(|relevance_obj| relevance_obj.aliases()).collect();
+	// filepath -> (blame item for links, line ranges)
+	let mut filemap = BTreeMap::<String, (&BlameItem, Vec<(usize, usize)>)>::new();
+	for blame in prhunk.blamevec() {
+		if !aliases.contains(&blame.author()) {
+			continue;
+		}
+		let start_res = blame.line_start().parse::<usize>();
+		let end_res = blame.line_end().parse::<usize>();
+		if start_res.is_err() || end_res.is_err() {
+			log::error!("[reviewer_details_text] Invalid line range in blame: {:?}", blame);
+			continue;
+		}
+		let range = (start_res.expect("Uncaught error in start_res"), end_res.expect("Uncaught error in end_res"));
+		filemap.entry(blame.filepath_raw().to_owned())
+			.or_insert((blame, Vec::new())).1.push(range);
+	}
+	if filemap.is_empty() {
+		return String::new();
+	}
+	let renders_html = repo_provider(review.provider()).is_some_and(|provider| provider.renders_html());
+	let total_lines: usize = filemap.values()
+		.flat_map(|(_, ranges)| ranges.iter().map(|(start, end)| end - start + 1))
+		.sum();
+	let summary = format!("{}: {} lines in {} files", provider_id, total_lines, filemap.len());
+	let mut details = String::new();
+	if renders_html {
+		details += &format!("<details>\n<summary>{}</summary>\n\n", summary);
+	} else {
+		details += &format!("**{}**\n\n", summary);
+	}
+	for (filepath, (blame, ranges)) in filemap.iter_mut() {
+		ranges.sort();
+		let range_links: Vec<String> = ranges.iter()
+			.map(|(start, end)| format!("[{}]({})", range_text(*start, *end),
+				diff_anchor_url(review, filepath, blame, Some((*start, *end)))))
+			.collect();
+		details += &format!("- [`{}`]({}) lines {}\n", filepath,
+			diff_anchor_url(review, filepath, blame, None), range_links.join(", "));
+	}
+	if renders_html {
+		details += "\n</details>\n";
+	}
+	details += "\n";
+	details
+}
+
+fn range_text(start: usize, end: usize) -> String {
+	if start == end {
+		return start.to_string();
+	}
+	format!("{}-{}", start, end)
+}
+
+// Line numbers are from the base commit, so they point at the old side of the diff
+fn diff_anchor_url(review: &Review, filepath: &str, blame: &BlameItem, range_opt: Option<(usize, usize)>) -> String {
+	repo_provider(review.provider())
+		.map(|provider| provider.diff_url(review, filepath, blame, range_opt))
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::github::config::github_web_url;
+	use crate::utils::relevance::test_relevance;
+	use crate::utils::review::test_review;
+
+	fn blame(author: &str, line_start: usize, line_end: usize, filepath: &str) -> BlameItem {
+		BlameItem::new(author.to_string(), "0".to_string(), line_start.to_string(), line_end.to_string(),
+			format!("{}-digest", filepath), "commit".to_string(), filepath.to_string())
+	}
+
+	#[test]
+	fn ranges_are_single_lines_or_spans() {
+		assert_eq!(range_text(7, 7), "7");
+		assert_eq!(range_text(3, 9), "3-9");
+	}
+
+	#[test]
+	fn lists_files_and_lines_of_all_merged_aliases() {
+		let prhunk = PrHunkItem::new("1".to_string(), "author".to_string(), vec![
+			blame("a@work.com", 10, 12, "src/main.rs"),
+			blame("bob@work.com", 1, 50, "src/main.rs"),
+			blame("a@home.com", 2, 2, "src/lib.rs"),
+			blame("a@work.com", 3, 4, "src/main.rs"),
+		]);
+		let mut relevance_obj = test_relevance("a@work.com", 50.0, &["alice"]);
+		relevance_obj.set_merged_aliases(vec!["a@home.com".to_string()]);
+		let details = reviewer_details_text("alice", &[&relevance_obj], &prhunk, &test_review("1", "head"));
+		let pr_files_url = format!("{}/owner/repo/pull/1/files", github_web_url());
+		assert_eq!(details, format!("<details>\n<summary>alice: 6 lines in 2 files</summary>\n\n\
+			- [`src/lib.rs`]({url}#diff-src/lib.rs-digest) lines [2]({url}#diff-src/lib.rs-digestL2-L2)\n\
+			- [`src/main.rs`]({url}#diff-src/main.rs-digest) lines [3-4]({url}#diff-src/main.rs-digestL3-L4), \
+			[10-12]({url}#diff-src/main.rs-digestL10-L12)\n\n</details>\n\n", url = pr_files_url));
+	}
+
+	#[test]
+	fn reviewers_without_changed_lines_get_no_details() {
+		let prhunk = PrHunkItem::new("1".to_string(), "author".to_string(), vec![blame("bob@work.com", 1, 5, "src/main.rs")]);
+		let relevance_obj = test_relevance("a@work.com", 50.0, &["alice"]);
+		assert_eq!(reviewer_details_text("alice", &[&relevance_obj], &prhunk, &test_review("1", "head")), "");
+	}
+}
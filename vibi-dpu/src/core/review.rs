@@ -3,9 +3,10 @@ use std::env;
 use serde_json::Value;
 
 use crate::{
-    core::{relevance::process_relevance, utils::get_access_token},
+    core::{ownership::spawn_ownership_refresh, provider::repo_provider, relevance::process_relevance, utils::get_access_token},
     db::{
         hunk::{get_hunk_from_db, store_hunkmap_to_db},
+        prs::pr_info_from_db,
         repo::get_clone_url_clone_dir,
         repo_config::save_repo_config_to_db,
         review::{get_review_from_db, save_review_to_db},
@@ -110,6 +111,8 @@ pub async fn commit_check(review: &Review, access_token: &str) {
 		|| !commit_exists(&review.pr_head_commit(), &review.clone_dir()) {
 		log::info!("Executing git pull on repo {}...", &review.repo_name());
 		git_pull(review, access_token).await;
+		spawn_ownership_refresh(review.provider(), review.repo_owner(),
+			review.repo_name(), review.clone_dir());
 	}
 }
 
@@ -122,15 +125,10 @@ fn parse_review(message_data: &Vec<u8>) -> (Option<(Review, RepoConfig)>, Option
 	}
 	let deserialized_data = data_res.expect("Uncaught error in deserializing message_data");
 	log::debug!("[parse_review] deserialized_data == {:?}", &deserialized_data["eventPayload"]["repository"]);
-	let repo_provider = deserialized_data["repositoryProvider"].to_string().trim_matches('"').to_string();
+	let provider = deserialized_data["repositoryProvider"].to_string().trim_matches('"').to_lowercase();
 
-	let (review_opt, old_review_opt): (Option<Review>, Option<Review>) = if repo_provider == ProviderEnum::Bitbucket.to_string().to_lowercase() {
-		create_and_save_bitbucket_review_object(&deserialized_data)
-	} else if repo_provider == ProviderEnum::Github.to_string().to_lowercase() {
-		(create_and_save_github_review_object(&deserialized_data), None)
-	} else {
-		(None, None)
-	};
+	let (review_opt, old_review_opt): (Option<Review>, Option<Review>) = repo_provider(&provider)
+		.map_or((None, None), |repo_provider| repo_provider.review_from_webhook(&deserialized_data));
 
 	if review_opt.is_none() {
 		log::error!("[parse_review] | empty review object");
@@ -174,7 +172,7 @@ fn publish_hunkmap(hunkmap: &HunkMap) {
 	});
 }
 
-fn create_and_save_bitbucket_review_object(deserialized_data: &Value) -> (Option<Review>, Option<Review>) {
+pub fn create_and_save_bitbucket_review_object(deserialized_data: &Value) -> (Option<Review>, Option<Review>) {
 	log::debug!("[create_and_save_bitbucket_review_object] deserialised_data {}", deserialized_data);
 	let workspace_name = deserialized_data["eventPayload"]["repository"]["workspace"]["slug"].to_string().trim_matches('"').to_string();
 	let repo_name = deserialized_data["eventPayload"]["repository"]["name"].to_string().trim_matches('"').to_string();
@@ -188,7 +186,7 @@ fn create_and_save_bitbucket_review_object(deserialized_data: &Value) -> (Option
 		return (None, old_review_opt);
 	}
 	let (clone_url, clone_dir) = clone_opt.expect("Empty clone_opt");
-	let review = Review::new(
+	let mut review = Review::new(
 		deserialized_data["eventPayload"]["pullrequest"]["destination"]["commit"]["hash"].to_string().replace("\"", ""),
 		deserialized_data["eventPayload"]["pullrequest"]["source"]["commit"]["hash"].to_string().replace("\"", ""),
 		pr_id.clone(),
@@ -201,37 +199,125 @@ fn create_and_save_bitbucket_review_object(deserialized_data: &Value) -> (Option
 		deserialized_data["eventPayload"]["pullrequest"]["author"]["uuid"].to_string().replace("\"", ""),
 		None,
 	);
+	// relevance handles are display names, the uuid alone never matches them
+	review.set_author_handle(deserialized_data["eventPayload"]["pullrequest"]["author"]["display_name"]
+		.as_str().map(str::to_string));
 	log::debug!("[create_and_save_bitbucket_review_object] bitbucket review object= {:?}", &review);
 	save_review_to_db(&review);
-	return (Some(review), old_review_opt);
+	(Some(review), old_review_opt)
 }
 
-fn create_and_save_github_review_object(deserialized_data: &Value) -> Option<Review> {
+// Also used for Gitea, whose pull request payloads follow GitHub's shape
+pub fn create_and_save_github_review_object(deserialized_data: &Value, repo_provider: &str) -> (Option<Review>, Option<Review>) {
 	log::debug!("[create_and_save_github_review_object] deserialised_data {}", deserialized_data);
 	let repo_owner = deserialized_data["eventPayload"]["repository"]["owner"]["login"].to_string().trim_matches('"').to_string();
 	let repo_name = deserialized_data["eventPayload"]["repository"]["name"].to_string().trim_matches('"').to_string();
-	let repo_provider = ProviderEnum::Github.to_string().to_lowercase();
+	let repo_provider = repo_provider.to_string();
+	let pr_id = deserialized_data["eventPayload"]["pull_request"]["number"].to_string().trim_matches('"').to_string();
+	let old_review_opt = get_review_from_db(&repo_name, &repo_owner,
+		&repo_provider, &pr_id);
 	let clone_opt = get_clone_url_clone_dir(&repo_provider, &repo_owner, &repo_name);
 	if clone_opt.is_none() {
 		log::error!("[create_and_save_github_review_object] Unable to get clone url and directory for bitbucket review");
-		return None;
+		return (None, old_review_opt);
 	}
 	let (clone_url, clone_dir) = clone_opt.expect("Empty clone_opt");
-	let pr_id = deserialized_data["eventPayload"]["pull_request"]["number"].to_string().trim_matches('"').to_string();
-	let review = Review::new(
+	let mut review = Review::new(
 		deserialized_data["eventPayload"]["pull_request"]["base"]["sha"].to_string().replace("\"", ""),
 		deserialized_data["eventPayload"]["pull_request"]["head"]["sha"].to_string().replace("\"", ""),
 		pr_id.clone(),
 		repo_name.clone(),
 		repo_owner.clone(),
 		repo_provider.clone(),
-		format!("github/{}/{}/{}", &repo_owner, &repo_name, &pr_id),
+		format!("{}/{}/{}/{}", &repo_provider, &repo_owner, &repo_name, &pr_id),
 		clone_dir,
 		clone_url,
 		deserialized_data["eventPayload"]["pull_request"]["user"]["id"].to_string().replace("\"", ""),
 		None,
 	);
-	log::debug!("[create_and_save_github_review_object] github review object = {:?}", &review);
+	review.set_author_handle(deserialized_data["eventPayload"]["pull_request"]["user"]["login"]
+		.as_str().map(str::to_string));
+	log::debug!("[create_and_save_github_review_object] {} review object = {:?}", &repo_provider, &review);
 	save_review_to_db(&review);
-	return Some(review);
-}
\ No newline at end of file
+	(Some(review), old_review_opt)
+}
+// Commits and author come from the MR info stored when the webhook was processed
+pub fn create_and_save_gitlab_review_object(deserialized_data: &Value) -> (Option<Review>, Option<Review>) {
+	log::debug!("[create_and_save_gitlab_review_object] deserialised_data {}", deserialized_data);
+	let path_with_namespace = deserialized_data["eventPayload"]["project"]["path_with_namespace"].to_string().trim_matches('"').to_string();
+	let (repo_owner, repo_name) = path_with_namespace.rsplit_once('/').unwrap_or_default();
+	let repo_provider = ProviderEnum::Gitlab.to_string();
+	let mr_iid = deserialized_data["eventPayload"]["object_attributes"]["iid"].to_string().trim_matches('"').to_string();
+	let old_review_opt = get_review_from_db(repo_name, repo_owner,
+		&repo_provider, &mr_iid);
+	let pr_info_opt = pr_info_from_db(repo_owner, repo_name, &mr_iid, &repo_provider);
+	if pr_info_opt.is_none() {
+		log::error!("[create_and_save_gitlab_review_object] No MR info in db for {}/{}", &path_with_namespace, &mr_iid);
+		return (None, old_review_opt);
+	}
+	let pr_info = pr_info_opt.expect("Empty pr_info_opt");
+	let clone_opt = get_clone_url_clone_dir(&repo_provider, repo_owner, repo_name);
+	if clone_opt.is_none() {
+		log::error!("[create_and_save_gitlab_review_object] Unable to get clone url and directory for gitlab review");
+		return (None, old_review_opt);
+	}
+	let (clone_url, clone_dir) = clone_opt.expect("Empty clone_opt");
+	let mut review = Review::new(
+		pr_info.base_head_commit.clone(),
+		pr_info.pr_head_commit.clone(),
+		mr_iid.clone(),
+		repo_name.to_string(),
+		repo_owner.to_string(),
+		repo_provider.clone(),
+		format!("{}/{}/{}/{}", &repo_provider, repo_owner, repo_name, &mr_iid),
+		clone_dir,
+		clone_url,
+		pr_info.author.clone().unwrap_or_default(),
+		None,
+	);
+	// the hook's user is whoever triggered it, only the author's username is their handle
+	let event_user = &deserialized_data["eventPayload"]["user"];
+	let author_handle_opt = if event_user["id"] == deserialized_data["eventPayload"]["object_attributes"]["author_id"] {
+		event_user["username"].as_str().map(str::to_string)
+	} else {
+		pr_info.author.clone()
+	};
+	review.set_author_handle(author_handle_opt);
+	log::debug!("[create_and_save_gitlab_review_object] gitlab review object = {:?}", &review);
+	save_review_to_db(&review);
+	(Some(review), old_review_opt)
+}
+
+pub fn create_and_save_bitbucket_server_review_object(deserialized_data: &Value) -> (Option<Review>, Option<Review>) {
+	log::debug!("[create_and_save_bitbucket_server_review_object] deserialised_data {}", deserialized_data);
+	let pull_request = &deserialized_data["eventPayload"]["pullRequest"];
+	let project_key = pull_request["toRef"]["repository"]["project"]["key"].to_string().trim_matches('"').to_string();
+	let repo_slug = pull_request["toRef"]["repository"]["slug"].to_string().trim_matches('"').to_string();
+	let repo_provider = ProviderEnum::BitbucketServer.to_string();
+	let pr_id = pull_request["id"].to_string().trim_matches('"').to_string();
+	let old_review_opt = get_review_from_db(&repo_slug, &project_key,
+		&repo_provider, &pr_id);
+	let clone_opt = get_clone_url_clone_dir(&repo_provider, &project_key, &repo_slug);
+	if clone_opt.is_none() {
+		log::error!("[create_and_save_bitbucket_server_review_object] Unable to get clone url and directory for bitbucket data center review");
+		return (None, old_review_opt);
+	}
+	let (clone_url, clone_dir) = clone_opt.expect("Empty clone_opt");
+	let mut review = Review::new(
+		pull_request["toRef"]["latestCommit"].to_string().replace("\"", ""),
+		pull_request["fromRef"]["latestCommit"].to_string().replace("\"", ""),
+		pr_id.clone(),
+		repo_slug.clone(),
+		project_key.clone(),
+		repo_provider.clone(),
+		format!("{}/{}/{}/{}", &repo_provider, &project_key, &repo_slug, &pr_id),
+		clone_dir,
+		clone_url,
+		pull_request["author"]["user"]["slug"].to_string().replace("\"", ""),
+		None,
+	);
+	review.set_author_handle(pull_request["author"]["user"]["name"].as_str().map(str::to_string));
+	log::debug!("[create_and_save_bitbucket_server_review_object] bitbucket data center review object = {:?}", &review);
+	save_review_to_db(&review);
+	(Some(review), old_review_opt)
+}
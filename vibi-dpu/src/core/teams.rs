@@ -0,0 +1,194 @@
+use crate::core::provider::{repo_provider, RepoProvider};
+use crate::utils::codeowners::is_team_owner;
+use crate::utils::relevance::{Relevance, RelevanceSource};
+use crate::utils::repo_config::{RelevanceMode, RepoConfig};
+use crate::utils::review::Review;
+
+#[derive(Debug, Clone)]
+pub struct TeamReviewer {
+	team: String,
+	slug: String,
+	relevance_num: f32,
+	member_handles: Vec<String>,
+}
+
+impl TeamReviewer {
+	// Public getter methods
+	pub fn team(&self) -> &String {
+		&self.team
+	}
+
+	pub fn slug(&self) -> &String {
+		&self.slug
+	}
+
+	pub fn relevance_num(&self) -> f32 {
+		self.relevance_num
+	}
+
+	pub fn member_handles(&self) -> &Vec<String> {
+		&self.member_handles
+	}
+}
+
+// Teams from the repo config and CODEOWNERS whose members together pass the team threshold
+pub async fn select_teams(relevance_vec: &[Relevance], review: &Review,
+	repo_config: &RepoConfig, access_token: &str) -> Vec<TeamReviewer> {
+	let mut selected_teams = Vec::<TeamReviewer>::new();
+	let provider_opt = repo_provider(review.provider());
+	if provider_opt.is_none() {
+		return selected_teams;
+	}
+	let provider = provider_opt.expect("Empty provider_opt");
+	for team in candidate_teams(relevance_vec, repo_config) {
+		let team_opt = resolve_team(provider.as_ref(), &team, review, access_token).await;
+		if team_opt.is_none() {
+			if is_required_team(&team, relevance_vec, repo_config) {
+				log::warn!("Required owner team {} can't be resolved, it won't be requested...", &team);
+			}
+			continue;
+		}
+		let mut team_reviewer = team_opt.expect("Empty team_opt");
+		team_reviewer.relevance_num = team_relevance(provider.as_ref(), &team_reviewer, relevance_vec);
+		log::debug!("[select_teams] team {} has combined relevance {}", &team, team_reviewer.relevance_num);
+		if is_required_team(&team, relevance_vec, repo_config) {
+			log::info!("Team {} is a required owner...", &team);
+			selected_teams.push(team_reviewer);
+			continue;
+		}
+		if team_reviewer.relevance_num < repo_config.team_min_relevance() {
+			continue;
+		}
+		log::info!("Team {} passes relevance threshold with {:.2}%...", &team, team_reviewer.relevance_num);
+		selected_teams.push(team_reviewer);
+	}
+	selected_teams
+}
+
+fn candidate_teams(relevance_vec: &[Relevance], repo_config: &RepoConfig) -> Vec<String> {
+	let mut teams = Vec::<String>::new();
+	let owner_teams = relevance_vec.iter()
+		.filter(|relevance_obj| is_team_entry(relevance_obj))
+		.map(|relevance_obj| relevance_obj.git_alias().to_owned());
+	for team in repo_config.team_reviewers().iter().cloned().chain(owner_teams) {
+		let team_name = team.trim_start_matches('@').to_string();
+		if !team_name.is_empty() && !teams.contains(&team_name) {
+			teams.push(team_name);
+		}
+	}
+	teams
+}
+
+async fn resolve_team(provider: &dyn RepoProvider, team: &str, review: &Review, access_token: &str) -> Option<TeamReviewer> {
+	let (org, slug) = team_path(team, review);
+	let members_opt = provider.team_members(&org, &slug, review, access_token).await;
+	if members_opt.is_none() {
+		log::debug!("[resolve_team] No members found for team {}", team);
+		return None;
+	}
+	Some(TeamReviewer {
+		team: team.to_string(),
+		slug,
+		relevance_num: 0.0,
+		member_handles: members_opt.expect("Empty members_opt"),
+	})
+}
+
+// `org/team` on GitHub, `group` or `teams/group` on Bitbucket, owner defaults to the repo owner
+fn team_path(team: &str, review: &Review) -> (String, String) {
+	match team.rsplit_once('/') {
+		Some((org, slug)) => (org.to_string(), slug.to_string()),
+		None => (review.repo_owner().to_string(), team.to_string()),
+	}
+}
+
+// CODEOWNERS teams are requested through the team path, never as individual handles
+pub fn is_team_entry(relevance_obj: &Relevance) -> bool {
+	relevance_obj.source() == &RelevanceSource::Codeowners && is_team_owner(relevance_obj.git_alias())
+}
+
+fn is_required_team(team: &str, relevance_vec: &[Relevance], repo_config: &RepoConfig) -> bool {
+	repo_config.relevance_mode() == &RelevanceMode::OwnersRequired
+		&& relevance_vec.iter().any(|relevance_obj| is_team_entry(relevance_obj)
+			&& relevance_obj.git_alias().trim_start_matches('@') == team)
+}
+
+fn team_relevance(provider: &dyn RepoProvider, team_reviewer: &TeamReviewer, relevance_vec: &[Relevance]) -> f32 {
+	let mut relevance_num = 0.0;
+	for relevance_obj in relevance_vec {
+		let handles = relevance_obj.handles().to_owned().unwrap_or_default();
+		let is_team_entry = handles.iter().any(|handle| handle == team_reviewer.team());
+		let member_ids: Vec<String> = handles.iter()
+			.flat_map(|handle| provider.member_ids(handle))
+			.collect();
+		if is_team_entry || is_team_member(team_reviewer, &member_ids) {
+			relevance_num += relevance_obj.relevance_num();
+		}
+	}
+	relevance_num
+}
+
+// Ids as given by the provider's member_ids
+pub fn is_team_member(team_reviewer: &TeamReviewer, member_ids: &[String]) -> bool {
+	member_ids.iter().any(|member_id| team_reviewer.member_handles().iter()
+		.any(|member| member.eq_ignore_ascii_case(member_id)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::github::provider::GithubProvider;
+	use crate::utils::relevance::test_relevance;
+	use crate::utils::repo_config::test_repo_config;
+	use crate::utils::review::test_review;
+
+	fn owner_team(team: &str) -> Relevance {
+		Relevance::new("github".to_string(), team.to_string(), "0.00".to_string(), 0.0,
+			Some(vec![team.trim_start_matches('@').to_string()]), RelevanceSource::Codeowners)
+	}
+
+	fn team_reviewer(team: &str, members: &[&str]) -> TeamReviewer {
+		TeamReviewer {
+			team: team.to_string(),
+			slug: team.rsplit('/').next().unwrap_or(team).to_string(),
+			relevance_num: 0.0,
+			member_handles: members.iter().map(|member| member.to_string()).collect(),
+		}
+	}
+
+	#[test]
+	fn candidates_come_from_config_and_codeowners_once() {
+		let relevance_vec = vec![owner_team("@org/payments"), test_relevance("org/ignored", 10.0, &["a"]),
+			owner_team("@org/infra")];
+		let config = test_repo_config(serde_json::json!({"team_reviewers": ["@org/payments", "org/web", ""]}));
+		assert_eq!(candidate_teams(&relevance_vec, &config), vec!["org/payments", "org/web", "org/infra"]);
+	}
+
+	#[test]
+	fn team_path_defaults_to_the_repo_owner() {
+		let review = test_review("1", "head");
+		assert_eq!(team_path("org/payments", &review), ("org".to_string(), "payments".to_string()));
+		assert_eq!(team_path("teams/devs", &review), ("teams".to_string(), "devs".to_string()));
+		assert_eq!(team_path("devs", &review), ("owner".to_string(), "devs".to_string()));
+	}
+
+	#[test]
+	fn teams_are_required_only_as_owners_in_owners_required_mode() {
+		let relevance_vec = vec![owner_team("@org/payments")];
+		let required_config = test_repo_config(serde_json::json!({"relevance_mode": "owners_required"}));
+		assert!(is_required_team("org/payments", &relevance_vec, &required_config));
+		assert!(!is_required_team("org/web", &relevance_vec, &required_config));
+		assert!(!is_required_team("org/payments", &relevance_vec, &test_repo_config(serde_json::json!({}))));
+	}
+
+	#[test]
+	fn team_relevance_sums_members_and_the_team_entry() {
+		let team = team_reviewer("org/payments", &["Alice", "bob"]);
+		let relevance_vec = vec![test_relevance("a", 40.0, &["alice"]), test_relevance("b", 25.0, &["bob"]),
+			test_relevance("c", 20.0, &["carol"]), test_relevance("d", 10.0, &[]),
+			test_relevance("owners", 5.0, &["org/payments"])];
+		assert_eq!(team_relevance(&GithubProvider, &team, &relevance_vec), 70.0);
+		assert!(is_team_member(&team, &["ALICE".to_string()]));
+		assert!(!is_team_member(&team, &["carol".to_string()]));
+	}
+}
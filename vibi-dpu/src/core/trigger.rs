@@ -1,6 +1,6 @@
 use serde_json::Value;
 
-use crate::{core::{review::{commit_check, process_review_changes, send_hunkmap}, utils::get_access_token}, db::{repo::get_clone_url_clone_dir, repo_config::save_repo_config_to_db, review::get_review_from_db}, github::prs::get_and_store_pr_info, utils::{parsing::parse_string_field_pubsub, pr_info::PrInfo, repo_config::RepoConfig, review::Review, user::ProviderEnum}};
+use crate::{core::{provider::repo_provider, review::{commit_check, process_review_changes, send_hunkmap}}, db::{prs::update_pr_info_in_db, repo::get_clone_url_clone_dir, repo_config::save_repo_config_to_db, review::get_review_from_db}, utils::{parsing::parse_string_field_pubsub, pr_info::PrInfo, repo_config::RepoConfig, review::Review}};
 
 #[derive(Debug)]
 struct TriggerReview {
@@ -20,12 +20,14 @@ pub async fn process_trigger(message_data: &Vec<u8>) {
 	}
 	let (trigger_review, repo_config) = parse_res.expect("Empty parse_res");
 	log::info!("Processing PR: {} in repo: {}...", &trigger_review.pr_number, &trigger_review.repo_name);
-	// get access token
-	if trigger_review.repo_provider != ProviderEnum::Github.to_string().to_lowercase() {
-		log::error!("[process_trigger] Not implemented for non github providers");
+	let repo_provider_opt = repo_provider(&trigger_review.repo_provider);
+	if repo_provider_opt.is_none() {
+		log::error!("[process_trigger] Unsupported repo provider: {:?}", &trigger_review);
 		return;
 	}
-	let access_token_opt = get_access_token(&None, &trigger_review.repo_provider).await;
+	let provider = repo_provider_opt.expect("Empty repo_provider_opt");
+	// get access token
+	let access_token_opt = provider.repo_access_token(&trigger_review.repo_owner, &trigger_review.repo_name).await;
 	if access_token_opt.is_none() {
 		log::error!("[process_trigger] Unable to retrieve access token, failing, message: {:?}",
 			&trigger_review);
@@ -33,12 +35,15 @@ pub async fn process_trigger(message_data: &Vec<u8>) {
 	}
 	let access_token = access_token_opt.expect("Empty access_token_opt");
 	// get pr information and update review object
-	let pr_info_opt = get_and_store_pr_info(&trigger_review.repo_owner,
-		&trigger_review.repo_name, &access_token, &trigger_review.pr_number).await;
+	let pr_info_opt = provider.pr_info(&trigger_review.repo_owner,
+		&trigger_review.repo_name, &trigger_review.pr_number, &access_token).await;
 	if pr_info_opt.is_none() {
 		log::error!("[process_trigger] Unable to get pr info from provider");
 		return;
 	}
+	update_pr_info_in_db(&trigger_review.repo_owner, &trigger_review.repo_name,
+		pr_info_opt.as_ref().expect("Empty pr_info_opt"), &trigger_review.pr_number,
+		&trigger_review.repo_provider).await;
 	let review_opt = get_review_obj(&trigger_review, &pr_info_opt).await;
 	if review_opt.is_none() {
 		log::error!("[process_trigger] Unable to get review details: {:?}", &trigger_review);
@@ -121,7 +126,7 @@ async fn get_review_obj(trigger_review: &TriggerReview, pr_info_opt: &Option<PrI
 		trigger_review.repo_name.clone(),
 		trigger_review.repo_owner.clone(),
 		trigger_review.repo_provider.clone(),
-		format!("github/{}/{}/{}", 
+		format!("{}/{}/{}/{}", &trigger_review.repo_provider,
 			&trigger_review.repo_owner, &trigger_review.repo_name, &trigger_review.pr_number),
 		clone_dir,
 		clone_url,
@@ -0,0 +1,247 @@
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::core::provider::{repo_provider, RepoProvider};
+use crate::db::github::auth::{delete_github_auth_info_from_db, get_exact_repo_installation_from_db};
+use crate::db::purge::delete_repo_records;
+use crate::db::repo::{get_repo_from_db, get_repos_from_db};
+use crate::utils::github_auth_info::GithubAuthInfo;
+use crate::utils::user::ProviderEnum;
+
+// Removes one repo when owner and name are given, an app installation when installation_id is given,
+// and everything of the provider only when all is set
+#[derive(Debug, Deserialize)]
+struct UninstallMessage {
+	repository_provider: String,
+	repo_owner: Option<String>,
+	repo_name: Option<String>,
+	installation_id: Option<String>,
+	#[serde(default)]
+	all: bool,
+}
+
+pub async fn process_uninstall(message_data: &[u8]) {
+	let msg_res = serde_json::from_slice::<UninstallMessage>(message_data);
+	if msg_res.is_err() {
+		let e = msg_res.expect_err("No error in msg_res");
+		log::error!("[process_uninstall] Unable to deserialize uninstall message: {:?}", e);
+		return;
+	}
+	let msg = msg_res.expect("Uncaught error in msg_res");
+	let provider_str = msg.repository_provider.to_lowercase();
+	let provider_opt = repo_provider(&provider_str);
+	if provider_opt.is_none() {
+		log::error!("[process_uninstall] Unsupported repo provider: {}", &provider_str);
+		return;
+	}
+	let provider = provider_opt.expect("Empty provider_opt");
+	let has_repo = msg.repo_owner.is_some() || msg.repo_name.is_some();
+	let scope_count = [has_repo, msg.installation_id.is_some(), msg.all].iter()
+		.filter(|is_set| **is_set).count();
+	if scope_count != 1 {
+		log::error!("[process_uninstall] Uninstall message needs exactly one of repo, installation_id or all: {:?}", &msg);
+		return;
+	}
+	if has_repo {
+		if msg.repo_owner.is_none() || msg.repo_name.is_none() {
+			log::error!("[process_uninstall] Uninstall message needs both repo_owner and repo_name: {:?}", &msg);
+			return;
+		}
+		let repo_owner = msg.repo_owner.as_ref().expect("Empty repo_owner");
+		let repo_name = msg.repo_name.as_ref().expect("Empty repo_name");
+		remove_repo(&provider_str, repo_owner, repo_name, true).await;
+		return;
+	}
+	if let Some(installation_id) = &msg.installation_id {
+		provider.remove_installation(installation_id);
+		return;
+	}
+	remove_provider(&provider_str, provider.as_ref()).await;
+}
+
+// GitHub app events, by the time they arrive the app has lost access so webhooks can't be deleted anymore
+pub async fn process_installation_event(event_type: &str, event_payload: &Value) {
+	let action = event_payload["action"].to_string().trim_matches('"').to_string();
+	let installation_id = event_payload["installation"]["id"].to_string().trim_matches('"').to_string();
+	log::debug!("[process_installation_event] {} {} for installation {}", event_type, &action, &installation_id);
+	if event_type == "installation" && action == "deleted" {
+		log::info!("GitHub app uninstalled from installation {}, removing its repos...", &installation_id);
+		remove_github_installation(&installation_id);
+		return;
+	}
+	if event_type == "installation_repositories" && action == "removed" {
+		let removed_repos = event_payload["repositories_removed"].as_array().cloned().unwrap_or_default();
+		for removed_repo in removed_repos {
+			let full_name = removed_repo["full_name"].to_string().trim_matches('"').to_string();
+			let full_name_split: Vec<&str> = full_name.splitn(2, '/').collect();
+			if full_name_split.len() != 2 {
+				log::error!("[process_installation_event] Invalid repo full_name: {}", &full_name);
+				continue;
+			}
+			log::info!("Repo {} removed from installation {}...", &full_name, &installation_id);
+			remove_repo(&ProviderEnum::Github.to_string(), full_name_split[0], full_name_split[1], false).await;
+		}
+	}
+}
+
+async fn remove_repo(repo_provider_str: &str, repo_owner: &str, repo_name: &str, delete_webhooks: bool) {
+	if delete_webhooks {
+		remove_repo_webhooks(repo_provider_str, repo_owner, repo_name).await;
+	}
+	let repo_opt = get_repo_from_db(repo_provider_str, repo_owner, repo_name);
+	if let Some(local_dir) = repo_opt.and_then(|repo| repo.local_dir().to_owned()) {
+		remove_clone(&local_dir);
+	}
+	delete_repo_records(repo_provider_str, repo_owner, repo_name);
+	log::info!("Removed {} repo {}/{}", repo_provider_str, repo_owner, repo_name);
+}
+
+async fn remove_repo_webhooks(repo_provider_str: &str, repo_owner: &str, repo_name: &str) {
+	let provider_opt = repo_provider(repo_provider_str);
+	if provider_opt.is_none() {
+		log::error!("[remove_repo_webhooks] Unsupported repo provider: {}", repo_provider_str);
+		return;
+	}
+	let provider = provider_opt.expect("Empty provider_opt");
+	let access_token_opt = provider.repo_access_token(repo_owner, repo_name).await;
+	if access_token_opt.is_none() {
+		log::error!("[remove_repo_webhooks] No access token for {}/{}, leaving webhooks", repo_owner, repo_name);
+		return;
+	}
+	let access_token = access_token_opt.expect("Empty access_token_opt");
+	provider.remove_webhooks(repo_owner, repo_name, &access_token).await;
+}
+
+fn remove_clone(local_dir: &str) {
+	if !Path::new(local_dir).exists() {
+		return;
+	}
+	if let Err(e) = std::fs::remove_dir_all(local_dir) {
+		log::error!("[remove_clone] Unable to remove clone dir {}: {:?}", local_dir, e);
+	}
+}
+
+pub fn remove_github_installation(installation_id: &str) {
+	let github = ProviderEnum::Github.to_string();
+	for repo in get_repos_from_db(&github) {
+		let repo_installation_opt = get_exact_repo_installation_from_db(repo.workspace(), repo.name());
+		if repo_installation_opt.as_deref() != Some(installation_id) {
+			continue;
+		}
+		if let Some(local_dir) = repo.local_dir() {
+			remove_clone(local_dir);
+		}
+		delete_repo_records(&github, repo.workspace(), repo.name());
+	}
+	delete_github_auth_info_from_db(installation_id);
+	let file_auth_opt = GithubAuthInfo::load_from_file();
+	if file_auth_opt.is_some_and(|file_auth| file_auth.installation_id().as_deref() == Some(installation_id)) {
+		GithubAuthInfo::remove_file();
+	}
+	log::info!("Removed GitHub installation {}", installation_id);
+}
+
+// Webhooks go first while the stored token still works, auth is purged last
+async fn remove_provider(repo_provider_str: &str, provider: &dyn RepoProvider) {
+	for repo in get_repos_from_db(repo_provider_str) {
+		remove_repo(repo_provider_str, repo.workspace(), repo.name(), true).await;
+	}
+	provider.purge_auth();
+	log::info!("Removed all {} repos and auth", repo_provider_str);
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+	use crate::db::github::auth::save_repo_installation_to_db;
+	use crate::db::repo::save_repo_to_db;
+	use crate::db::review::{get_review_from_db, save_review_to_db};
+	use crate::utils::repo::Repository;
+	use crate::utils::review::Review;
+
+	fn seed_repo(repo_provider_str: &str, repo_owner: &str, repo_name: &str) {
+		save_repo_to_db(&Repository::new(repo_name.to_string(), String::new(), repo_owner.to_string(), true,
+			String::new(), None, None, repo_owner.to_string(), None, repo_provider_str.to_string()));
+		save_review_to_db(&Review::new("base".to_string(), "abc1234".to_string(), "1".to_string(),
+			repo_name.to_string(), repo_owner.to_string(), repo_provider_str.to_string(),
+			format!("{}/{}/{}/1", repo_provider_str, repo_owner, repo_name),
+			"/nonexistent/clone_dir".to_string(), "clone_url".to_string(), "author".to_string(), None));
+	}
+
+	fn has_records(repo_provider_str: &str, repo_owner: &str, repo_name: &str) -> bool {
+		get_repo_from_db(repo_provider_str, repo_owner, repo_name).is_some()
+			|| get_review_from_db(repo_name, repo_owner, repo_provider_str, "1").is_some()
+	}
+
+	async fn uninstall(message: Value) {
+		process_uninstall(&serde_json::to_vec(&message).expect("Unable to serialize message")).await;
+	}
+
+	#[tokio::test]
+	async fn repo_scope_removes_only_that_repo() {
+		seed_repo("gitea", "uninstall-repo", "a");
+		seed_repo("gitea", "uninstall-repo", "b");
+		uninstall(json!({"repository_provider": "Gitea", "repo_owner": "uninstall-repo", "repo_name": "a"})).await;
+		assert!(!has_records("gitea", "uninstall-repo", "a"));
+		assert!(has_records("gitea", "uninstall-repo", "b"));
+	}
+
+	#[tokio::test]
+	async fn installation_scope_removes_repos_of_that_installation() {
+		seed_repo("github", "uninstall-installation", "a");
+		seed_repo("github", "uninstall-installation", "b");
+		save_repo_installation_to_db("uninstall-installation", "a", "111");
+		save_repo_installation_to_db("uninstall-installation", "b", "222");
+		uninstall(json!({"repository_provider": "github", "installation_id": "111"})).await;
+		assert!(!has_records("github", "uninstall-installation", "a"));
+		assert!(get_exact_repo_installation_from_db("uninstall-installation", "a").is_none());
+		assert!(has_records("github", "uninstall-installation", "b"));
+	}
+
+	#[tokio::test]
+	async fn all_scope_removes_every_repo_of_the_provider() {
+		seed_repo("bitbucket-server", "uninstall-all", "a");
+		seed_repo("bitbucket-server", "uninstall-all-other", "b");
+		seed_repo("gitlab", "uninstall-all", "c");
+		uninstall(json!({"repository_provider": "bitbucket-server", "all": true})).await;
+		assert!(!has_records("bitbucket-server", "uninstall-all", "a"));
+		assert!(!has_records("bitbucket-server", "uninstall-all-other", "b"));
+		assert!(has_records("gitlab", "uninstall-all", "c"));
+	}
+
+	#[tokio::test]
+	async fn message_needs_exactly_one_scope() {
+		seed_repo("gitea", "uninstall-ambiguous", "a");
+		uninstall(json!({"repository_provider": "gitea", "repo_owner": "uninstall-ambiguous", "repo_name": "a", "all": true})).await;
+		uninstall(json!({"repository_provider": "gitea", "repo_owner": "uninstall-ambiguous"})).await;
+		uninstall(json!({"repository_provider": "gitea"})).await;
+		assert!(has_records("gitea", "uninstall-ambiguous", "a"));
+	}
+
+	#[tokio::test]
+	async fn repositories_removed_event_removes_listed_repos() {
+		seed_repo("github", "uninstall-event", "a");
+		seed_repo("github", "uninstall-event", "b");
+		let payload = json!({
+			"action": "removed",
+			"installation": {"id": 333},
+			"repositories_removed": [{"full_name": "uninstall-event/a"}, {"full_name": "invalid"}],
+		});
+		process_installation_event("installation_repositories", &payload).await;
+		assert!(!has_records("github", "uninstall-event", "a"));
+		assert!(has_records("github", "uninstall-event", "b"));
+	}
+
+	#[tokio::test]
+	async fn installation_deleted_event_removes_the_installation() {
+		seed_repo("github", "uninstall-deleted", "a");
+		save_repo_installation_to_db("uninstall-deleted", "a", "444");
+		process_installation_event("installation", &json!({"action": "deleted", "installation": {"id": 444}})).await;
+		assert!(!has_records("github", "uninstall-deleted", "a"));
+		process_installation_event("installation", &json!({"action": "created", "installation": {"id": 555}})).await;
+	}
+}
\ No newline at end of file
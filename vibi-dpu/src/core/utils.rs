@@ -5,16 +5,14 @@ use reqwest::Response;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::bitbucket;
+use crate::core::provider::repo_provider;
 use crate::db::aliases::update_handles_in_db;
-use crate::github;
 use crate::health::status::send_status_failed;
 use crate::health::status::send_status_success;
 use crate::utils::repo::Repository;
 use crate::utils::reqwest_client::get_client;
 use crate::utils::review::Review;
 use crate::utils::setup_info::SetupInfo;
-use crate::utils::user::ProviderEnum;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct PublishRequest {
@@ -36,8 +34,9 @@ struct AliasResponse {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct AliasResponseHandles {
 	git_alias: String,
-	github: Option<Vec<String>>,
-	bitbucket: Option<Vec<String>>
+	// handles keyed by provider name, e.g. github or bitbucket-server
+	#[serde(flatten)]
+	handles: HashMap<String, Option<Vec<String>>>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -145,19 +144,13 @@ pub async fn get_handles_from_server(review: &Review) -> Option<HashMap<String,
 	let alias_handles = alias_response.aliases.to_owned();
 	let mut aliases_map = HashMap::<String, Vec<String>>::new();
 	for alias_handle in alias_handles {
-		if review.provider().to_owned() == ProviderEnum::Github.to_string()
-			&& alias_handle.github.is_some() {
-				let gh_handles = alias_handle.github.expect("Empty github handles");
-				update_handles_in_db(&alias_handle.git_alias, &review.provider(), gh_handles.clone());
-				aliases_map.insert(alias_handle.git_alias, gh_handles);
-				continue;
-		}
-		if review.provider().to_owned() == ProviderEnum::Bitbucket.to_string()
-			&& alias_handle.bitbucket.is_some() {
-				let bb_handles = alias_handle.bitbucket.expect("Empty github handles");
-				update_handles_in_db(&alias_handle.git_alias, &review.provider(), bb_handles.clone());
-				aliases_map.insert(alias_handle.git_alias, bb_handles);
+		let handles_opt = alias_handle.handles.get(review.provider()).cloned().flatten();
+		if handles_opt.is_none() {
+			continue;
 		}
+		let handles = handles_opt.expect("Empty handles_opt");
+		update_handles_in_db(&alias_handle.git_alias, review.provider(), handles.clone());
+		aliases_map.insert(alias_handle.git_alias, handles);
 	}
 	if aliases_map.is_empty() {
 		log::error!(
@@ -169,29 +162,18 @@ pub async fn get_handles_from_server(review: &Review) -> Option<HashMap<String,
 }
 
 pub async fn get_access_token (review: &Option<Review>, provider: &str) -> Option<String> {
-	let access_token: String;
-	if provider == ProviderEnum::Bitbucket.to_string().to_lowercase() {
-		let access_token_opt = bitbucket::auth::refresh_git_auth(review).await;
-		if access_token_opt.is_none() {
-			log::error!("[get_access_token] Unable to get access token, review: {:?}",
-				&review);
-			return None;
-		}
-		access_token = access_token_opt.expect("Empty access_token_opt");
-	} 
-	else if provider == ProviderEnum::Github.to_string().to_lowercase(){
-		let access_token_opt = github::auth::gh_access_token(review).await;
-		if access_token_opt.is_none() {
-			log::error!("[get_access_token] Unable to get access token, review: {:?}",
-				&review);
-			return None;
-		}
-		access_token = access_token_opt.expect("Empty access_token");
-	} else {
-		log::error!("[git pull] | repo provider is not github or bitbucket");
+	let repo_provider_opt = repo_provider(provider);
+	if repo_provider_opt.is_none() {
+		log::error!("[get_access_token] Unsupported repo provider: {}", provider);
+		return None;
+	}
+	let access_token_opt = repo_provider_opt.expect("Empty repo_provider_opt").access_token(review).await;
+	if access_token_opt.is_none() {
+		log::error!("[get_access_token] Unable to get access token, review: {:?}",
+			&review);
 		return None;
 	}
-	return Some(access_token);
+	access_token_opt
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -0,0 +1,215 @@
+use std::env;
+use std::time::Duration;
+
+use tokio::task;
+
+use crate::core::provider::repo_provider;
+use crate::db::repo::get_repos_from_db;
+use crate::utils::user::ProviderEnum;
+
+const DEFAULT_RECONCILE_INTERVAL_SECS: u64 = 6 * 60 * 60;
+
+// A webhook as the provider reports it
+#[derive(Debug, Clone)]
+pub struct HookState {
+	pub id: String,
+	pub url: String,
+	pub events: Vec<String>,
+	pub active: bool,
+	pub has_secret: bool,
+}
+
+// The webhook every repo should have, pointing at the current SERVER_URL
+#[derive(Debug, Clone)]
+pub struct DesiredHook {
+	pub url: String,
+	pub callback_path: String,
+	pub events: Vec<String>,
+	pub secret: Option<String>,
+}
+
+impl DesiredHook {
+	pub fn new(callback_path: &str, events: &[&str]) -> Self {
+		let server_url = env::var("SERVER_URL").expect("SERVER_URL must be set");
+		Self {
+			url: format!("{}{}", server_url.trim_end_matches('/'), callback_path),
+			callback_path: callback_path.to_string(),
+			events: events.iter().map(|event| event.to_string()).collect(),
+			secret: env::var("WEBHOOK_SECRET").ok().filter(|secret| !secret.is_empty()),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HookAction {
+	Create,
+	Update(String),
+	Delete(String),
+}
+
+// Hooks whose url ends with our callback path are ours, including ones left behind by an old SERVER_URL.
+// One of them is kept and brought in line with the desired config, the rest are removed.
+pub fn plan_reconcile(desired: &DesiredHook, actual: &[HookState]) -> Vec<HookAction> {
+	let our_hooks: Vec<&HookState> = actual.iter()
+		.filter(|hook| is_our_hook(&desired.callback_path, hook))
+		.collect();
+	let keeper_opt = our_hooks.iter()
+		.find(|hook| hook.url == desired.url)
+		.or(our_hooks.first());
+	if keeper_opt.is_none() {
+		return vec![HookAction::Create];
+	}
+	let keeper = keeper_opt.expect("Empty keeper_opt");
+	let mut actions = Vec::<HookAction>::new();
+	if needs_update(desired, keeper) {
+		actions.push(HookAction::Update(keeper.id.clone()));
+	}
+	for hook in our_hooks.iter().filter(|hook| hook.id != keeper.id) {
+		actions.push(HookAction::Delete(hook.id.clone()));
+	}
+	actions
+}
+
+// Ids of hooks pointing at our callback on any server, for removal on uninstall
+pub fn our_hook_ids(callback_path: &str, actual: &[HookState]) -> Vec<String> {
+	actual.iter()
+		.filter(|hook| is_our_hook(callback_path, hook))
+		.map(|hook| hook.id.clone())
+		.collect()
+}
+
+fn is_our_hook(callback_path: &str, hook: &HookState) -> bool {
+	hook.url.trim_end_matches('/').ends_with(callback_path)
+}
+
+// Providers never return the secret itself, so a hook with some secret set is taken as matching
+fn needs_update(desired: &DesiredHook, hook: &HookState) -> bool {
+	let mut desired_events = desired.events.clone();
+	let mut hook_events = hook.events.clone();
+	desired_events.sort();
+	hook_events.sort();
+	hook.url != desired.url
+		|| !hook.active
+		|| desired_events != hook_events
+		|| (desired.secret.is_some() && !hook.has_secret)
+}
+
+// Reconciles hooks of every repo set up on this dpu, for the providers that support it
+pub async fn reconcile_all_webhooks() {
+	let providers = [ProviderEnum::Github, ProviderEnum::Bitbucket];
+	for provider_enum in providers {
+		let repos = get_repos_from_db(&provider_enum.to_string());
+		if repos.is_empty() {
+			continue;
+		}
+		let provider_opt = repo_provider(&provider_enum.to_string());
+		if provider_opt.is_none() {
+			continue;
+		}
+		let provider = provider_opt.expect("Empty provider_opt");
+		log::info!("Reconciling webhooks of {} {} repos...", repos.len(), &provider_enum);
+		for repo in repos {
+			let access_token_opt = provider.repo_access_token(repo.workspace(), repo.name()).await;
+			if access_token_opt.is_none() {
+				log::error!("[reconcile_all_webhooks] No access token for {}/{}", repo.workspace(), repo.name());
+				continue;
+			}
+			let access_token = access_token_opt.expect("Empty access_token_opt");
+			provider.reconcile_webhooks(repo.workspace(), repo.name(), &access_token).await;
+		}
+	}
+}
+
+// Runs reconciliation now and then every WEBHOOK_RECONCILE_INTERVAL_SECS
+pub fn spawn_webhook_reconciler() {
+	let interval_secs = env::var("WEBHOOK_RECONCILE_INTERVAL_SECS").ok()
+		.and_then(|interval| interval.trim().parse::<u64>().ok())
+		.filter(|interval| *interval > 0)
+		.unwrap_or(DEFAULT_RECONCILE_INTERVAL_SECS);
+	task::spawn(async move {
+		let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+		loop {
+			interval.tick().await;
+			reconcile_all_webhooks().await;
+		}
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const CALLBACK_PATH: &str = "/api/github/callbacks/webhook";
+
+	fn desired(secret: Option<&str>) -> DesiredHook {
+		DesiredHook {
+			url: format!("https://dpu.example.com{}", CALLBACK_PATH),
+			callback_path: CALLBACK_PATH.to_string(),
+			events: vec!["pull_request".to_string(), "push".to_string()],
+			secret: secret.map(|secret| secret.to_string()),
+		}
+	}
+
+	fn hook(id: &str, url: &str, events: &[&str], active: bool, has_secret: bool) -> HookState {
+		HookState {
+			id: id.to_string(),
+			url: url.to_string(),
+			events: events.iter().map(|event| event.to_string()).collect(),
+			active,
+			has_secret,
+		}
+	}
+
+	#[test]
+	fn creates_when_no_hook_is_ours() {
+		let actual = vec![hook("1", "https://ci.example.com/hook", &["push"], true, false)];
+		assert_eq!(plan_reconcile(&desired(None), &actual), vec![HookAction::Create]);
+		assert_eq!(plan_reconcile(&desired(None), &[]), vec![HookAction::Create]);
+	}
+
+	#[test]
+	fn matching_hook_needs_nothing() {
+		let actual = vec![hook("1", "https://dpu.example.com/api/github/callbacks/webhook",
+			&["push", "pull_request"], true, true)];
+		assert!(plan_reconcile(&desired(Some("secret")), &actual).is_empty());
+	}
+
+	#[test]
+	fn keeps_current_url_and_deletes_old_server_hooks() {
+		let actual = vec![
+			hook("1", "https://old.example.com/api/github/callbacks/webhook", &["pull_request", "push"], true, false),
+			hook("2", "https://dpu.example.com/api/github/callbacks/webhook", &["pull_request", "push"], true, false),
+			hook("3", "https://dpu.example.com/api/github/callbacks/webhook/", &["pull_request", "push"], true, false),
+		];
+		assert_eq!(plan_reconcile(&desired(None), &actual),
+			vec![HookAction::Delete("1".to_string()), HookAction::Delete("3".to_string())]);
+	}
+
+	#[test]
+	fn old_server_hook_is_repointed_when_it_is_the_only_one() {
+		let actual = vec![hook("1", "https://old.example.com/api/github/callbacks/webhook",
+			&["pull_request", "push"], true, false)];
+		assert_eq!(plan_reconcile(&desired(None), &actual), vec![HookAction::Update("1".to_string())]);
+	}
+
+	#[test]
+	fn updates_on_events_active_or_secret_mismatch() {
+		let url = "https://dpu.example.com/api/github/callbacks/webhook";
+		let missing_event = vec![hook("1", url, &["pull_request"], true, true)];
+		let inactive = vec![hook("1", url, &["pull_request", "push"], false, true)];
+		let no_secret = vec![hook("1", url, &["pull_request", "push"], true, false)];
+		for actual in [missing_event, inactive, no_secret] {
+			assert_eq!(plan_reconcile(&desired(Some("secret")), &actual), vec![HookAction::Update("1".to_string())]);
+		}
+	}
+
+	#[test]
+	fn our_hook_ids_skips_foreign_hooks() {
+		let actual = vec![
+			hook("1", "https://old.example.com/api/github/callbacks/webhook", &["push"], true, false),
+			hook("2", "https://ci.example.com/hook", &["push"], true, false),
+			hook("3", "https://dpu.example.com/api/github/callbacks/webhook/", &["push"], false, false),
+		];
+		assert_eq!(our_hook_ids(CALLBACK_PATH, &actual), vec!["1".to_string(), "3".to_string()]);
+	}
+}
\ No newline at end of file
@@ -1,6 +1,6 @@
-use sled::IVec;
 use std::collections::HashSet;
 use crate::db::config::get_db;
+use crate::utils::aliases::ResolvedAlias;
 
 fn save_handles_to_db(alias_key: &str, provider: &str, provider_login_ids: Vec<String>) {
     let db = get_db();
@@ -16,12 +16,12 @@ fn save_handles_to_db(alias_key: &str, provider: &str, provider_login_ids: Vec<S
     }
     let val = val_res.expect("Uncaught error in val_res");
     let insert_res = db.insert(
-        IVec::from(full_key.as_bytes()),
-        val,
+        full_key.as_bytes(),
+        &val,
     );
     if let Err(e) = insert_res {
         log::error!(
-            "[save_handles_to_db] Failed to upsert aliases into sled DB: {:?}",
+            "[save_handles_to_db] Failed to upsert aliases into DB: {:?}",
             e
         );
         return;
@@ -37,10 +37,10 @@ pub fn get_handles_from_db(alias_key: &str, provider: &str) -> Option<Vec<String
     let full_key = format!("{}/aliases/{}", provider, alias_key);
     log::debug!("[get_handles_from_db] full_key = {}", &full_key);
     
-    let result = db.get(IVec::from(full_key.as_bytes()));
+    let result = db.get(full_key.as_bytes());
     if let Err(e) = result {
         log::error!(
-            "[get_handles_from_db] Failed to retrieve aliases from sled DB: {:?}",
+            "[get_handles_from_db] Failed to retrieve aliases from DB: {:?}",
             e
         );
         return None;
@@ -85,3 +85,41 @@ pub fn update_handles_in_db(alias_key: &str, provider: &str, new_logins: Vec<Str
     save_handles_to_db(alias_key, provider, updated_aliases.to_owned());
     return updated_aliases;
 }
+
+// Handles the dpu resolved itself from commit authors, kept apart so server mappings always win
+pub fn save_resolved_alias_to_db(alias_key: &str, provider: &str, resolved_alias: &ResolvedAlias) {
+    let db = get_db();
+    let full_key = format!("{}/resolved_aliases/{}", provider, alias_key);
+    let val_res = serde_json::to_vec(resolved_alias);
+    if let Err(e) = val_res {
+        log::error!("[save_resolved_alias_to_db] Failed to serialize resolved alias: {:?}", e);
+        return;
+    }
+    let val = val_res.expect("Uncaught error in val_res");
+    if let Err(e) = db.insert(full_key.as_bytes(), &val) {
+        log::error!("[save_resolved_alias_to_db] Failed to upsert resolved alias into DB: {:?}", e);
+    }
+}
+
+pub fn get_resolved_alias_from_db(alias_key: &str, provider: &str) -> Option<ResolvedAlias> {
+    let db = get_db();
+    let full_key = format!("{}/resolved_aliases/{}", provider, alias_key);
+    let result = db.get(full_key.as_bytes());
+    if let Err(e) = result {
+        log::error!("[get_resolved_alias_from_db] Failed to retrieve resolved alias from DB: {:?}", e);
+        return None;
+    }
+    let ivec_opt = result.expect("Uncaught error in result");
+    if ivec_opt.is_none() {
+        log::debug!("[get_resolved_alias_from_db] No resolved alias found for {}", &full_key);
+        return None;
+    }
+    let ivec = ivec_opt.expect("Empty ivec_opt");
+    let resolved_res = serde_json::from_slice(&ivec);
+    if let Err(e) = resolved_res {
+        log::error!("[get_resolved_alias_from_db] Failed to deserialize resolved alias from json: {:?}", e);
+        return None;
+    }
+    let resolved_alias: ResolvedAlias = resolved_res.expect("Uncaught error in resolved_res");
+    Some(resolved_alias)
+}
\ No newline at end of file
@@ -0,0 +1,47 @@
+use crate::db::config::get_db;
+use crate::utils::approval::ApprovalRecord;
+use crate::utils::review::Review;
+
+pub fn save_approval_records_to_db(review: &Review, records: &Vec<ApprovalRecord>) {
+    let db = get_db();
+    let approval_key = format!("approvals/{}", review.db_key());
+    let json_res = serde_json::to_vec(records);
+    if json_res.is_err() {
+        let e = json_res.expect_err("No error in json_res");
+        log::error!("[save_approval_records_to_db] Failed to serialize approval records: {:?}", e);
+        return;
+    }
+    let json = json_res.expect("Uncaught error in json_res");
+    let insert_res = db.insert(approval_key.as_bytes(), &json);
+    if insert_res.is_err() {
+        let e = insert_res.expect_err("No error in insert_res");
+        log::error!("[save_approval_records_to_db] Failed to upsert approval records into DB: {e}");
+        return;
+    }
+    log::debug!("[save_approval_records_to_db] Approval records saved for {}: {:?}", &approval_key, records);
+}
+
+pub fn get_approval_records_from_db(review: &Review) -> Option<Vec<ApprovalRecord>> {
+    let db = get_db();
+    let approval_key = format!("approvals/{}", review.db_key());
+    let approval_res = db.get(approval_key.as_bytes());
+    if approval_res.is_err() {
+        let e = approval_res.expect_err("No error in approval_res");
+        log::error!("[get_approval_records_from_db] Unable to get approval records for {}: {:?}", &approval_key, e);
+        return None;
+    }
+    let ivec_opt = approval_res.expect("Uncaught error in approval_res");
+    if ivec_opt.is_none() {
+        log::debug!("[get_approval_records_from_db] No approval records found for {}", &approval_key);
+        return None;
+    }
+    let ivec = ivec_opt.expect("Empty ivec_opt");
+    let records_res = serde_json::from_slice(&ivec);
+    if records_res.is_err() {
+        let e = records_res.expect_err("No error in records_res");
+        log::error!("[get_approval_records_from_db] Failed to deserialize approval records: {:?}", e);
+        return None;
+    }
+    let records: Vec<ApprovalRecord> = records_res.expect("Uncaught error in records_res");
+    Some(records)
+}
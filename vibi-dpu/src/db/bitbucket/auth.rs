@@ -1,6 +1,5 @@
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
-use sled::IVec;
 
 use crate::db::config::get_db;
 use crate::utils::bitbucket_auth_info::BitbucketAuthInfo;
@@ -12,17 +11,11 @@ pub fn save_bitbucket_auth_info_to_db(auth_info: &mut BitbucketAuthInfo) {
     auth_info.set_timestamp(since_epoch.as_secs());
     log::debug!("[save_bitbucket_auth_info_to_db] auth info = {:?}", &auth_info);
     let json = serde_json::to_string(&auth_info).expect("Failed to serialize auth info");
-    // Convert JSON string to bytes
-    let bytes = json.as_bytes(); 
-
-    // Create IVec from bytes
-    let ivec = IVec::from(bytes);
-
-    // Insert into sled DB
-    let insert_res = db.insert("bitbucket_auth_info", ivec);
+    // Insert into DB
+    let insert_res = db.insert(b"bitbucket_auth_info", json.as_bytes());
     if insert_res.is_err() {
         let e = insert_res.expect_err("No error in insert_res");
-        log::error!("[save_bitbucket_auth_info_to_db] Failed to upsert bitbucket auth info into sled DB: {e}");
+        log::error!("[save_bitbucket_auth_info_to_db] Failed to upsert bitbucket auth info into DB: {e}");
         return;
     }
     log::debug!("[save_bitbucket_auth_info_to_db] BitbucketAuthInfo succesfully upserted: {:?}", auth_info);
@@ -31,7 +24,7 @@ pub fn save_bitbucket_auth_info_to_db(auth_info: &mut BitbucketAuthInfo) {
 pub fn bitbucket_auth_info() -> Option<BitbucketAuthInfo> {
     let db = get_db();
 	let authinfo_key = "bitbucket_auth_info";
-	let authinfo_res = db.get(IVec::from(authinfo_key.as_bytes()));
+	let authinfo_res = db.get(authinfo_key.as_bytes());
     if authinfo_res.is_err() {
         let e = authinfo_res.expect_err("No error in authinfo_res");
         log::error!("[bitbucket_auth_info] Unable to get bb authinfo from db: {:?}", e);
@@ -0,0 +1,56 @@
+use crate::db::config::get_db;
+use crate::utils::review::Review;
+
+// Kept apart from the review record, which is overwritten on every push
+pub fn save_comment_id_to_db(review: &Review, comment_id: &str) {
+    let db = get_db();
+    let comment_key = format!("comment/{}", review.db_key());
+    let insert_res = db.insert(comment_key.as_bytes(), comment_id.as_bytes());
+    if insert_res.is_err() {
+        let e = insert_res.expect_err("No error in insert_res");
+        log::error!("[save_comment_id_to_db] Failed to upsert comment id into DB: {e}");
+        return;
+    }
+    log::debug!("[save_comment_id_to_db] Comment id {} saved for {}", comment_id, &comment_key);
+}
+
+pub fn get_comment_id_from_db(review: &Review) -> Option<String> {
+    let db = get_db();
+    let comment_key = format!("comment/{}", review.db_key());
+    let comment_res = db.get(comment_key.as_bytes());
+    if comment_res.is_err() {
+        let e = comment_res.expect_err("No error in comment_res");
+        log::error!("[get_comment_id_from_db] Unable to get comment id for {}: {:?}", &comment_key, e);
+        return None;
+    }
+    let ivec_opt = comment_res.expect("Uncaught error in comment_res");
+    if ivec_opt.is_none() {
+        log::debug!("[get_comment_id_from_db] No comment id found for {}", &comment_key);
+        return None;
+    }
+    let ivec = ivec_opt.expect("Empty ivec_opt");
+    Some(String::from_utf8_lossy(&ivec).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn review_with_key(db_key: &str) -> Review {
+        Review::new("base".to_string(), "head".to_string(), "1".to_string(),
+            "repo".to_string(), "owner".to_string(), "github".to_string(), db_key.to_string(),
+            "/nonexistent/clone_dir".to_string(), "clone_url".to_string(), "author".to_string(), None)
+    }
+
+    #[test]
+    fn comment_id_is_kept_per_review() {
+        let review = review_with_key("github/comment-owner/repo/1");
+        let other_review = review_with_key("github/comment-owner/repo/2");
+        assert_eq!(get_comment_id_from_db(&review), None);
+        save_comment_id_to_db(&review, "101");
+        assert_eq!(get_comment_id_from_db(&review), Some("101".to_string()));
+        assert_eq!(get_comment_id_from_db(&other_review), None);
+        save_comment_id_to_db(&review, "102");
+        assert_eq!(get_comment_id_from_db(&review), Some("102".to_string()));
+    }
+}
@@ -1,14 +1,45 @@
-use std::sync::Mutex;
-
-static mut DB: Option<sled::Db> = None;
-static DB_MUTEX: Mutex<()> = Mutex::new(()); 
-
-pub fn get_db() -> &'static sled::Db {
-  let _lock = DB_MUTEX.lock().unwrap();
-  unsafe {
-    if DB.is_none() {
-      DB = Some(sled::open("/tmp/db").unwrap());
-    }
-    DB.as_ref().unwrap()
+use std::env;
+
+use once_cell::sync::Lazy;
+
+use crate::db::sled_store::SledStore;
+use crate::db::sqlite_store::SqliteStore;
+use crate::db::store::Store;
+
+const DEFAULT_SLED_PATH: &str = "/tmp/db";
+const DEFAULT_SQLITE_PATH: &str = "/tmp/db.sqlite3";
+
+static DB: Lazy<Box<dyn Store>> = Lazy::new(open_store);
+
+pub fn get_db() -> &'static dyn Store {
+  DB.as_ref()
+}
+
+// DB_BACKEND picks sled (default) or sqlite, DB_PATH where its files are kept
+fn open_store() -> Box<dyn Store> {
+  let backend = env::var("DB_BACKEND").unwrap_or_default().trim().to_lowercase();
+  let path_opt = db_path_opt();
+  if backend == "sqlite" {
+    let path = path_opt.unwrap_or(DEFAULT_SQLITE_PATH.to_string());
+    log::info!("Opening sqlite db at {}...", &path);
+    return Box::new(SqliteStore::open(&path).expect("Unable to open sqlite db"));
+  }
+  if !backend.is_empty() && backend != "sled" {
+    log::error!("[open_store] Unknown DB_BACKEND {}, using sled", &backend);
   }
+  let path = path_opt.unwrap_or(DEFAULT_SLED_PATH.to_string());
+  log::info!("Opening sled db at {}...", &path);
+  Box::new(SledStore::open(&path).expect("Unable to open sled db"))
+}
+
+#[cfg(not(test))]
+fn db_path_opt() -> Option<String> {
+  env::var("DB_PATH").ok().filter(|path| !path.trim().is_empty())
+}
+
+// Tests get a fresh db under the temp dir instead of the configured one
+#[cfg(test)]
+fn db_path_opt() -> Option<String> {
+  let path = env::temp_dir().join(format!("vibi-dpu-test-{}", uuid::Uuid::new_v4()));
+  Some(path.to_string_lossy().to_string())
 }
\ No newline at end of file
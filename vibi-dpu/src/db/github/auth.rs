@@ -1,32 +1,36 @@
-use sled::IVec;
-
 use crate::db::config::get_db;
 use crate::utils::github_auth_info::GithubAuthInfo;
 
+// Single-installation key used before installations were stored separately
+const LEGACY_AUTH_INFO_KEY: &str = "github_auth_info";
+const AUTH_INFO_PREFIX: &str = "github_auth_info/";
+const REPO_INSTALLATION_PREFIX: &str = "github_installation/";
+
 pub fn save_github_auth_info_to_db(auth_info: &mut GithubAuthInfo) {
     let db = get_db();
     log::debug!("[save_github_auth_info_to_db] auth info = {:?}", &auth_info);
+    let installation_id_opt = auth_info.installation_id().to_owned();
+    if installation_id_opt.is_none() {
+        log::error!("[save_github_auth_info_to_db] No installation id in auth info, not saving");
+        return;
+    }
+    let installation_id = installation_id_opt.expect("Empty installation_id_opt");
     let json = serde_json::to_string(&auth_info).expect("Failed to serialize auth info");
-    // Convert JSON string to bytes
-    let bytes = json.as_bytes(); 
-
-    // Create IVec from bytes
-    let ivec = IVec::from(bytes);
-
-    // Insert into sled DB
-    let insert_res = db.insert("github_auth_info", ivec);
+    // Insert into DB
+    let key = format!("{}{}", AUTH_INFO_PREFIX, &installation_id);
+    let insert_res = db.insert(key.as_bytes(), json.as_bytes());
     if insert_res.is_err() {
         let e = insert_res.expect_err("No error in insert_res");
-        log::error!("[save_github_auth_info_to_db] Failed to upsert github auth info into sled DB: {e}");
+        log::error!("[save_github_auth_info_to_db] Failed to upsert github auth info into DB: {e}");
         return;
     }
     log::debug!("[save_github_auth_info_to_db] GithubAuthInfo succesfully upserted: {:?}", auth_info);
 }
 
-pub fn get_github_auth_info_from_db() -> Option<GithubAuthInfo> {
+pub fn get_github_auth_info_from_db(installation_id: &str) -> Option<GithubAuthInfo> {
     let db = get_db();
-	let authinfo_key = "github_auth_info";
-	let authinfo_res = db.get(IVec::from(authinfo_key.as_bytes()));
+	let authinfo_key = format!("{}{}", AUTH_INFO_PREFIX, installation_id);
+	let authinfo_res = db.get(authinfo_key.as_bytes());
     if authinfo_res.is_err() {
         let e = authinfo_res.expect_err("No error in authinfo_res");
         log::error!("[get_github_auth_info_from_db] Unable to get github authinfo from db: {:?}", e);
@@ -34,14 +38,97 @@ pub fn get_github_auth_info_from_db() -> Option<GithubAuthInfo> {
     }
     let authinfo_opt = authinfo_res.expect("Uncaught error in authinfo_res");
     if authinfo_opt.is_none() {
-        log::error!("[get_github_auth_info_from_db] No github authinfo in db");
+        log::error!("[get_github_auth_info_from_db] No github authinfo in db for installation {}", installation_id);
         return None;
     }
     let authinfo_ivec = authinfo_opt.expect("Empty authinfo_opt");
-    let authinfo_parse = serde_json::from_slice(&authinfo_ivec);
+    parse_auth_info(&authinfo_ivec)
+}
+
+// All installations with stored auth, an auth info saved before multi-installation support is moved over first
+pub fn get_github_installations_from_db() -> Vec<GithubAuthInfo> {
+    migrate_legacy_auth_info();
+    let db = get_db();
+    let mut installations = Vec::<GithubAuthInfo>::new();
+    let entries_res = db.scan_prefix(AUTH_INFO_PREFIX.as_bytes());
+    if let Err(e) = entries_res {
+        log::error!("[get_github_installations_from_db] Unable to read github auth info: {:?}", e);
+        return installations;
+    }
+    for (_, authinfo_ivec) in entries_res.expect("Uncaught error in entries_res") {
+        if let Some(auth_info) = parse_auth_info(&authinfo_ivec) {
+            installations.push(auth_info);
+        }
+    }
+    installations
+}
+
+pub fn delete_github_auth_info_from_db(installation_id: &str) {
+    let db = get_db();
+    let key = format!("{}{}", AUTH_INFO_PREFIX, installation_id);
+    if let Err(e) = db.remove(key.as_bytes()) {
+        log::error!("[delete_github_auth_info_from_db] Unable to remove auth info of installation {}: {:?}", installation_id, e);
+    }
+}
+
+pub fn save_repo_installation_to_db(repo_owner: &str, repo_name: &str, installation_id: &str) {
+    let db = get_db();
+    let key = format!("{}{}/{}", REPO_INSTALLATION_PREFIX, repo_owner, repo_name);
+    let insert_res = db.insert(key.as_bytes(), installation_id.as_bytes());
+    if let Err(e) = insert_res {
+        log::error!("[save_repo_installation_to_db] Failed to save installation of {}/{}: {:?}", repo_owner, repo_name, e);
+    }
+}
+
+// Installation saved for exactly this repo
+pub fn get_exact_repo_installation_from_db(repo_owner: &str, repo_name: &str) -> Option<String> {
+    let db = get_db();
+    let key = format!("{}{}/{}", REPO_INSTALLATION_PREFIX, repo_owner, repo_name);
+    let installation_res = db.get(key.as_bytes());
+    if let Err(e) = installation_res {
+        log::error!("[get_exact_repo_installation_from_db] Unable to get installation of {}/{}: {:?}", repo_owner, repo_name, e);
+        return None;
+    }
+    installation_res.expect("Uncaught error in installation_res")
+        .map(|installation_ivec| String::from_utf8_lossy(&installation_ivec).to_string())
+}
+
+// Installation serving the repo, falls back to any repo of the same owner since installations are per account
+pub fn get_repo_installation_from_db(repo_owner: &str, repo_name: &str) -> Option<String> {
+    let installation_opt = get_exact_repo_installation_from_db(repo_owner, repo_name);
+    if installation_opt.is_some() {
+        return installation_opt;
+    }
+    let db = get_db();
+    let owner_prefix = format!("{}{}/", REPO_INSTALLATION_PREFIX, repo_owner);
+    db.scan_prefix(owner_prefix.as_bytes()).ok()
+        .and_then(|entries| entries.into_iter().next())
+        .map(|(_, installation_ivec)| String::from_utf8_lossy(&installation_ivec).to_string())
+}
+
+fn migrate_legacy_auth_info() {
+    let db = get_db();
+    let legacy_res = db.remove(LEGACY_AUTH_INFO_KEY.as_bytes());
+    if let Err(e) = legacy_res {
+        log::error!("[migrate_legacy_auth_info] Unable to read legacy github auth info: {:?}", e);
+        return;
+    }
+    let legacy_opt = legacy_res.expect("Uncaught error in legacy_res");
+    if legacy_opt.is_none() {
+        return;
+    }
+    let legacy_ivec = legacy_opt.expect("Empty legacy_opt");
+    if let Some(mut auth_info) = parse_auth_info(&legacy_ivec) {
+        log::info!("Moving stored GitHub auth to installation {:?}...", auth_info.installation_id());
+        save_github_auth_info_to_db(&mut auth_info);
+    }
+}
+
+fn parse_auth_info(authinfo_ivec: &[u8]) -> Option<GithubAuthInfo> {
+    let authinfo_parse = serde_json::from_slice(authinfo_ivec);
     if authinfo_parse.is_err() {
         let e = authinfo_parse.expect_err("No error in authinfo_parse");
-        log::error!("[get_github_auth_info_from_db] Unable to deserialize github authinfo_parse: {:?}", e);
+        log::error!("[parse_auth_info] Unable to deserialize github authinfo_parse: {:?}", e);
         return None;
     }
 	let github_auth_info: GithubAuthInfo =  authinfo_parse.expect("Uncaught error in authinfo_parse");
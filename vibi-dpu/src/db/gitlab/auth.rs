@@ -0,0 +1,46 @@
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::db::config::get_db;
+use crate::utils::gitlab_auth_info::GitlabAuthInfo;
+
+pub fn save_gitlab_auth_info_to_db(auth_info: &mut GitlabAuthInfo) {
+    let db = get_db();
+    let now = SystemTime::now();
+    let since_epoch = now.duration_since(UNIX_EPOCH).expect("Time went backwards");
+    auth_info.set_timestamp(since_epoch.as_secs());
+    log::debug!("[save_gitlab_auth_info_to_db] auth info = {:?}", &auth_info);
+    let json = serde_json::to_string(&auth_info).expect("Failed to serialize auth info");
+    let insert_res = db.insert(b"gitlab_auth_info", json.as_bytes());
+    if insert_res.is_err() {
+        let e = insert_res.expect_err("No error in insert_res");
+        log::error!("[save_gitlab_auth_info_to_db] Failed to upsert gitlab auth info into DB: {e}");
+        return;
+    }
+    log::debug!("[save_gitlab_auth_info_to_db] GitlabAuthInfo succesfully upserted: {:?}", auth_info);
+}
+
+pub fn gitlab_auth_info() -> Option<GitlabAuthInfo> {
+    let db = get_db();
+    let authinfo_key = "gitlab_auth_info";
+    let authinfo_res = db.get(authinfo_key.as_bytes());
+    if authinfo_res.is_err() {
+        let e = authinfo_res.expect_err("No error in authinfo_res");
+        log::error!("[gitlab_auth_info] Unable to get gitlab authinfo from db: {:?}", e);
+        return None;
+    }
+    let authinfo_opt = authinfo_res.expect("Uncaught error in authinfo_res");
+    if authinfo_opt.is_none() {
+        log::error!("[gitlab_auth_info] No gitlab authinfo in db");
+        return None;
+    }
+    let authinfo_ivec = authinfo_opt.expect("Empty authinfo_opt");
+    let authinfo_parse = serde_json::from_slice(&authinfo_ivec);
+    if authinfo_parse.is_err() {
+        let e = authinfo_parse.expect_err("No error in authinfo_parse");
+        log::error!("[gitlab_auth_info] Unable to deserialize gitlab authinfo_parse: {:?}", e);
+        return None;
+    }
+    let gitlab_auth_info: GitlabAuthInfo = authinfo_parse.expect("Uncaught error in authinfo_parse");
+    Some(gitlab_auth_info)
+}
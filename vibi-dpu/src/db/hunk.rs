@@ -1,5 +1,3 @@
-use sled::IVec;
-
 use crate::db::config::get_db;
 use crate::db::hunk;
 use crate::utils::hunk::HunkMap;
@@ -8,7 +6,7 @@ pub fn get_hunk_from_db(review: &Review) -> Option<HunkMap> {
 	let db = get_db();
 	let key = format!("hunk/{}/{}/{}", review.db_key(), 
 		review.base_head_commit(), review.pr_head_commit());
-	let hunkmap_res = db.get(&key);
+	let hunkmap_res = db.get(key.as_bytes());
 	if hunkmap_res.is_err() {
 		let e = hunkmap_res.expect_err("No error in hunkmap_res");
 		log::error!("[get_hunk_from_db] Error getting hunkmap from db, key: {:?}, err: {:?}", &key, e);
@@ -37,11 +35,11 @@ pub fn store_hunkmap_to_db(hunkmap: &HunkMap, review: &Review) {
 	log::debug!("[store_hunkmap_to_db] hunk_key = {}", hunk_key);
 	let json = serde_json::to_vec(hunkmap).expect("Failed to serialize hunkmap");
   
-    // Insert JSON into sled DB
-    let insert_res = db.insert(IVec::from(hunk_key.as_bytes()), json);
+    // Insert JSON into DB
+    let insert_res = db.insert(hunk_key.as_bytes(), &json);
     if insert_res.is_err() {
         let e = insert_res.expect_err("No error in insert_res");
-        log::error!("[store_hunkmap_to_db] Failed to upsert hunkmap into sled DB: {e}");
+        log::error!("[store_hunkmap_to_db] Failed to upsert hunkmap into DB: {e}");
         return;
     }
     log::debug!("[store_hunkmap_to_db] Hunkmap succesfully upserted: {:?}", hunkmap);
@@ -9,4 +9,12 @@ pub mod repo_config;
 pub mod prs;
 pub mod bitbucket;
 pub mod github;
-pub mod aliases;
\ No newline at end of file
+pub mod aliases;
+pub mod ownership;
+pub mod comment;
+pub mod approval;
+pub mod gitlab;
+pub mod purge;
+pub mod store;
+mod sled_store;
+mod sqlite_store;
\ No newline at end of file
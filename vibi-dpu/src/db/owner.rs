@@ -1,5 +1,3 @@
-use sled::IVec;
-
 use crate::db::config::get_db;
 use crate::utils::owner::Workspace;
 
@@ -14,14 +12,11 @@ pub fn save_workspace_to_db(workspace: &Workspace) {
         return;
     }
     let json = parse_json.expect("Uncaught error in parse_json workspace");
-    // Convert JSON string to bytes
-    let bytes = json.as_bytes(); 
-    // Create IVec from bytes
-    let ivec = IVec::from(bytes);
-    let insert_res = db.insert(format!("owners:{}", uuid), ivec);
+    let key = format!("owners:{}", uuid);
+    let insert_res = db.insert(key.as_bytes(), json.as_bytes());
     if insert_res.is_err() {
         let e = insert_res.expect_err("No error in insert_res");
-        log::error!("[save_workspace_to_db] Failed to upsert workspace into sled DB: {e}");
+        log::error!("[save_workspace_to_db] Failed to upsert workspace into DB: {e}");
         return;
     }
     log::debug!("[save_workspace_to_db] Workspace succesfully upserted: {:?}", workspace);  
@@ -0,0 +1,113 @@
+use crate::db::config::get_db;
+use crate::utils::ownership::{OwnershipMeta, PathOwnership};
+
+fn ownership_prefix(repo_provider: &str, repo_owner: &str, repo_name: &str) -> String {
+    format!("ownership/{}/{}/{}/", repo_provider, repo_owner, repo_name)
+}
+
+fn path_ownership_key(repo_provider: &str, repo_owner: &str, repo_name: &str, path: &str, is_dir: bool) -> String {
+    let kind = if is_dir { "dir" } else { "file" };
+    format!("{}{}/{}", ownership_prefix(repo_provider, repo_owner, repo_name), kind, path)
+}
+
+pub fn save_path_ownership_to_db(path_ownership: &PathOwnership,
+    repo_provider: &str, repo_owner: &str, repo_name: &str) {
+    let db = get_db();
+    let key = path_ownership_key(repo_provider, repo_owner, repo_name,
+        path_ownership.path(), path_ownership.is_dir());
+    let parse_res = serde_json::to_vec(path_ownership);
+    if let Err(e) = parse_res {
+        log::error!("[save_path_ownership_to_db] Unable to serialize path ownership: {:?}, error: {:?}",
+            path_ownership, e);
+        return;
+    }
+    let ownership_json = parse_res.expect("Uncaught error in parse_res");
+    let insert_res = db.insert(key.as_bytes(), &ownership_json);
+    if let Err(e) = insert_res {
+        log::error!("[save_path_ownership_to_db] Failed to upsert path ownership into DB: {:?}", e);
+        return;
+    }
+    log::debug!("[save_path_ownership_to_db] Path ownership succesfully upserted: {}", &key);
+}
+
+pub fn get_path_ownership_from_db(repo_provider: &str, repo_owner: &str, repo_name: &str,
+    path: &str, is_dir: bool) -> Option<PathOwnership> {
+    let db = get_db();
+    let key = path_ownership_key(repo_provider, repo_owner, repo_name, path, is_dir);
+    let get_res = db.get(key.as_bytes());
+    if let Err(e) = get_res {
+        log::error!("[get_path_ownership_from_db] Unable to get path ownership from db: {:?}, key: {}", e, &key);
+        return None;
+    }
+    let ownership_opt = get_res.expect("Uncaught error in get_res");
+    if ownership_opt.is_none() {
+        log::debug!("[get_path_ownership_from_db] No path ownership in db for key: {}", &key);
+        return None;
+    }
+    let ownership_ivec = ownership_opt.expect("Empty ownership_opt");
+    let parse_res = serde_json::from_slice(&ownership_ivec);
+    if let Err(e) = parse_res {
+        log::error!("[get_path_ownership_from_db] Unable to deserialize path ownership: {:?}", e);
+        return None;
+    }
+    let path_ownership: PathOwnership = parse_res.expect("Uncaught error in parse_res");
+    Some(path_ownership)
+}
+
+pub fn delete_repo_ownership_from_db(repo_provider: &str, repo_owner: &str, repo_name: &str) {
+    let db = get_db();
+    let prefix = ownership_prefix(repo_provider, repo_owner, repo_name);
+    let entries_res = db.scan_prefix(prefix.as_bytes());
+    if let Err(e) = entries_res {
+        log::error!("[delete_repo_ownership_from_db] Unable to read ownership keys: {:?}", e);
+        return;
+    }
+    for (key, _) in entries_res.expect("Uncaught error in entries_res") {
+        let remove_res = db.remove(&key);
+        if let Err(e) = remove_res {
+            log::error!("[delete_repo_ownership_from_db] Unable to remove ownership key: {:?}", e);
+        }
+    }
+    log::debug!("[delete_repo_ownership_from_db] Cleared ownership index for {}", &prefix);
+}
+
+pub fn save_ownership_meta_to_db(meta: &OwnershipMeta,
+    repo_provider: &str, repo_owner: &str, repo_name: &str) {
+    let db = get_db();
+    let key = format!("{}meta", ownership_prefix(repo_provider, repo_owner, repo_name));
+    let parse_res = serde_json::to_vec(meta);
+    if let Err(e) = parse_res {
+        log::error!("[save_ownership_meta_to_db] Unable to serialize ownership meta: {:?}, error: {:?}", meta, e);
+        return;
+    }
+    let meta_json = parse_res.expect("Uncaught error in parse_res");
+    let insert_res = db.insert(key.as_bytes(), &meta_json);
+    if let Err(e) = insert_res {
+        log::error!("[save_ownership_meta_to_db] Failed to upsert ownership meta into DB: {:?}", e);
+        return;
+    }
+    log::debug!("[save_ownership_meta_to_db] Ownership meta succesfully upserted: {:?}", meta);
+}
+
+pub fn get_ownership_meta_from_db(repo_provider: &str, repo_owner: &str, repo_name: &str) -> Option<OwnershipMeta> {
+    let db = get_db();
+    let key = format!("{}meta", ownership_prefix(repo_provider, repo_owner, repo_name));
+    let get_res = db.get(key.as_bytes());
+    if let Err(e) = get_res {
+        log::error!("[get_ownership_meta_from_db] Unable to get ownership meta from db: {:?}", e);
+        return None;
+    }
+    let meta_opt = get_res.expect("Uncaught error in get_res");
+    if meta_opt.is_none() {
+        log::debug!("[get_ownership_meta_from_db] No ownership index built yet for key: {}", &key);
+        return None;
+    }
+    let meta_ivec = meta_opt.expect("Empty meta_opt");
+    let parse_res = serde_json::from_slice(&meta_ivec);
+    if let Err(e) = parse_res {
+        log::error!("[get_ownership_meta_from_db] Unable to deserialize ownership meta: {:?}", e);
+        return None;
+    }
+    let meta: OwnershipMeta = parse_res.expect("Uncaught error in parse_res");
+    Some(meta)
+}
@@ -1,7 +1,9 @@
 use serde_json::Value;
-use sled::IVec;
 use crate::core::approval::process_approval;
+use crate::core::utils::get_access_token;
 use crate::db::config::get_db;
+use crate::bitbucket_server;
+use crate::gitlab;
 use crate::utils::pr_info::PrInfo;
 
 pub async fn update_pr_info_in_db(workspace_slug: &str, repo_slug: &str, pr_info: &PrInfo, pr_number: &str, repo_provider: &str) {
@@ -19,7 +21,7 @@ pub async fn update_pr_info_in_db(workspace_slug: &str, repo_slug: &str, pr_info
 	let pr_info_bytes = pr_info_json_result.expect("empty pr_info_json_result");
 
 	// Update the entry in the database. It will create a new entry if the key does not exist.
-	let update_result = db.insert(IVec::from(key.as_bytes()), pr_info_bytes);
+	let update_result = db.insert(key.as_bytes(), &pr_info_bytes);
 
 	if update_result.is_err() {
 		let e = update_result.expect_err("No error in updating pr_info");
@@ -80,7 +82,7 @@ fn parse_bitbucket_webhook_data(webhook_data: &Value) -> Option<PrInfo> {
 pub async fn get_pr_info_from_db(workspace_slug: &str, repo_slug: &str, pr_number: &str, repo_provider: &str, pr_info_parsed: &PrInfo) -> Option<PrInfo> {
 	let db = get_db();
 	let db_pr_key = format!("pr_info/{}/{}/{}/{}", repo_provider, workspace_slug, repo_slug, pr_number);
-	let pr_info_res = db.get(IVec::from(db_pr_key.as_bytes()));
+	let pr_info_res = db.get(db_pr_key.as_bytes());
 
 	if pr_info_res.is_err() {
 		let e = pr_info_res.expect_err("No error in pr_info_res");
@@ -168,3 +170,122 @@ fn parse_github_webhook_data(webhook_data: &Value) -> Option<PrInfo> {
 	log::debug!("[parse_github_webhook_data] pr_info :{:?}", &pr_info);
 	return Some(pr_info)
 }
+
+// Gitea payloads follow GitHub's shape, only the action names differ
+pub async fn gitea_process_and_update_pr_if_different(webhook_data: &Value, repo_config: &Value, repo_owner: &str, repo_name: &str, pr_number: &str, repo_provider: &str) -> bool {
+	let event_action = webhook_data["action"].to_string().trim_matches('"').to_string();
+	log::debug!("[gitea_process_and_update_pr_if_different] {}, {}, {}, {}", event_action, repo_owner, repo_name, pr_number);
+	let pr_info_parsed_opt = parse_github_webhook_data(webhook_data);
+	if pr_info_parsed_opt.is_none() {
+		log::error!("[gitea_process_and_update_pr_if_different] Unable to parse webhook data");
+		return false;
+	}
+	let pr_info_parsed = pr_info_parsed_opt.expect("Empty pr_info_parsed_opt");
+	if event_action == "opened" || event_action == "reopened" || event_action == "synchronized" {
+		update_pr_info_in_db(repo_owner, repo_name, &pr_info_parsed, pr_number, repo_provider).await;
+		return true;
+	}
+	// rejected reviews can drop earlier approvals, so both are processed
+	if event_action == "reviewed" {
+		let review_type = webhook_data["review"]["type"].to_string().trim_matches('"').to_string();
+		if review_type == "pull_request_review_approved" || review_type == "pull_request_review_rejected" {
+			log::info!("Processing Approved PR event...");
+			process_approval(webhook_data, repo_config, repo_owner, repo_name, pr_number, repo_provider).await;
+		}
+		return false;
+	}
+	log::debug!("[gitea_process_and_update_pr_if_different] PR action {} needs no review", event_action);
+	false
+}
+
+// Every pr: event carries the whole pull request, reviewer events only change approvals
+pub async fn bitbucket_server_process_and_update_pr_if_different(webhook_data: &Value, repo_config: &Value, repo_owner: &str, repo_name: &str, pr_number: &str, repo_provider: &str) -> bool {
+	let event_key = webhook_data["eventKey"].to_string().trim_matches('"').to_string();
+	log::debug!("[bitbucket_server_process_and_update_pr_if_different] {}, {}, {}, {}", event_key, repo_owner, repo_name, pr_number);
+	// needs_work can follow an approval by the same reviewer, so it is processed too
+	if event_key.starts_with("pr:reviewer:") {
+		log::info!("Processing Approved PR event...");
+		process_approval(webhook_data, repo_config, repo_owner, repo_name, pr_number, repo_provider).await;
+		return false;
+	}
+	if event_key != "pr:opened" && event_key != "pr:from_ref_updated" {
+		log::debug!("[bitbucket_server_process_and_update_pr_if_different] PR event {} needs no review", event_key);
+		return false;
+	}
+	let pr_info_parsed_opt = bitbucket_server::prs::parse_pr_info(&webhook_data["pullRequest"]);
+	if pr_info_parsed_opt.is_none() {
+		log::error!("[bitbucket_server_process_and_update_pr_if_different] Unable to parse webhook data");
+		return false;
+	}
+	let pr_info_parsed = pr_info_parsed_opt.expect("Empty pr_info_parsed_opt");
+	if let Some(pr_info_db) = pr_info_from_db(repo_owner, repo_name, pr_number, repo_provider) {
+		if pr_info_db.pr_head_commit().eq_ignore_ascii_case(pr_info_parsed.pr_head_commit()) {
+			log::debug!("[bitbucket_server_process_and_update_pr_if_different] {:?} same commit id as in db, doing nothing", pr_info_db.pr_head_commit());
+			return false;
+		}
+	}
+	update_pr_info_in_db(repo_owner, repo_name, &pr_info_parsed, pr_number, repo_provider).await;
+	true
+}
+
+// Stored PR info, without adding anything when it is missing
+pub fn pr_info_from_db(repo_owner: &str, repo_name: &str, pr_number: &str, repo_provider: &str) -> Option<PrInfo> {
+	let db = get_db();
+	let db_pr_key = format!("pr_info/{}/{}/{}/{}", repo_provider, repo_owner, repo_name, pr_number);
+	let pr_info_res = db.get(db_pr_key.as_bytes());
+	if pr_info_res.is_err() {
+		let e = pr_info_res.expect_err("No error in pr_info_res");
+		log::error!("[pr_info_from_db] Unable to get pr info from db: {:?}", e);
+		return None;
+	}
+	let pr_info_opt = pr_info_res.expect("Uncaught error in pr_info_res");
+	if pr_info_opt.is_none() {
+		log::debug!("[pr_info_from_db] No pr info in db for {}", &db_pr_key);
+		return None;
+	}
+	let pr_info_parse = serde_json::from_slice(&pr_info_opt.expect("Empty pr_info_opt"));
+	if pr_info_parse.is_err() {
+		let e = pr_info_parse.expect_err("No error in pr_info_parse");
+		log::error!("[pr_info_from_db] Unable to deserialize pr_info: {:?}", e);
+		return None;
+	}
+	Some(pr_info_parse.expect("Uncaught error in pr_info_parse"))
+}
+
+// Merge request hooks don't carry the base commit, so the MR info is fetched and stored from the api
+pub async fn gitlab_process_and_update_pr_if_different(webhook_data: &Value, repo_config: &Value, repo_owner: &str, repo_name: &str, pr_number: &str, repo_provider: &str) -> bool {
+	let event_action = webhook_data["object_attributes"]["action"].to_string().trim_matches('"').to_string();
+	log::debug!("[gitlab_process_and_update_pr_if_different] {}, {}, {}, {}", event_action, repo_owner, repo_name, pr_number);
+	// approval and unapproval are per user, approved and unapproved only repeat them when the MR approval state flips
+	if event_action == "approval" || event_action == "unapproval" {
+		log::info!("Processing Approved PR event...");
+		process_approval(webhook_data, repo_config, repo_owner, repo_name, pr_number, repo_provider).await;
+		return false;
+	}
+	// updates without oldrev only change title, labels and the like
+	let has_new_commits = event_action == "update" && !webhook_data["object_attributes"]["oldrev"].is_null();
+	if event_action != "open" && event_action != "reopen" && !has_new_commits {
+		log::debug!("[gitlab_process_and_update_pr_if_different] MR action {} needs no review", event_action);
+		return false;
+	}
+	let pr_info_db_opt = pr_info_from_db(repo_owner, repo_name, pr_number, repo_provider);
+	let access_token_opt = get_access_token(&None, repo_provider).await;
+	if access_token_opt.is_none() {
+		log::error!("[gitlab_process_and_update_pr_if_different] Unable to get access token");
+		return false;
+	}
+	let access_token = access_token_opt.expect("Empty access_token_opt");
+	let pr_info_opt = gitlab::prs::get_and_store_pr_info(repo_owner, repo_name, &access_token, pr_number).await;
+	if pr_info_opt.is_none() {
+		log::error!("[gitlab_process_and_update_pr_if_different] Unable to get MR info from gitlab");
+		return false;
+	}
+	let pr_info = pr_info_opt.expect("Empty pr_info_opt");
+	if let Some(pr_info_db) = pr_info_db_opt {
+		if pr_info_db.pr_head_commit().eq_ignore_ascii_case(pr_info.pr_head_commit()) && event_action == "update" {
+			log::debug!("[gitlab_process_and_update_pr_if_different] {:?} same commit id as in db, doing nothing", pr_info.pr_head_commit());
+			return false;
+		}
+	}
+	true
+}
@@ -0,0 +1,77 @@
+use crate::db::config::get_db;
+use crate::utils::user::ProviderEnum;
+
+// Key prefixes holding data of one repo, reviews and PRs are keyed by review.db_key() = provider/owner/repo/pr
+fn repo_prefixes(repo_provider: &str, repo_owner: &str, repo_name: &str) -> Vec<String> {
+    let repo_path = format!("{}/{}/{}", repo_provider, repo_owner, repo_name);
+    let mut prefixes: Vec<String> = ["pr_info", "comment", "hunk", "review", "approvals", "ownership", "webhook"].iter()
+        .map(|kind| format!("{}/{}/", kind, &repo_path))
+        .collect();
+    prefixes.push(format!("{}/", &repo_path));
+    prefixes
+}
+
+pub fn delete_repo_records(repo_provider: &str, repo_owner: &str, repo_name: &str) {
+    let repo_key = format!("{}/{}/{}", repo_provider, repo_owner, repo_name);
+    delete_key(&repo_key);
+    if repo_provider == ProviderEnum::Github.to_string() {
+        delete_key(&format!("github_installation/{}/{}", repo_owner, repo_name));
+    }
+    for prefix in repo_prefixes(repo_provider, repo_owner, repo_name) {
+        delete_keys_with_prefix(&prefix);
+    }
+    log::debug!("[delete_repo_records] Purged records of {}", &repo_key);
+}
+
+pub fn delete_key(key: &str) {
+    let db = get_db();
+    if let Err(e) = db.remove(key.as_bytes()) {
+        log::error!("[delete_key] Unable to remove {}: {:?}", key, e);
+    }
+}
+
+pub fn delete_keys_with_prefix(prefix: &str) {
+    let db = get_db();
+    let entries_res = db.scan_prefix(prefix.as_bytes());
+    if let Err(e) = entries_res {
+        log::error!("[delete_keys_with_prefix] Unable to read keys under {}: {:?}", prefix, e);
+        return;
+    }
+    for (key, _) in entries_res.expect("Uncaught error in entries_res") {
+        if let Err(e) = db.remove(&key) {
+            log::error!("[delete_keys_with_prefix] Unable to remove key under {}: {:?}", prefix, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys_with_prefix(prefix: &str) -> usize {
+        get_db().scan_prefix(prefix.as_bytes()).expect("Unable to scan keys").len()
+    }
+
+    #[test]
+    fn repo_records_are_purged_by_prefix() {
+        let db = get_db();
+        let keys = [
+            "gitea/purge-owner/repo",
+            "webhook/gitea/purge-owner/repo/1",
+            "webhook/gitea/purge-owner/repo/2",
+            "review/gitea/purge-owner/repo/7",
+            "gitea/purge-owner/repo/config",
+            "webhook/gitea/purge-owner/repo-other/1",
+            "webhook/github/purge-owner/repo/1",
+        ];
+        for key in keys {
+            db.insert(key.as_bytes(), b"{}").expect("Unable to insert key");
+        }
+        delete_repo_records("gitea", "purge-owner", "repo");
+        assert_eq!(keys_with_prefix("gitea/purge-owner/repo"), 0);
+        assert_eq!(keys_with_prefix("webhook/gitea/purge-owner/repo/"), 0);
+        assert_eq!(keys_with_prefix("review/gitea/purge-owner/repo/"), 0);
+        assert_eq!(keys_with_prefix("webhook/gitea/purge-owner/repo-other/"), 1);
+        assert_eq!(keys_with_prefix("webhook/github/purge-owner/repo/"), 1);
+    }
+}
\ No newline at end of file
@@ -1,5 +1,3 @@
-use sled::IVec;
-
 use crate::db::config::get_db;
 use crate::utils::repo::Repository;
 
@@ -16,11 +14,11 @@ pub fn save_repo_to_db(repo: &Repository) {
 		return;
 	}
 	let repo_json = parse_res.expect("Uncaught error in parse_res repo");
-    // Insert JSON into sled DB
-    let insert_res = db.insert(IVec::from(repo_key.as_bytes()), repo_json);
+    // Insert JSON into DB
+    let insert_res = db.insert(repo_key.as_bytes(), &repo_json);
     if insert_res.is_err() {
         let e = insert_res.expect_err("No error in insert_res");
-        log::error!("[save_repo_to_db] Failed to upsert repo into sled DB: {:?}", e);
+        log::error!("[save_repo_to_db] Failed to upsert repo into DB: {:?}", e);
         return;
     }
     log::debug!("[save_repo_to_db] Repo succesfully upserted: {:?}", repo);
@@ -47,7 +45,7 @@ pub fn get_clone_url_clone_dir(repo_provider: &str, workspace_name: &str, repo_n
 pub fn get_repo_from_db(repo_provider: &str, workspace_name: &str, repo_name: &str) -> Option<Repository> {
 	let db = get_db();
 	let key = format!("{}/{}/{}", repo_provider, workspace_name, repo_name);
-	let repo_res = db.get(IVec::from(key.as_bytes()));
+	let repo_res = db.get(key.as_bytes());
 	if repo_res.is_err() {
 		let e = repo_res.expect_err("No error in repo_res");
 		log::error!("[get_repo_from_db] Unable to get repo from db: {:?}", e);
@@ -67,4 +65,25 @@ pub fn get_repo_from_db(repo_provider: &str, workspace_name: &str, repo_name: &s
 	}
 	let repo: Repository = parse_res.expect("Uncaught error in parse_res");
 	return Some(repo);
+}
+
+// Repos stored for a provider, keys of other records under the same prefix have more segments
+pub fn get_repos_from_db(repo_provider: &str) -> Vec<Repository> {
+	let db = get_db();
+	let prefix = format!("{}/", repo_provider);
+	let mut repos = Vec::<Repository>::new();
+	let entries_res = db.scan_prefix(prefix.as_bytes());
+	if let Err(e) = entries_res {
+		log::error!("[get_repos_from_db] Unable to read repo entries: {:?}", e);
+		return repos;
+	}
+	for (key, repo_ivec) in entries_res.expect("Uncaught error in entries_res") {
+		if key.iter().filter(|byte| **byte == b'/').count() != 2 {
+			continue;
+		}
+		if let Ok(repo) = serde_json::from_slice::<Repository>(&repo_ivec) {
+			repos.push(repo);
+		}
+	}
+	repos
 }
\ No newline at end of file
@@ -1,5 +1,3 @@
-use sled::IVec;
-
 use crate::db::config::get_db;
 use crate::utils::repo_config::RepoConfig;
 
@@ -18,11 +16,11 @@ pub fn save_repo_config_to_db(repo_config: &RepoConfig,
         return;
     }
     let config_json = parse_res.expect("Uncaught error in parse_res save_repo_config_to_db");
-    // Insert JSON into sled DB
-    let insert_res = db.insert(IVec::from(config_key.as_bytes()), config_json);
+    // Insert JSON into DB
+    let insert_res = db.insert(config_key.as_bytes(), &config_json);
     if insert_res.is_err() {
         let e = insert_res.expect_err("No error in insert_res save_repo_config_to_db");
-        log::error!("[save_repo_config_to_db] Failed to upsert repo config into sled DB: {:?}", e);
+        log::error!("[save_repo_config_to_db] Failed to upsert repo config into DB: {:?}", e);
         return;
     }
     log::debug!("[save_repo_config_to_db] Repo Config succesfully upserted: {:?}", repo_config);
@@ -1,5 +1,3 @@
-use sled::IVec;
-
 use crate::db::config::get_db;
 use crate::utils::review::Review;
 pub fn save_review_to_db(review: &Review) {
@@ -7,11 +5,11 @@ pub fn save_review_to_db(review: &Review) {
     let review_key = format!("review/{}", review.db_key());
     // Serialize repo struct to JSON 
     let json = serde_json::to_vec(review).expect("Failed to serialize review");
-    // Insert JSON into sled DB
-    let insert_res = db.insert(IVec::from(review_key.as_bytes()), json);
+    // Insert JSON into DB
+    let insert_res = db.insert(review_key.as_bytes(), &json);
     if insert_res.is_err() {
         let e = insert_res.expect_err("No error in insert_res");
-        log::error!("[save_review_to_db] Failed to upsert review into sled DB: {e}");
+        log::error!("[save_review_to_db] Failed to upsert review into DB: {e}");
         return;
     }
     log::debug!("[save_review_to_db] Review succesfully upserted: {:?}", review);
@@ -22,7 +20,7 @@ pub fn get_review_from_db(repo_name: &str, repo_owner: &str,
     let db = get_db();
     let review_key = format!("review/{}/{}/{}/{}",
         repo_provider, repo_owner, repo_name, review_id);
-    let review_res = db.get(IVec::from(review_key.as_bytes()));
+    let review_res = db.get(review_key.as_bytes());
     if let Err(e) = review_res {
         log::error!("[get_review_from_db] Review key not found in db - {}, error: {:?}",
             &review_key, e);
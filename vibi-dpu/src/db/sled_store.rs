@@ -0,0 +1,68 @@
+use crate::db::store::{Entries, Store, StoreError};
+
+pub struct SledStore {
+    db: sled::Db,
+}
+
+impl SledStore {
+    pub fn open(path: &str) -> Result<Self, StoreError> {
+        let db = sled::open(path)?;
+        Ok(Self { db })
+    }
+}
+
+impl Store for SledStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        let value_opt = self.db.get(key)?;
+        Ok(value_opt.map(|value| value.to_vec()))
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        self.db.insert(key, value)?;
+        Ok(())
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        let value_opt = self.db.remove(key)?;
+        Ok(value_opt.map(|value| value.to_vec()))
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Entries, StoreError> {
+        let mut entries = Vec::<(Vec<u8>, Vec<u8>)>::new();
+        for entry_res in self.db.scan_prefix(prefix) {
+            let (key, value) = entry_res?;
+            entries.push((key.to_vec(), value.to_vec()));
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::store::tests::{check_remove, check_round_trip, check_scan_prefix, check_scan_prefix_trailing_max_byte, temp_path};
+
+    fn open_temp_store() -> SledStore {
+        SledStore::open(&temp_path("sled")).expect("Unable to open sled store")
+    }
+
+    #[test]
+    fn round_trip() {
+        check_round_trip(&open_temp_store());
+    }
+
+    #[test]
+    fn remove() {
+        check_remove(&open_temp_store());
+    }
+
+    #[test]
+    fn scan_prefix() {
+        check_scan_prefix(&open_temp_store());
+    }
+
+    #[test]
+    fn scan_prefix_trailing_max_byte() {
+        check_scan_prefix_trailing_max_byte(&open_temp_store());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,161 @@
+use std::sync::{Mutex, MutexGuard};
+
+use rusqlite::{params, types::{Value, ValueRef}, Connection, OptionalExtension};
+
+use crate::db::store::{Entries, Store, StoreError};
+
+// All key spaces share one table so keys stay the same as with sled.
+// Values are JSON and kept as text, so they can be queried with json_extract.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: &str) -> Result<Self, StoreError> {
+        let conn = Connection::open(path)?;
+        conn.query_row("PRAGMA journal_mode=WAL", [], |_| Ok(()))?;
+        conn.execute_batch("CREATE TABLE IF NOT EXISTS kv (key BLOB PRIMARY KEY NOT NULL, value NOT NULL)")?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> Result<MutexGuard<'_, Connection>, StoreError> {
+        self.conn.lock().map_err(|_| StoreError::LockPoisoned)
+    }
+}
+
+impl Store for SqliteStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        let conn = self.conn()?;
+        get_value(&conn, key)
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        let conn = self.conn()?;
+        conn.execute("INSERT INTO kv (key, value) VALUES (?1, ?2)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value_param(value)])?;
+        Ok(())
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        let conn = self.conn()?;
+        let value_opt = get_value(&conn, key)?;
+        if value_opt.is_some() {
+            conn.execute("DELETE FROM kv WHERE key = ?1", params![key])?;
+        }
+        Ok(value_opt)
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Entries, StoreError> {
+        let conn = self.conn()?;
+        let read_entry = |row: &rusqlite::Row| -> rusqlite::Result<(Vec<u8>, Vec<u8>)> {
+            return Ok((value_bytes(row.get_ref(0)?), value_bytes(row.get_ref(1)?)));
+        };
+        let mut entries = Vec::<(Vec<u8>, Vec<u8>)>::new();
+        if let Some(prefix_end) = prefix_end(prefix) {
+            let mut stmt = conn.prepare("SELECT key, value FROM kv WHERE key >= ?1 AND key < ?2 ORDER BY key")?;
+            for entry_res in stmt.query_map(params![prefix, prefix_end], read_entry)? {
+                entries.push(entry_res?);
+            }
+        } else {
+            let mut stmt = conn.prepare("SELECT key, value FROM kv WHERE key >= ?1 ORDER BY key")?;
+            for entry_res in stmt.query_map(params![prefix], read_entry)? {
+                entries.push(entry_res?);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+fn get_value(conn: &Connection, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+    let value_opt = conn.query_row("SELECT value FROM kv WHERE key = ?1", params![key],
+        |row| Ok(value_bytes(row.get_ref(0)?))).optional()?;
+    Ok(value_opt)
+}
+
+fn value_param(value: &[u8]) -> Value {
+    match std::str::from_utf8(value) {
+        Ok(text) => Value::Text(text.to_string()),
+        Err(_) => Value::Blob(value.to_vec()),
+    }
+}
+
+fn value_bytes(value: ValueRef) -> Vec<u8> {
+    match value {
+        ValueRef::Text(bytes) | ValueRef::Blob(bytes) => bytes.to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+// Smallest key after every key starting with prefix, keys are compared bytewise.
+// None when there is no such key, e.g. for an empty prefix.
+fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return Some(end);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::store::tests::{check_remove, check_round_trip, check_scan_prefix, check_scan_prefix_trailing_max_byte, temp_path};
+
+    fn open_temp_store() -> SqliteStore {
+        SqliteStore::open(&temp_path("sqlite")).expect("Unable to open sqlite store")
+    }
+
+    #[test]
+    fn round_trip() {
+        check_round_trip(&open_temp_store());
+    }
+
+    #[test]
+    fn remove() {
+        check_remove(&open_temp_store());
+    }
+
+    #[test]
+    fn scan_prefix() {
+        check_scan_prefix(&open_temp_store());
+    }
+
+    #[test]
+    fn scan_prefix_trailing_max_byte() {
+        check_scan_prefix_trailing_max_byte(&open_temp_store());
+    }
+
+    #[test]
+    fn values_survive_reopening() {
+        let path = temp_path("sqlite");
+        {
+            let store = SqliteStore::open(&path).expect("Unable to open sqlite store");
+            store.insert(b"github/owner/repo", b"{}").expect("insert failed");
+        }
+        let store = SqliteStore::open(&path).expect("Unable to reopen sqlite store");
+        assert_eq!(store.get(b"github/owner/repo").expect("get failed"), Some(b"{}".to_vec()));
+    }
+
+    #[test]
+    fn prefix_end_increments_the_last_byte() {
+        assert_eq!(prefix_end(b"review/"), Some(b"review0".to_vec()));
+        assert_eq!(prefix_end(&[b'a', 0xFF]), Some(vec![b'b']));
+        assert_eq!(prefix_end(&[b'a', 0xFF, 0xFF]), Some(vec![b'b']));
+        assert_eq!(prefix_end(&[0xFF, 0xFF]), None);
+        assert_eq!(prefix_end(b""), None);
+    }
+
+    #[test]
+    fn json_values_are_stored_as_text() {
+        let store = open_temp_store();
+        store.insert(b"github/owner/repo", br#"{"name":"repo"}"#).expect("insert failed");
+        let conn = store.conn().expect("Unable to lock connection");
+        let name: String = conn.query_row("SELECT json_extract(value, '$.name') FROM kv WHERE key = ?1",
+            params![b"github/owner/repo".as_slice()], |row| row.get(0)).expect("Unable to query json");
+        assert_eq!(name, "repo");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,104 @@
+use std::fmt;
+
+// Key-value persistence behind every db module. Key spaces are told apart by prefix:
+// {provider}/{owner}/{repo} for repos and their /config, review/, hunk/, pr_info/, comment/,
+// approvals/, ownership/ and webhook/ per PR or repo, {provider}/aliases/, *_auth_info and
+// github_installation/ for auth and owners: for workspaces.
+// Key and value of every entry, as returned by scan_prefix
+pub type Entries = Vec<(Vec<u8>, Vec<u8>)>;
+
+pub trait Store: Send + Sync {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError>;
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<(), StoreError>;
+
+    // Returns the value that was removed, if any
+    fn remove(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError>;
+
+    // Entries whose key starts with prefix, in key order
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Entries, StoreError>;
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Sled(sled::Error),
+    Sqlite(rusqlite::Error),
+    LockPoisoned,
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Sled(e) => write!(f, "sled error: {}", e),
+            StoreError::Sqlite(e) => write!(f, "sqlite error: {}", e),
+            StoreError::LockPoisoned => write!(f, "db connection lock poisoned"),
+        }
+    }
+}
+
+impl From<sled::Error> for StoreError {
+    fn from(e: sled::Error) -> Self {
+        StoreError::Sled(e)
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        StoreError::Sqlite(e)
+    }
+}
+
+// Behaviour every backend must share, run against each of them from their own tests
+#[cfg(test)]
+pub mod tests {
+    use std::env;
+
+    use super::*;
+
+    pub fn temp_path(backend: &str) -> String {
+        let path = env::temp_dir().join(format!("vibi-dpu-{}-test-{}", backend, uuid::Uuid::new_v4()));
+        path.to_string_lossy().to_string()
+    }
+
+    pub fn check_round_trip(store: &dyn Store) {
+        assert_eq!(store.get(b"github/owner/repo").expect("get failed"), None);
+        store.insert(b"github/owner/repo", br#"{"name":"repo"}"#).expect("insert failed");
+        assert_eq!(store.get(b"github/owner/repo").expect("get failed"), Some(br#"{"name":"repo"}"#.to_vec()));
+        store.insert(b"github/owner/repo", br#"{"name":"renamed"}"#).expect("insert failed");
+        assert_eq!(store.get(b"github/owner/repo").expect("get failed"), Some(br#"{"name":"renamed"}"#.to_vec()));
+        // keys and values are bytes, nothing requires them to be utf-8
+        store.insert(&[0xFF, 0x00], &[0xC3, 0x28]).expect("insert failed");
+        assert_eq!(store.get(&[0xFF, 0x00]).expect("get failed"), Some(vec![0xC3, 0x28]));
+    }
+
+    pub fn check_remove(store: &dyn Store) {
+        store.insert(b"review/github/owner/repo/1", b"{}").expect("insert failed");
+        assert_eq!(store.remove(b"review/github/owner/repo/1").expect("remove failed"), Some(b"{}".to_vec()));
+        assert_eq!(store.get(b"review/github/owner/repo/1").expect("get failed"), None);
+        assert_eq!(store.remove(b"review/github/owner/repo/1").expect("remove failed"), None);
+    }
+
+    pub fn check_scan_prefix(store: &dyn Store) {
+        for key in ["hunk/github/owner/repo/2", "hunk/github/owner/repo/1", "hunk/github/owner/repo-other/1",
+            "hunk/github/owner/rep", "review/github/owner/repo/1"] {
+            store.insert(key.as_bytes(), b"{}").expect("insert failed");
+        }
+        let keys: Vec<Vec<u8>> = store.scan_prefix(b"hunk/github/owner/repo/").expect("scan failed")
+            .into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec![b"hunk/github/owner/repo/1".to_vec(), b"hunk/github/owner/repo/2".to_vec()]);
+        assert_eq!(store.scan_prefix(b"hunk/github/owner/repo").expect("scan failed").len(), 3);
+        assert_eq!(store.scan_prefix(b"hunk/gitlab/").expect("scan failed").len(), 0);
+        assert_eq!(store.scan_prefix(b"").expect("scan failed").len(), 5);
+    }
+
+    // a prefix ending in 0xFF has no end key of the same length to stop the scan at
+    pub fn check_scan_prefix_trailing_max_byte(store: &dyn Store) {
+        for key in [vec![b'a', 0xFF], vec![b'a', 0xFF, 0x01], vec![b'a', 0xFF, 0xFF], vec![b'b'], vec![b'a', 0xFE]] {
+            store.insert(&key, b"{}").expect("insert failed");
+        }
+        let keys: Vec<Vec<u8>> = store.scan_prefix(&[b'a', 0xFF]).expect("scan failed")
+            .into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec![vec![b'a', 0xFF], vec![b'a', 0xFF, 0x01], vec![b'a', 0xFF, 0xFF]]);
+        assert_eq!(store.scan_prefix(&[0xFF]).expect("scan failed").len(), 0);
+    }
+}
\ No newline at end of file
@@ -1,5 +1,4 @@
 use std::collections::HashSet;
-use sled::IVec;
 
 use crate::db::config::get_db;
 use crate::utils::user::{BitbucketUser, WorkspaceUser};
@@ -15,10 +14,10 @@ pub fn set_workspace_user_in_db(user: &WorkspaceUser) {
 		return;
 	}
 	let value = value_res.expect("Uncaught error in value_res");
-	let insert_res = db.insert(user_key.clone(), value);
+	let insert_res = db.insert(user_key.as_bytes(), &value);
 	if insert_res.is_err() {
 		let e = insert_res.expect_err("No error in insert_res");
-		log::error!("[set_workspace_user_in_db] Failed to upsert user into sled DB: {:?}, key: {}", e, &user_key);
+		log::error!("[set_workspace_user_in_db] Failed to upsert user into DB: {:?}, key: {}", e, &user_key);
 		return;
 	}
 	log::debug!("[set_workspace_user_in_db] Workspace User succesfully upserted: {:?} at key: {}", user, &user_key);
@@ -26,7 +25,7 @@ pub fn set_workspace_user_in_db(user: &WorkspaceUser) {
 
 pub fn get_workspace_user_from_db(user_key: &str) -> Option<WorkspaceUser> {
 	let db = get_db();
-	let get_res = db.get(IVec::from(user_key.to_string().as_bytes()));
+	let get_res = db.get(user_key.to_string().as_bytes());
 	if get_res.is_err() {
 		let e = get_res.expect("No error in get_res get_workspace_user_from_db");
 		log::error!("[get_workspace_user_from_db] Unable to get workspace_user_from_db: {:?}", e);
@@ -1,15 +1,16 @@
-use sled::IVec;
-use uuid::Uuid;
 use serde::Serialize;
 use std::fmt::Debug;
 
 use crate::db::config::get_db;
 
-pub fn save_webhook_to_db<T: Serialize>(webhook: &T) where T: Serialize + Debug, {
+// Webhooks are keyed by repo so they can be found and purged along with it
+fn webhook_key(repo_provider: &str, repo_owner: &str, repo_name: &str, hook_id: &str) -> String {
+    format!("webhook/{}/{}/{}/{}", repo_provider, repo_owner, repo_name, hook_id)
+}
+
+pub fn save_webhook_to_db<T>(repo_provider: &str, repo_owner: &str, repo_name: &str, hook_id: &str, webhook: &T) where T: Serialize + Debug, {
     let db = get_db();
-    // Generate unique ID
-    let uuid = Uuid::new_v4();
-    let id = uuid.as_bytes();
+    let key = webhook_key(repo_provider, repo_owner, repo_name, hook_id);
     // Serialize webhook struct to JSON
     let parse_res = serde_json::to_vec(webhook);
     if parse_res.is_err() {
@@ -18,12 +19,20 @@ pub fn save_webhook_to_db<T: Serialize>(webhook: &T) where T: Serialize + Debug,
         return;
     }
     let webhook_json = parse_res.expect("Uncaught error in parse_res webhook");
-    // Insert JSON into sled DB
-    let insert_res = db.insert(IVec::from(id), webhook_json);
+    // Insert JSON into DB
+    let insert_res = db.insert(key.as_bytes(), &webhook_json);
     if insert_res.is_err() {
         let e = insert_res.expect_err("No error in insert_res");
-        log::error!("[save_webhook_to_db] Failed to upsert webhook into sled DB: {e}");
+        log::error!("[save_webhook_to_db] Failed to upsert webhook into DB: {e}");
         return;
     }
     log::debug!("[save_webhook_to_db] Webhook succesfully upserted: {:?}", webhook);
+}
+
+pub fn delete_webhook_from_db(repo_provider: &str, repo_owner: &str, repo_name: &str, hook_id: &str) {
+    let db = get_db();
+    let key = webhook_key(repo_provider, repo_owner, repo_name, hook_id);
+    if let Err(e) = db.remove(key.as_bytes()) {
+        log::error!("[delete_webhook_from_db] Unable to remove {}: {:?}", &key, e);
+    }
 }
\ No newline at end of file
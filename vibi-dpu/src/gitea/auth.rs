@@ -0,0 +1,16 @@
+use std::env;
+
+// Gitea only has token auth for this, an access token of the user the DPU acts as
+pub fn gitea_access_token() -> Option<String> {
+    let gitea_token_res = env::var("GITEA_TOKEN");
+    if gitea_token_res.is_err() {
+        log::debug!("[gitea_access_token] GITEA_TOKEN env var must be set");
+        return None;
+    }
+    let gitea_token = gitea_token_res.expect("Empty GITEA_TOKEN env var");
+    if gitea_token.is_empty() {
+        log::debug!("[gitea_access_token] GITEA_TOKEN 0 length");
+        return None;
+    }
+    Some(gitea_token)
+}
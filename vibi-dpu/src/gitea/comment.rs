@@ -0,0 +1,66 @@
+use serde_json::{json, Value};
+
+use crate::utils::review::Review;
+use crate::utils::reqwest_client::{get_client, send_request};
+
+use super::config::{gitea_base_url, prepare_headers};
+
+// Returns the id of the created comment
+pub async fn add_comment(comment_text: &str, review: &Review, access_token: &str) -> Option<String> {
+    let url = format!("{}/repos/{}/{}/issues/{}/comments", gitea_base_url(),
+        review.repo_owner(), review.repo_name(), review.id());
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[gitea/add_comment] Unable to prepare headers, empty headers_opt");
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let response_res = send_request(get_client().post(&url)
+        .headers(headers).json(&json!({ "body": comment_text }))).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[gitea/add_comment] Error in post request for adding comment - {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[gitea/add_comment] Unable to add comment, status: {}", response.status());
+        return None;
+    }
+    let response_json_res = response.json::<Value>().await;
+    if response_json_res.is_err() {
+        let e = response_json_res.expect_err("No error in response_json_res");
+        log::error!("[gitea/add_comment] Unable to deserialize comment response: {:?}", e);
+        return None;
+    }
+    let response_json = response_json_res.expect("Uncaught error in response_json_res");
+    response_json["id"].as_u64().map(|comment_id| comment_id.to_string())
+}
+
+// Some(false) if the comment no longer exists, None if the edit failed for any other reason
+pub async fn edit_comment(comment_id: &str, comment_text: &str, review: &Review, access_token: &str) -> Option<bool> {
+    let url = format!("{}/repos/{}/{}/issues/comments/{}", gitea_base_url(),
+        review.repo_owner(), review.repo_name(), comment_id);
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[gitea/edit_comment] Unable to prepare headers, empty headers_opt");
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let response_res = send_request(get_client().patch(&url)
+        .headers(headers).json(&json!({ "body": comment_text }))).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[gitea/edit_comment] Error in patch request for editing comment - {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Some(false);
+    }
+    if !response.status().is_success() {
+        log::error!("[gitea/edit_comment] Unable to edit comment {}, status: {}", comment_id, response.status());
+        return None;
+    }
+    Some(true)
+}
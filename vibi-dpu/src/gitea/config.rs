@@ -0,0 +1,118 @@
+use std::env;
+use reqwest::{Response, header::{self, HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION}};
+use serde_json::Value;
+
+use crate::utils::reqwest_client::{get_client, send_request};
+
+// Gitea and Forgejo are always self-hosted, e.g. http://localhost:3000/api/v1
+pub fn gitea_base_url() -> String {
+    env::var("GITEA_BASE_URL").expect("GITEA_BASE_URL must be set")
+}
+
+// Web host for links shown to users, e.g. http://localhost:3000 for http://localhost:3000/api/v1
+pub fn gitea_web_url() -> String {
+    web_url(&gitea_base_url())
+}
+
+fn web_url(base_url: &str) -> String {
+    base_url.trim_end_matches('/').trim_end_matches("/api/v1").to_string()
+}
+
+// All items of a list endpoint, following the Link header across pages
+pub async fn get_api_paginated(url: &str, access_token: &str) -> Option<Vec<Value>> {
+    let mut next_url_opt = Some(url.to_string());
+    let mut values = Vec::<Value>::new();
+    while let Some(get_url) = next_url_opt {
+        let response_opt = get_api_response(&get_url, access_token).await;
+        if response_opt.is_none() {
+            log::error!("[get_api_paginated] Unable to call get api for: {:?}", &get_url);
+            return None;
+        }
+        let response = response_opt.expect("Empty response_opt");
+        next_url_opt = extract_next_url(&response);
+        let parse_res = response.json::<Value>().await;
+        if parse_res.is_err() {
+            let e = parse_res.expect_err("No error in parse_res");
+            log::error!("[get_api_paginated] Unable to deserialize response for {}: {:?}", &get_url, e);
+            return None;
+        }
+        let page_json = parse_res.expect("Uncaught error in parse_res");
+        let page_values_opt = page_json.as_array();
+        if page_values_opt.is_none() {
+            log::error!("[get_api_paginated] Response is not a list: {:?}", &page_json);
+            return None;
+        }
+        values.extend(page_values_opt.expect("Empty page_values_opt").to_owned());
+    }
+    Some(values)
+}
+
+pub async fn get_api_response(url: &str, access_token: &str) -> Option<Response> {
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[get_api_response] Unable to prepare_headers, empty headers_opt");
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let client = get_client();
+    let get_res = send_request(client.get(url).headers(headers)).await;
+    if get_res.is_err() {
+        let e = get_res.expect_err("No error in get_res");
+        log::error!("[get_api_response] Error sending GET request to {}, error: {}", url, e);
+        return None;
+    }
+    let response = get_res.expect("Uncaught error in get_res");
+    if !response.status().is_success() {
+        log::error!("[get_api_response] Failed to call Gitea API {}, status: {}", url, response.status());
+        return None;
+    }
+    Some(response)
+}
+
+fn extract_next_url(response: &Response) -> Option<String> {
+    let link_header_opt = response.headers().get(header::LINK);
+    link_header_opt
+        .and_then(|value| value.to_str().ok())
+        .and_then(next_link_url)
+}
+
+fn next_link_url(link_header: &str) -> Option<String> {
+    link_header.split(',')
+        .find(|part| part.contains(r#"rel="next""#))
+        .and_then(|next_link_part| next_link_part.split(';').next())
+        .map(|url| url.trim_matches(&[' ', '<', '>', '"'] as &[_]).to_string())
+}
+
+pub fn prepare_headers(access_token: &str) -> Option<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    let auth_header_res = HeaderValue::from_str(&format!("token {}", access_token));
+    if auth_header_res.is_err() {
+        let e = auth_header_res.expect_err("Empty error in auth_header_res");
+        log::error!("[prepare_headers] Invalid auth header: {:?}", e);
+        return None;
+    }
+    headers.insert(AUTHORIZATION, auth_header_res.expect("Uncaught error in auth_header_res"));
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    Some(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn web_url_drops_the_api_path() {
+        assert_eq!(web_url("http://localhost:3000/api/v1"), "http://localhost:3000");
+        assert_eq!(web_url("http://localhost:3000/api/v1/"), "http://localhost:3000");
+        assert_eq!(web_url("https://git.example.com"), "https://git.example.com");
+    }
+
+    #[test]
+    fn next_link_is_read_from_the_link_header() {
+        let link_header = r#"<http://localhost:3000/api/v1/repos/o/r/pulls?page=3>; rel="next", <http://localhost:3000/api/v1/repos/o/r/pulls?page=5>; rel="last""#;
+        assert_eq!(next_link_url(link_header),
+            Some("http://localhost:3000/api/v1/repos/o/r/pulls?page=3".to_string()));
+        let last_page_header = r#"<http://localhost:3000/api/v1/repos/o/r/pulls?page=1>; rel="first""#;
+        assert_eq!(next_link_url(last_page_header), None);
+    }
+}
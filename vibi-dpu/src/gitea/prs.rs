@@ -0,0 +1,124 @@
+use serde_json::Value;
+
+use crate::db::prs::update_pr_info_in_db;
+use crate::utils::pr_info::PrInfo;
+use crate::utils::user::ProviderEnum;
+
+use super::config::{get_api_paginated, get_api_response, gitea_base_url};
+
+// Numbers of the repo's pull requests, `state` is one of open, closed or all
+pub async fn list_prs_gitea(repo_owner: &str, repo_name: &str, access_token: &str, state: &str) -> Option<Vec<String>> {
+    let url = format!("{}/repos/{}/{}/pulls?state={}&limit=50",
+        gitea_base_url(), repo_owner, repo_name, state);
+    let prs_opt = get_api_paginated(&url, access_token).await;
+    if prs_opt.is_none() {
+        log::error!("[list_prs_gitea] Unable to get pull requests for {}/{}", repo_owner, repo_name);
+        return None;
+    }
+    let pr_list = prs_opt.expect("Empty prs_opt").iter()
+        .map(|pr| pr["number"].to_string())
+        .collect();
+    Some(pr_list)
+}
+
+pub async fn get_pr_info_gitea(repo_owner: &str, repo_name: &str, access_token: &str, pr_number: &str) -> Option<PrInfo> {
+    let url = format!("{}/repos/{}/{}/pulls/{}", gitea_base_url(), repo_owner, repo_name, pr_number);
+    let response_opt = get_api_response(&url, access_token).await;
+    if response_opt.is_none() {
+        log::error!("[get_pr_info_gitea] Unable to get pull request {} for {}/{}", pr_number, repo_owner, repo_name);
+        return None;
+    }
+    let parse_res = response_opt.expect("Empty response_opt").json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[get_pr_info_gitea] Error parsing PR data: {:?}", e);
+        return None;
+    }
+    let pr_data = parse_res.expect("Uncaught error in parse_res");
+    let pr_info = PrInfo {
+        base_head_commit: pr_data["base"]["sha"].as_str()?.to_string(),
+        pr_head_commit: pr_data["head"]["sha"].as_str()?.to_string(),
+        state: pr_data["state"].as_str()?.to_string(),
+        pr_branch: pr_data["head"]["ref"].as_str()?.to_string(),
+        author: Some(pr_data["user"]["login"].as_str()?.to_string()),
+    };
+    log::debug!("[get_pr_info_gitea] PR info: {:?}", &pr_info);
+    Some(pr_info)
+}
+
+pub async fn get_and_store_pr_info(repo_owner: &str, repo_name: &str, access_token: &str, pr_number: &str) {
+    let pr_info_opt = get_pr_info_gitea(repo_owner, repo_name, access_token, pr_number).await;
+    if pr_info_opt.is_none() {
+        log::error!("[get_and_store_pr_info] No PR info available for PR number: {:?} repository: {:?}/{:?}",
+            pr_number, repo_owner, repo_name);
+        return;
+    }
+    update_pr_info_in_db(repo_owner, repo_name, &pr_info_opt.expect("Empty pr_info_opt"), pr_number,
+        &ProviderEnum::Gitea.to_string()).await;
+}
+
+// Latest approval of every reviewer as (login, approved commit), later change requests
+// or dismissals drop the approval
+pub async fn pr_approvals(repo_owner: &str, repo_name: &str, pr_number: &str, access_token: &str) -> Option<Vec<(String, String)>> {
+    let url = format!("{}/repos/{}/{}/pulls/{}/reviews", gitea_base_url(), repo_owner, repo_name, pr_number);
+    let reviews_opt = get_api_paginated(&url, access_token).await;
+    if reviews_opt.is_none() {
+        log::error!("[pr_approvals] Unable to get reviews of pull request {}", pr_number);
+        return None;
+    }
+    Some(latest_approvals(reviews_opt.expect("Empty reviews_opt")))
+}
+
+// reviews are returned in chronological order
+fn latest_approvals(reviews: Vec<Value>) -> Vec<(String, String)> {
+    let mut approvals = Vec::<(String, String)>::new();
+    for review in reviews {
+        let login_opt = review["user"]["login"].as_str();
+        if login_opt.is_none() {
+            continue;
+        }
+        let login = login_opt.expect("Empty login_opt");
+        let state = review["state"].as_str().unwrap_or_default();
+        let dismissed = review["dismissed"].as_bool().unwrap_or(false);
+        if state == "APPROVED" || state == "REQUEST_CHANGES" || dismissed {
+            approvals.retain(|(approver, _)| approver != login);
+        }
+        if state == "APPROVED" && !dismissed {
+            let commit_id = review["commit_id"].as_str().unwrap_or_default();
+            approvals.push((login.to_string(), commit_id.to_string()));
+        }
+    }
+    approvals
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn latest_approval_per_login_is_kept() {
+        let reviews = vec![
+            json!({"user": {"login": "alice"}, "state": "APPROVED", "commit_id": "c1"}),
+            json!({"user": {"login": "bob"}, "state": "APPROVED", "commit_id": "c1"}),
+            json!({"user": {"login": "alice"}, "state": "APPROVED", "commit_id": "c2"}),
+        ];
+        assert_eq!(latest_approvals(reviews), vec![
+            ("bob".to_string(), "c1".to_string()),
+            ("alice".to_string(), "c2".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn rejections_and_dismissals_drop_approvals() {
+        let reviews = vec![
+            json!({"user": {"login": "alice"}, "state": "APPROVED", "commit_id": "c1"}),
+            json!({"user": {"login": "bob"}, "state": "APPROVED", "commit_id": "c1"}),
+            json!({"user": {"login": "carol"}, "state": "APPROVED", "commit_id": "c1", "dismissed": true}),
+            json!({"user": {"login": "alice"}, "state": "REQUEST_CHANGES", "commit_id": "c2"}),
+            json!({"user": {"login": "bob"}, "state": "COMMENT", "commit_id": "c2", "dismissed": true}),
+        ];
+        assert!(latest_approvals(reviews).is_empty());
+    }
+}
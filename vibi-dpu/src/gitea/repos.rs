@@ -0,0 +1,43 @@
+use serde_json::Value;
+
+use crate::db::repo::save_repo_to_db;
+use crate::utils::repo::Repository;
+use crate::utils::user::ProviderEnum;
+
+use super::config::{get_api_paginated, gitea_base_url};
+
+// Repos the token's user owns or is a member of
+pub async fn get_gitea_repos(access_token: &str) -> Option<Vec<Repository>> {
+    let repos_url = format!("{}/user/repos?limit=50", gitea_base_url());
+    let repos_opt = get_api_paginated(&repos_url, access_token).await;
+    if repos_opt.is_none() {
+        log::error!("[get_gitea_repos] Unable to call get api and get all repos");
+        return None;
+    }
+    let mut repos = Vec::<Repository>::new();
+    for repo_json in repos_opt.expect("Empty repos_opt") {
+        let repo = deserialize_repo_object(&repo_json);
+        save_repo_to_db(&repo);
+        repos.push(repo);
+    }
+    log::debug!("[get_gitea_repos] Fetched {:?} repositories from Gitea", &repos);
+    Some(repos)
+}
+
+// Keeps the http clone url, ssh is often turned off on self-hosted instances
+fn deserialize_repo_object(repo_json: &Value) -> Repository {
+    let repo_owner = repo_json["owner"]["login"].to_string().trim_matches('"').to_string();
+    let repo = Repository::new(
+        repo_json["name"].to_string().trim_matches('"').to_string(),
+        repo_json["id"].to_string(),
+        repo_owner.clone(),
+        repo_json["private"].as_bool().unwrap_or(true),
+        repo_json["clone_url"].to_string().trim_matches('"').to_string(),
+        None,
+        None,
+        repo_owner,
+        None,
+        ProviderEnum::Gitea.to_string(),
+    );
+    repo
+}
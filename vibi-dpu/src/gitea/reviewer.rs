@@ -0,0 +1,30 @@
+use serde_json::json;
+
+use crate::utils::review::Review;
+use crate::utils::reqwest_client::{get_client, send_request};
+
+use super::config::{gitea_base_url, prepare_headers};
+
+pub async fn add_reviewers(reviewers: &Vec<String>, review: &Review, access_token: &str) {
+    let url = format!("{}/repos/{}/{}/pulls/{}/requested_reviewers", gitea_base_url(),
+        review.repo_owner(), review.repo_name(), review.id());
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[add_reviewers] Unable to prepare headers, empty headers_opt");
+        return;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let response_res = send_request(get_client().post(&url)
+        .headers(headers).json(&json!({ "reviewers": reviewers }))).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[add_reviewers] Unable to add reviewers: {:?}, {:?}", e, reviewers);
+        return;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[add_reviewers] Unable to add reviewers {:?}, status: {}", reviewers, response.status());
+        return;
+    }
+    log::debug!("[add_reviewers] Added reviewers {:?} to PR {}", reviewers, review.id());
+}
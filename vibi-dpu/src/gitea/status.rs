@@ -0,0 +1,36 @@
+use serde_json::json;
+
+use crate::utils::reqwest_client::{get_client, send_request};
+
+use super::config::{gitea_base_url, prepare_headers};
+
+const COVERAGE_STATUS_CONTEXT: &str = "vibinex/relevant-approvals";
+
+pub async fn set_commit_status(repo_owner: &str, repo_name: &str, commit: &str,
+        state: &str, description: &str, target_url: &str, access_token: &str) {
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[set_commit_status] Unable to prepare auth headers for repository: {}", repo_name);
+        return;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let url = format!("{}/repos/{}/{}/statuses/{}", gitea_base_url(), repo_owner, repo_name, commit);
+    let body = json!({
+        "state": state,
+        "description": description,
+        "target_url": target_url,
+        "context": COVERAGE_STATUS_CONTEXT,
+    });
+    let response_res = send_request(get_client().post(&url).headers(headers).json(&body)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[set_commit_status] Failed to send the request: {:?}", e);
+        return;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[set_commit_status] Unable to set status on {}, status: {}", commit, response.status());
+        return;
+    }
+    log::debug!("[set_commit_status] Status {} set on commit {}", state, commit);
+}
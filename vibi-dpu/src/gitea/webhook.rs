@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::env;
+
+use serde_json::{json, Value};
+
+use crate::db::webhook::save_webhook_to_db;
+use crate::utils::github_webhook::Webhook;
+use crate::utils::reqwest_client::{get_client, send_request};
+use crate::utils::user::ProviderEnum;
+
+use super::config::{get_api_paginated, gitea_base_url, prepare_headers};
+
+pub async fn get_webhooks_in_repo(repo_owner: &str, repo_name: &str, access_token: &str) -> Option<Vec<Webhook>> {
+    let url = format!("{}/repos/{}/{}/hooks", gitea_base_url(), repo_owner, repo_name);
+    log::debug!("[get_webhooks_in_repo] Getting webhooks from {}", url);
+    let hooks_opt = get_api_paginated(&url, access_token).await;
+    if hooks_opt.is_none() {
+        log::error!("[get_webhooks_in_repo] Unable to call get api and get all webhooks");
+        return None;
+    }
+    let webhooks = hooks_opt.expect("Empty hooks_opt").iter()
+        .map(deserialize_webhook_object)
+        .collect();
+    Some(webhooks)
+}
+
+pub async fn add_webhook(repo_owner: &str, repo_name: &str, access_token: &str) {
+    let url = format!("{}/repos/{}/{}/hooks", gitea_base_url(), repo_owner, repo_name);
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[add_webhook] Unable to prepare headers for {}/{}", repo_owner, repo_name);
+        return;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let callback_url = format!("{}/api/gitea/callbacks/webhook",
+        env::var("SERVER_URL").expect("SERVER_URL must be set"));
+    let payload = json!({
+        "type": "gitea",
+        "events": ["pull_request", "pull_request_sync",
+            "pull_request_review_approved", "pull_request_review_rejected"],
+        "config": { "url": callback_url, "content_type": "json" },
+        "active": true,
+    });
+    let response_res = send_request(get_client().post(&url).headers(headers).json(&payload)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[add_webhook] Error in api call: {:?}", e);
+        return;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[add_webhook] Failed to add webhook. Status code: {}, Text: {:?}",
+            response.status(), response.text().await);
+        return;
+    }
+    let parse_res = response.json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[add_webhook] Unable to deserialize webhook response: {:?}", e);
+        return;
+    }
+    let webhook = deserialize_webhook_object(&parse_res.expect("Uncaught error in parse_res"));
+    log::info!("Webhook added: {:?}", webhook.url());
+    save_webhook_to_db(&ProviderEnum::Gitea.to_string(), repo_owner, repo_name, webhook.id(), &webhook);
+}
+
+fn deserialize_webhook_object(webhook_json: &Value) -> Webhook {
+    let config: HashMap<String, Value> = webhook_json["config"].as_object()
+        .map(|config_obj| config_obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default();
+    let webhook = Webhook::new(
+        webhook_json["id"].to_string(),
+        webhook_json["active"].as_bool().unwrap_or(false),
+        webhook_json["created_at"].to_string().replace('"', ""),
+        webhook_json["events"].as_array().cloned().unwrap_or_default().iter()
+            .filter_map(|event| event.as_str())
+            .map(|event| event.to_string())
+            .collect(),
+        String::new(),
+        webhook_json["config"]["url"].to_string().replace('"', ""),
+        config,
+    );
+    webhook
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhook_is_read_from_the_api_object() {
+        let webhook_json = json!({
+            "id": 7,
+            "active": true,
+            "created_at": "2024-01-01T00:00:00Z",
+            "events": ["pull_request", "pull_request_review_approved"],
+            "config": {"url": "https://dpu.example.com/api/gitea/callbacks/webhook", "content_type": "json"},
+        });
+        let webhook = deserialize_webhook_object(&webhook_json);
+        assert_eq!(webhook.id(), "7");
+        assert!(webhook.active());
+        assert_eq!(webhook.events(), &vec!["pull_request".to_string(), "pull_request_review_approved".to_string()]);
+        assert_eq!(webhook.url(), "https://dpu.example.com/api/gitea/callbacks/webhook");
+        assert_eq!(webhook.config().get("content_type"), Some(&json!("json")));
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let webhook = deserialize_webhook_object(&json!({"id": 8}));
+        assert!(!webhook.active());
+        assert!(webhook.events().is_empty());
+        assert!(webhook.config().is_empty());
+    }
+}
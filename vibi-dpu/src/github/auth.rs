@@ -1,16 +1,21 @@
 use chrono::DateTime;
 use jsonwebtoken::{encode, Header, EncodingKey, Algorithm};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::env;
 use std::str;
 use chrono::{Utc, Duration};
 use std::fs;
-use crate::db::github::auth::get_github_auth_info_from_db;
+use crate::db::github::auth::{get_github_auth_info_from_db, get_github_installations_from_db, get_repo_installation_from_db, save_repo_installation_to_db};
 use crate::utils::review::Review;
 use crate::utils::user::ProviderEnum;
-use crate::{utils::reqwest_client::get_client, utils::github_auth_info::GithubAuthInfo, db::github::auth::save_github_auth_info_to_db};
+use crate::{utils::reqwest_client::{get_client, send_request}, utils::github_auth_info::GithubAuthInfo, db::github::auth::save_github_auth_info_to_db};
 use crate::utils::gitops::set_git_remote_url;
 
+use super::config::github_base_url;
+
+const DEFAULT_PEM_PATH: &str = "/app/repo-profiler.pem";
+
 #[derive(Debug, Serialize, Deserialize)]
 struct AccessTokenResponse {
     token: String,
@@ -50,16 +55,34 @@ fn generate_claims(github_app_id: &str) -> Claims{
     };
 }
 
-fn generate_encoding_key() -> Option<EncodingKey>{
-    let pem_file_path = "/app/repo-profiler.pem";
-    let pem_data_res = fs::read(pem_file_path);
-    
+// The app's private key, inline from GITHUB_APP_PRIVATE_KEY or read from GITHUB_APP_PRIVATE_KEY_PATH
+fn app_private_key() -> Option<Vec<u8>> {
+    if let Ok(pem_str) = env::var("GITHUB_APP_PRIVATE_KEY") {
+        if !pem_str.trim().is_empty() {
+            // keys pasted into env files usually have their newlines escaped
+            return Some(pem_str.replace("\\n", "\n").into_bytes());
+        }
+    }
+    let pem_file_path = env::var("GITHUB_APP_PRIVATE_KEY_PATH").ok()
+        .filter(|path| !path.trim().is_empty())
+        .unwrap_or(DEFAULT_PEM_PATH.to_string());
+    let pem_data_res = fs::read(&pem_file_path);
     if pem_data_res.is_err() {
         let pem_data_err = pem_data_res.expect_err("Empty error in reading pem file");
-        log::error!("[generate_encoding_key] Error reading pem file: {:?}", pem_data_err);
+        log::error!("[app_private_key] Error reading pem file {}: {:?}", &pem_file_path, pem_data_err);
         return None;
     }
     let pem_data = pem_data_res.expect("Error reading pem file");
+    Some(pem_data)
+}
+
+fn generate_encoding_key() -> Option<EncodingKey>{
+    let pem_data_opt = app_private_key();
+    if pem_data_opt.is_none() {
+        log::error!("[generate_encoding_key] No GitHub App private key configured");
+        return None;
+    }
+    let pem_data = pem_data_opt.expect("Empty pem_data_opt");
     let encoding_key_res = EncodingKey::from_rsa_pem(&pem_data);
     if encoding_key_res.is_err() {
         let e= encoding_key_res.err().expect("Empty error in encoding_key_res");
@@ -92,12 +115,10 @@ async fn call_access_token_api(installation_id: &str) -> Option<GithubAuthInfo>{
     }
     let jwt_token = jwt_token_opt.expect("Empty jwt_token_opt");
     let client = get_client();
-    let response = client.post(&format!("https://api.github.com/app/installations/{}/access_tokens", installation_id))
+    let response = send_request(client.post(format!("{}/app/installations/{}/access_tokens", github_base_url(), installation_id))
         .header("Accept", "application/vnd.github+json")
         .header("Authorization", format!("Bearer {}", jwt_token))
-        .header("User-Agent", "Vibinex code review Test App")
-        .send()
-        .await;
+        .header("User-Agent", "Vibinex code review Test App")).await;
     if response.is_err() {
         let e = response.expect_err("No error in response");
         log::error!("[call_access_token_api] error in calling github api : {:?}", e);
@@ -125,33 +146,113 @@ async fn call_access_token_api(installation_id: &str) -> Option<GithubAuthInfo>{
 }
 
 async fn get_or_update_auth(review_opt: &Option<Review>) -> Option<GithubAuthInfo> {
-    let authinfo_opt = get_github_auth_info_from_db()
-        .or_else(|| GithubAuthInfo::load_from_file());
+    let authinfo_opt = match review_opt {
+        Some(review) => repo_auth_info(review.repo_owner(), review.repo_name()).await,
+        None => single_installation_auth_info(),
+    };
     if authinfo_opt.is_none() {
         log::error!("[get_or_update_auth] Unable to get github auth info from db or storage");
         return None;
     }
     let auth_info = authinfo_opt.expect("empty authinfo_opt in app_access_token");
+    return refresh_if_expired(auth_info, review_opt).await;
+}
+
+// Auth of the installation serving the repo, fetched if it isn't stored yet
+async fn repo_auth_info(repo_owner: &str, repo_name: &str) -> Option<GithubAuthInfo> {
+    let installation_id_opt = repo_installation_id(repo_owner, repo_name).await;
+    if installation_id_opt.is_none() {
+        log::error!("[repo_auth_info] No github app installation found for {}/{}", repo_owner, repo_name);
+        return None;
+    }
+    let installation_id = installation_id_opt.expect("Empty installation_id_opt");
+    let auth_info_opt = get_github_auth_info_from_db(&installation_id);
+    if auth_info_opt.is_some() {
+        return auth_info_opt;
+    }
+    return fetch_access_token(&installation_id).await;
+}
+
+async fn repo_installation_id(repo_owner: &str, repo_name: &str) -> Option<String> {
+    let installation_id_opt = get_repo_installation_from_db(repo_owner, repo_name);
+    if installation_id_opt.is_some() {
+        return installation_id_opt;
+    }
+    log::info!("No installation stored for {}/{}, asking github...", repo_owner, repo_name);
+    let installation_id_opt = call_repo_installation_api(repo_owner, repo_name).await;
+    if let Some(installation_id) = &installation_id_opt {
+        save_repo_installation_to_db(repo_owner, repo_name, installation_id);
+    }
+    installation_id_opt
+}
+
+async fn call_repo_installation_api(repo_owner: &str, repo_name: &str) -> Option<String> {
+    let github_app_id = env::var("GITHUB_APP_ID");
+    let github_app_id_str = github_app_id.expect("GITHUB_APP_ID must be set");
+    let jwt_token_opt = generate_jwt(&github_app_id_str);
+    if jwt_token_opt.is_none() {
+        log::error!("[call_repo_installation_api] Unable to generate jwt token");
+        return None;
+    }
+    let jwt_token = jwt_token_opt.expect("Empty jwt_token_opt");
+    let client = get_client();
+    let response_res = send_request(client.get(format!("{}/repos/{}/{}/installation", github_base_url(), repo_owner, repo_name))
+        .header("Accept", "application/vnd.github+json")
+        .header("Authorization", format!("Bearer {}", jwt_token))
+        .header("User-Agent", "Vibinex code review Test App")).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[call_repo_installation_api] error in calling github api : {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[call_repo_installation_api] Failed to get installation of {}/{}: {}",
+            repo_owner, repo_name, response.status());
+        return None;
+    }
+    let installation_res = response.json::<Value>().await;
+    if installation_res.is_err() {
+        let e = installation_res.expect_err("No error in installation_res");
+        log::error!("[call_repo_installation_api] Unable to deserialize installation: {:?}", e);
+        return None;
+    }
+    let installation = installation_res.expect("Uncaught error in installation_res");
+    installation["id"].as_u64().map(|installation_id| installation_id.to_string())
+}
+
+// Calls without a repo can only be served when there is no choice of installation
+fn single_installation_auth_info() -> Option<GithubAuthInfo> {
+    let mut installations = get_github_installations_from_db();
+    if installations.len() > 1 {
+        log::error!("[single_installation_auth_info] {} github installations stored, can't pick one without a repo",
+            installations.len());
+        return None;
+    }
+    installations.pop().or_else(GithubAuthInfo::load_from_file)
+}
+
+async fn refresh_if_expired(auth_info: GithubAuthInfo, review_opt: &Option<Review>) -> Option<GithubAuthInfo> {
     let app_installation_id_opt = auth_info.installation_id().to_owned();
     if app_installation_id_opt.is_none() {
-        log::error!("[get_or_update_auth] app_installation_id empty");
+        log::error!("[refresh_if_expired] app_installation_id empty");
         return None;
     }
     let app_installation_id = app_installation_id_opt.expect("Empty app_installation_id_opt");
     if update_condition_satisfied(auth_info.expires_at()) {  
-        log::debug!("[get_or_update_auth] access token not yet expired");
+        log::debug!("[refresh_if_expired] access token not yet expired");
         return Some(auth_info.to_owned());
     }
     // auth info has expired
-    log::debug!("[get_or_update_auth] github auth info expired");
+    log::debug!("[refresh_if_expired] github auth info expired for installation {}", &app_installation_id);
     let new_auth_info_opt = fetch_access_token(app_installation_id.as_str()).await;
     if new_auth_info_opt.is_none() {
-        log::error!("[get_or_update_auth] Unable to fetch access token");
+        log::error!("[refresh_if_expired] Unable to fetch access token");
         return None;
     }
-    let mut new_auth_info = new_auth_info_opt.clone()
-        .expect("empty auhtinfo_opt from get_or_update_auth");
-    log::debug!("[get_or_update_auth] New github auth info  = {:?}", &new_auth_info);
+    let new_auth_info = new_auth_info_opt.clone()
+        .expect("empty auhtinfo_opt from refresh_if_expired");
+    log::debug!("[refresh_if_expired] New github auth info  = {:?}", &new_auth_info);
     if review_opt.is_some() {
         let review = review_opt.to_owned().expect("Empty review");
         set_git_remote_url(&review, new_auth_info.token(),
@@ -186,6 +287,17 @@ pub async fn app_access_token(review: &Option<Review>) -> Option<String>{
     return Some(access_token);
 }
 
+// Token of one installation, refreshed if expired
+pub async fn installation_access_token(auth_info: GithubAuthInfo) -> Option<String> {
+    let authinfo_opt = refresh_if_expired(auth_info, &None).await;
+    if authinfo_opt.is_none() {
+        log::error!("[installation_access_token] Unable to refresh github auth info");
+        return None;
+    }
+    let authinfo = authinfo_opt.expect("Empty authinfo_opt");
+    Some(authinfo.token().to_string())
+}
+
 fn pat_access_token() -> Option<String> {
     let github_pat_res: Result<String, env::VarError> = env::var("GITHUB_PAT");
 	let provider_res = env::var("PROVIDER");	
@@ -223,4 +335,17 @@ pub async fn gh_access_token(review: &Option<Review>) -> Option<String> {
         return Some(pat_token);
     }
     return app_access_token(review).await;
+}
+
+// Token of the installation serving the repo, for calls made without a review
+pub async fn repo_access_token(repo_owner: &str, repo_name: &str) -> Option<String> {
+    if let Some(pat_token) = pat_access_token() {
+        return Some(pat_token);
+    }
+    let auth_info_opt = repo_auth_info(repo_owner, repo_name).await;
+    if auth_info_opt.is_none() {
+        log::error!("[repo_access_token] Unable to get github auth info for {}/{}", repo_owner, repo_name);
+        return None;
+    }
+    installation_access_token(auth_info_opt.expect("Empty auth_info_opt")).await
 }
\ No newline at end of file
@@ -2,10 +2,60 @@ use std::{env, collections::HashMap};
 use reqwest::{Response, header::{HeaderMap, HeaderValue, AUTHORIZATION, ACCEPT, USER_AGENT}, header};
 use serde_json::Value;
 
-use crate::utils::reqwest_client::get_client;
+use crate::utils::reqwest_client::{get_client, send_request};
+
+const GITHUB_API_URL: &str = "https://api.github.com";
 
 pub fn github_base_url() -> String {
-    env::var("GITHUB_BASE_URL").expect("BITBUCKET_BASE_URL must be set")
+    base_url(env::var("GITHUB_ENTERPRISE_URL").ok(), env::var("GITHUB_BASE_URL").ok())
+}
+
+// GITHUB_ENTERPRISE_URL is the web root of a GitHub Enterprise Server instance, e.g.
+// https://github.example.com. REST, GraphQL and git endpoints are all derived from it when set.
+fn base_url(enterprise_url_opt: Option<String>, base_url_opt: Option<String>) -> String {
+    let enterprise_url_opt = enterprise_url_opt
+        .map(|url| url.trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty());
+    if let Some(enterprise_url) = enterprise_url_opt {
+        return format!("{}/api/v3", enterprise_url);
+    }
+    base_url_opt
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or(GITHUB_API_URL.to_string())
+}
+
+pub fn github_graphql_url() -> String {
+    graphql_url(&github_base_url())
+}
+
+// GHES serves graphql next to the REST root, at /api/graphql instead of /api/v3/graphql
+fn graphql_url(base_url: &str) -> String {
+    if let Some(enterprise_root) = base_url.strip_suffix("/api/v3") {
+        return format!("{}/api/graphql", enterprise_root);
+    }
+    format!("{}/graphql", base_url)
+}
+
+pub fn github_web_url() -> String {
+    web_url(&github_base_url())
+}
+
+// Web host for links shown to users, e.g. https://github.com for https://api.github.com
+fn web_url(base_url: &str) -> String {
+    if base_url.contains("://api.github.com") {
+        return "https://github.com".to_string();
+    }
+    base_url.trim_end_matches('/').trim_end_matches("/api/v3").to_string()
+}
+
+pub fn github_git_host() -> String {
+    git_host(&github_web_url())
+}
+
+// Host part of ssh clone urls, e.g. github.com in git@github.com:owner/repo.git
+fn git_host(web_url: &str) -> String {
+    let host = web_url.split_once("://").map(|(_, host)| host).unwrap_or(web_url);
+    host.split('/').next().unwrap_or(host).to_string()
 }
 
 pub async fn get_api_paginated(url: &str, access_token: &str, params: Option<HashMap<&str, &str>> ) -> Option<Vec<Value>> {
@@ -14,12 +64,15 @@ pub async fn get_api_paginated(url: &str, access_token: &str, params: Option<Has
     let mut get_url = url.to_string();
     let mut values = Vec::<Value>::new();
     while is_first_call || next_url_mut.is_some() {
+        // next links already carry the query params
+        let mut page_params = &None;
         if is_first_call {
             is_first_call = false;
+            page_params = &params;
         } else {
             get_url = next_url_mut.expect("Empty next_url_mut");
         }
-        let response_opt = get_api_response(&get_url, None, &access_token, &params).await;
+        let response_opt = get_api_response(&get_url, None, access_token, page_params).await;
         if response_opt.is_none() {
             log::error!("[get_api_paginated] Unable to call get api and get initial response for: 
                 {:?}, {:?}, {:?}", url, access_token, params);
@@ -49,11 +102,9 @@ async fn get_api_response(url: &str, headers_opt: Option<reqwest::header::Header
     }
     let headers = get_headers_opt.expect("Uncaught error in get_headers_opt");
     let client = get_client();
-    let get_response = client.get(url)
+    let get_response = send_request(client.get(url)
         .headers(headers.clone())
-        .query(params)
-        .send()
-        .await;
+        .query(params)).await;
 
     if get_response.is_err() {
         let e = get_response.expect_err("No error in get_response");
@@ -152,4 +203,42 @@ pub fn prepare_headers(access_token: &str) -> Option<HeaderMap> {
     headers.insert(USER_AGENT, user_agent_header);
 
     return Some(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enterprise_base_url() -> String {
+        base_url(Some("https://github.example.com/".to_string()), None)
+    }
+
+    #[test]
+    fn base_url_defaults_to_public_api() {
+        assert_eq!(base_url(None, None), "https://api.github.com");
+        assert_eq!(base_url(Some("".to_string()), None), "https://api.github.com");
+        assert_eq!(base_url(None, Some("http://localhost:8080/".to_string())), "http://localhost:8080");
+    }
+
+    #[test]
+    fn enterprise_url_takes_precedence_over_base_url() {
+        assert_eq!(enterprise_base_url(), "https://github.example.com/api/v3");
+        assert_eq!(base_url(Some("https://github.example.com".to_string()),
+            Some("http://localhost:8080".to_string())), "https://github.example.com/api/v3");
+    }
+
+    #[test]
+    fn graphql_url_follows_base_url() {
+        assert_eq!(graphql_url(&base_url(None, None)), "https://api.github.com/graphql");
+        assert_eq!(graphql_url(&enterprise_base_url()), "https://github.example.com/api/graphql");
+    }
+
+    #[test]
+    fn web_url_and_git_host_follow_base_url() {
+        assert_eq!(web_url(&base_url(None, None)), "https://github.com");
+        assert_eq!(web_url(&enterprise_base_url()), "https://github.example.com");
+        assert_eq!(git_host(&web_url(&base_url(None, None))), "github.com");
+        assert_eq!(git_host(&web_url(&enterprise_base_url())), "github.example.com");
+        assert_eq!(git_host("http://localhost:3000/sub/path"), "localhost:3000");
+    }
 }
\ No newline at end of file
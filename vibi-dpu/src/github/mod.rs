@@ -3,4 +3,6 @@ pub mod repos;
 pub mod config;
 pub mod webhook;
 pub mod prs;
-pub mod user;
\ No newline at end of file
+pub mod user;
+pub mod teams;
+pub mod status;
\ No newline at end of file
@@ -1,75 +1,44 @@
 use crate::db::prs::update_pr_info_in_db;
 use crate::utils::user::ProviderEnum;
-use crate::utils::{pr_info::PrInfo, reqwest_client::get_client};
-use reqwest::header::{HeaderMap, USER_AGENT};
+use crate::utils::{pr_info::{PrInfo, PrSummary}, reqwest_client::{get_client, send_request}};
+use reqwest::header::USER_AGENT;
 use reqwest::Response;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::str;
 
-use super::config::{github_base_url, prepare_headers};
+use super::config::{get_api_paginated, github_base_url, prepare_headers};
 
 
-pub async fn list_prs_github(repo_owner: &str, repo_name: &str, access_token: &str, state: &str) -> Option<Vec<String>> {
-    let headers_opt = prepare_headers(access_token);
-    if headers_opt.is_none() {
-        log::error!("[list_prs_github] Unable to prepare auth headers for repository: {}", repo_name);
-        return None;
-    }
-    let headers = headers_opt.expect("Headers should be present");
-
+pub async fn list_prs_github(repo_owner: &str, repo_name: &str, access_token: &str, state: &str) -> Option<Vec<PrSummary>> {
+    let url = format!("{}/repos/{}/{}/pulls", github_base_url(), repo_owner, repo_name);
+    let state_param = state.to_lowercase();
     let mut params = HashMap::new();
-    params.insert("state".to_string(), state.to_string());
-
-    let pr_list_opt = get_list_prs_github(&headers, &params, repo_owner, repo_name).await;
-    pr_list_opt
-}
-
-async fn get_list_prs_github(headers: &HeaderMap, params: &HashMap<String, String>, repo_owner: &str, repo_name: &str) -> Option<Vec<String>> {
-    let client = get_client();
-    let base_url = github_base_url();
-    let response_result = client
-        .get(&format!(
-            "{}/repos/{}/{}/pulls",
-            &base_url, repo_owner, repo_name
-        ))
-        .headers(headers.to_owned())
-        .query(params)
-        .send()
-        .await;
-
-    if response_result.is_err() {
-        let e = response_result.expect_err("No error in sending request");
-        log::error!("[get_list_prs_github] Failed to send the request: {:?}", e);
-        return None;
-    }
-
-    let response = response_result.expect("Uncaught error in parsing response");
-    if !response.status().is_success() {
-        log::error!(
-            "[get_list_prs_github] Request failed with status: {:?}",
-            response.status()
-        );
+    params.insert("state", state_param.as_str());
+    params.insert("per_page", "100");
+    let pages_opt = get_api_paginated(&url, access_token, Some(params)).await;
+    if pages_opt.is_none() {
+        log::error!("[list_prs_github] Unable to get pull requests for {}/{}", repo_owner, repo_name);
         return None;
     }
-
-    let parse_result = response.json::<Value>().await;
-    if parse_result.is_err() {
-        let e = parse_result.expect_err("No error in parsing");
-        log::error!(
-            "[get_list_prs_github] Failed to parse JSON: {:?}",
-            e
-        );
-        return None;
+    let mut pr_list = Vec::<PrSummary>::new();
+    for page in pages_opt.expect("Empty pages_opt") {
+        let prs_opt = page.as_array();
+        if prs_opt.is_none() {
+            log::error!("[list_prs_github] Pull request page is not a list: {:?}", &page);
+            return None;
+        }
+        for pr in prs_opt.expect("Empty prs_opt") {
+            pr_list.push(PrSummary {
+                number: pr["number"].to_string(),
+                title: pr["title"].as_str().unwrap_or_default().to_string(),
+                draft: pr["draft"].as_bool().unwrap_or(false),
+                base_ref: pr["base"]["ref"].as_str().unwrap_or_default().to_string(),
+                head_ref: pr["head"]["ref"].as_str().unwrap_or_default().to_string(),
+                author: pr["user"]["login"].as_str().map(str::to_string),
+            });
+        }
     }
-    let prs_data = parse_result.expect("Uncaught error in parsing PRs data");
-
-    let pr_list = prs_data.as_array()
-        .expect("Expected an array of PRs")
-        .iter()
-        .map(|pr| pr["number"].to_string())
-        .collect();
-
     Some(pr_list)
 }
 
@@ -81,13 +50,11 @@ pub async fn get_pr_info_github(repo_owner: &str, repo_name: &str, access_token:
     );
     log::debug!("[get_pr_info_github] URL: {:?}", &url);
     let client = get_client();
-    let response_result = client
+    let response_result = send_request(client
         .get(&url)
         .header("Authorization", format!("Bearer {}", access_token))
         .header("Accept", "application/json")
-        .header(USER_AGENT, "Vibinex code review app")
-        .send()
-        .await;
+        .header(USER_AGENT, "Vibinex code review app")).await;
 
     if response_result.is_err() {
         let e = response_result.expect_err("No error in getting PR response");
@@ -134,13 +101,15 @@ pub async fn get_and_store_pr_info(repo_owner: &str, repo_name: &str, access_tok
     }
 }
 
-pub async fn pr_reviewer_handles(repo_owner: &str, repo_name: &str,
-        pr_number: &str, pr_head_commit: &str,access_token: &str)
-        -> Option<Vec<String>> {
+// Latest approval of every reviewer as (login, approved commit), later change requests
+// or dismissals drop the approval
+pub async fn pr_approvals(repo_owner: &str, repo_name: &str,
+        pr_number: &str, access_token: &str)
+        -> Option<Vec<(String, String)>> {
     let response_opt = all_pr_reviews(access_token,
         repo_owner, repo_name, pr_number).await;
     if response_opt.is_none() {
-        log::error!("[pr_reviewer_handles] Unable to get reviewer handles from gh api");
+        log::error!("[pr_approvals] Unable to get reviews from gh api");
         return None;
     }
     let response = response_opt.expect("Uncaught empty pr reviewers response");
@@ -148,27 +117,34 @@ pub async fn pr_reviewer_handles(repo_owner: &str, repo_name: &str,
     if parse_result.is_err() {
 		let e = parse_result.expect_err("No error in parsing");
 		log::error!(
-			"[pr_reviewer_handles] Failed to parse JSON: {:?}",
+			"[pr_approvals] Failed to parse JSON: {:?}",
 			e
 		);
 		return None;
 	}
 	let reviewer_list_result = parse_result.expect("Uncaught error in parsing reviewers list data");
-    // Initialize a vector to store reviewer handles
-    let mut reviewer_handles = Vec::new();
+    Some(latest_approvals(reviewer_list_result))
+}
 
-    // Process the review list
-    for review in reviewer_list_result {
+// reviews are returned in chronological order
+fn latest_approvals(reviews: Vec<Value>) -> Vec<(String, String)> {
+    let mut approvals = Vec::<(String, String)>::new();
+    for review in reviews {
         let state = review["state"].as_str().unwrap_or_default();
         let commit_id = review["commit_id"].as_str().unwrap_or_default();
-        if state == "APPROVED" && commit_id == pr_head_commit {
-            // Extract reviewer login
-            if let Some(login) = review["user"]["login"].as_str() {
-                reviewer_handles.push(login.to_string());
-            }
+        let login_opt = review["user"]["login"].as_str();
+        if login_opt.is_none() {
+            continue;
+        }
+        let login = login_opt.expect("Empty login_opt");
+        if state == "APPROVED" || state == "CHANGES_REQUESTED" || state == "DISMISSED" {
+            approvals.retain(|(approver, _)| approver != login);
+        }
+        if state == "APPROVED" {
+            approvals.push((login.to_string(), commit_id.to_string()));
         }
     }
-    Some(reviewer_handles)
+    approvals
 }
 
 async fn all_pr_reviews(access_token: &str,
@@ -180,14 +156,12 @@ async fn all_pr_reviews(access_token: &str,
     }
     let headers = headers_opt.expect("Headers should be present");
     let client = get_client();
-    let response_result = client
+    let response_result = send_request(client
         .get(&format!(
-            "https://api.github.com/repos/{}/{}/pulls/{}/reviews",
-            repo_owner, repo_name, pr_number
+            "{}/repos/{}/{}/pulls/{}/reviews",
+            github_base_url(), repo_owner, repo_name, pr_number
         ))
-        .headers(headers)
-        .send()
-        .await;
+        .headers(headers)).await;
 
     if response_result.is_err() {
 		let e = response_result.expect_err("No error in sending request");
@@ -204,4 +178,79 @@ async fn all_pr_reviews(access_token: &str,
         return None;
     }
     return Some(response);
-}
\ No newline at end of file
+}
+pub async fn open_review_requests_count(repo_owner: &str, login: &str, access_token: &str) -> Option<u64> {
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[open_review_requests_count] Unable to prepare auth headers for: {}", login);
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let query = format!("is:pr is:open review-requested:{} user:{}", login, repo_owner);
+    let client = get_client();
+    let response_res = send_request(client
+        .get(format!("{}/search/issues", github_base_url()))
+        .headers(headers)
+        .query(&[("q", query.as_str()), ("per_page", "1")])).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[open_review_requests_count] Failed to send the request: {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[open_review_requests_count] Request failed with status: {:?}", response.status());
+        return None;
+    }
+    let parse_res = response.json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[open_review_requests_count] Failed to parse JSON: {:?}", e);
+        return None;
+    }
+    let response_json = parse_res.expect("Uncaught error in parse_res");
+    response_json["total_count"].as_u64()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn review(login: &str, state: &str, commit_id: &str) -> Value {
+        json!({"user": {"login": login}, "state": state, "commit_id": commit_id})
+    }
+
+    #[test]
+    fn latest_approval_per_login_is_kept() {
+        let reviews = vec![
+            review("alice", "APPROVED", "c1"),
+            review("bob", "APPROVED", "c1"),
+            review("alice", "COMMENTED", "c2"),
+            review("alice", "APPROVED", "c3"),
+        ];
+        assert_eq!(latest_approvals(reviews), vec![
+            ("bob".to_string(), "c1".to_string()),
+            ("alice".to_string(), "c3".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn change_requests_and_dismissals_drop_approvals() {
+        let reviews = vec![
+            review("alice", "APPROVED", "c1"),
+            review("bob", "APPROVED", "c1"),
+            review("alice", "CHANGES_REQUESTED", "c2"),
+            review("bob", "DISMISSED", "c1"),
+            review("carol", "APPROVED", "c2"),
+        ];
+        assert_eq!(latest_approvals(reviews), vec![("carol".to_string(), "c2".to_string())]);
+    }
+
+    #[test]
+    fn reviews_without_login_are_skipped() {
+        let reviews = vec![json!({"user": null, "state": "APPROVED", "commit_id": "c1"})];
+        assert!(latest_approvals(reviews).is_empty());
+    }
+}
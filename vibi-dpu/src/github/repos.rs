@@ -1,7 +1,7 @@
 use serde_json::json;
 use serde_json::Value;
 
-use super::config::{get_api_paginated, github_base_url};
+use super::config::{get_api_paginated, github_base_url, github_graphql_url};
 use crate::db::repo::save_repo_to_db;
 use crate::utils::repo::Repository;
 use crate::utils::user::ProviderEnum;
@@ -84,7 +84,7 @@ pub async fn get_user_github_repos_using_graphql_api(
         });
 
         let graphql_request = client
-            .post("https://api.github.com/graphql")
+            .post(github_graphql_url())
             .header("Authorization", format!("Bearer {access_token}"))
             .header("Content-Type", "application/json")
             .header("User-Agent", "vibi-dpu")
@@ -0,0 +1,25 @@
+use super::config::{get_api_paginated, github_base_url};
+
+pub async fn get_team_members(org: &str, team_slug: &str, access_token: &str) -> Option<Vec<String>> {
+    let members_url = format!("{}/orgs/{}/teams/{}/members", github_base_url(), org, team_slug);
+    let pages_opt = get_api_paginated(&members_url, access_token, None).await;
+    if pages_opt.is_none() {
+        log::error!("[get_team_members] Unable to get members of team {}/{}", org, team_slug);
+        return None;
+    }
+    let pages = pages_opt.expect("Empty pages_opt");
+    let mut members = Vec::<String>::new();
+    for page in pages {
+        let members_opt = page.as_array();
+        if members_opt.is_none() {
+            log::error!("[get_team_members] Unexpected members response: {:?}", &page);
+            continue;
+        }
+        for member in members_opt.expect("Empty members_opt") {
+            if let Some(login) = member["login"].as_str() {
+                members.push(login.to_string());
+            }
+        }
+    }
+    Some(members)
+}
\ No newline at end of file
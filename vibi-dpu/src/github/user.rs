@@ -1,8 +1,39 @@
 use serde_json::{json, Value};
 
-use crate::utils::{review::Review, hunk::BlameItem, reqwest_client::get_client};
+use crate::utils::{review::Review, hunk::BlameItem, reqwest_client::{get_client, send_request}};
 
-use super::config::{prepare_headers, github_base_url};
+use super::config::{prepare_headers, github_base_url, github_graphql_url};
+
+// Login GitHub linked to the commit's author email, None for emails not on any account
+pub async fn commit_author_login(repo_owner: &str, repo_name: &str, commit: &str, access_token: &str) -> Option<String> {
+    let url = format!("{}/repos/{}/{}/commits/{}", github_base_url(), repo_owner, repo_name, commit);
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[commit_author_login] Unable to prepare headers for commit {}", commit);
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let client = get_client();
+    let response_res = send_request(client.get(&url).headers(headers)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("Empty error in response_res");
+        log::error!("[commit_author_login] Unable to get commit {}, error: {:?}", commit, e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[commit_author_login] Unexpected status {} for {}", response.status(), &url);
+        return None;
+    }
+    let json_res = response.json::<Value>().await;
+    if json_res.is_err() {
+        let e = json_res.expect_err("Empty error in json_res");
+        log::error!("[commit_author_login] Unable to deserialize commit {}, error: {:?}", commit, e);
+        return None;
+    }
+    let json = json_res.expect("Uncaught error in json_res");
+    json["author"]["login"].as_str().map(|login| login.to_string())
+}
 
 pub async fn get_blame_user(blame: &BlameItem, review: &Review, access_token: &str) -> Option<String> {
     let body = prepare_body(blame, review);
@@ -12,9 +43,9 @@ pub async fn get_blame_user(blame: &BlameItem, review: &Review, access_token: &s
         return None;
     }
     let headers = headers_opt.expect("Empty headers_opt");
-    let url = format!("{}/graphql", github_base_url());
+    let url = github_graphql_url();
     let client = get_client();
-    let response_res = client.post(url).headers(headers).json(&body).send().await;
+    let response_res = send_request(client.post(url).headers(headers).json(&body)).await;
     if response_res.is_err() {
         let e = response_res.expect_err("Empty error in response_res");
         log::error!("[get_blame_user] Unable to get blame user for blame: {:?}, error: {:?}", blame, e);
@@ -96,4 +127,31 @@ fn prepare_body(blame: &BlameItem, review: &Review) -> Value {
         "query": query
     });
     return body;
-}
\ No newline at end of file
+}
+// None when GitHub can't tell us, so callers can decide to keep the user
+pub async fn is_repo_collaborator(repo_owner: &str, repo_name: &str, login: &str, access_token: &str) -> Option<bool> {
+    let url = format!("{}/repos/{}/{}/collaborators/{}", github_base_url(), repo_owner, repo_name, login);
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[is_repo_collaborator] Unable to prepare headers for {}", login);
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let client = get_client();
+    let response_res = send_request(client.get(&url).headers(headers)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("Empty error in response_res");
+        log::error!("[is_repo_collaborator] Unable to check collaborator {}, error: {:?}", login, e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    let status = response.status();
+    if status == reqwest::StatusCode::NO_CONTENT {
+        return Some(true);
+    }
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Some(false);
+    }
+    log::error!("[is_repo_collaborator] Unexpected status {} for {}", status, &url);
+    None
+}
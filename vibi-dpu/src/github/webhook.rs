@@ -1,13 +1,15 @@
-use std::env;
 use std::collections::HashMap;
 
 use reqwest::{Response, Error};
 use serde_json::{json, Value};
 
-use crate::{db::webhook::save_webhook_to_db, utils::github_webhook::Webhook, github::config::{github_base_url, get_api_paginated}};
-use crate::utils::reqwest_client::get_client;
+use crate::{core::webhooks::DesiredHook, db::webhook::{delete_webhook_from_db, save_webhook_to_db}, utils::github_webhook::Webhook, github::config::{github_base_url, get_api_paginated}};
+use crate::utils::reqwest_client::{get_client, send_request};
+use crate::utils::user::ProviderEnum;
 use super::config::prepare_headers;
 
+pub const WEBHOOK_CALLBACK_PATH: &str = "/api/github/callbacks/webhook";
+pub const WEBHOOK_EVENTS: [&str; 3] = ["pull_request", "pull_request_review", "push"];
 
 pub async fn get_webhooks_in_repo(repo_owner: &str, repo_name: &str, access_token: &str) -> Option<Vec<Webhook>> {
     let url = format!("{}/repos/{}/{}/hooks", github_base_url(), repo_owner, repo_name);
@@ -33,7 +35,6 @@ fn deserialize_webhooks(webhook_val: Vec<Value>) -> Vec<Webhook> {
         let webhook_page_json = webhook_json_opt.expect("Empty repo_json_opt").to_owned();
         for webhook_json in webhook_page_json {
             let webhook = deserialize_webhook_object(&webhook_json);
-            save_webhook_to_db(&webhook);
             all_webhooks.push(webhook);
         }
     }
@@ -41,7 +42,7 @@ fn deserialize_webhooks(webhook_val: Vec<Value>) -> Vec<Webhook> {
 }
 
 fn deserialize_webhook_object(webhook_json: &Value) -> Webhook {
-    let active = matches!(webhook_json["active"].to_string().trim_matches('"'), "true" | "false");
+    let active = webhook_json["active"].as_bool().unwrap_or(false);
     let webhook = Webhook::new(
         webhook_json["id"].to_string(),
         active,
@@ -59,7 +60,7 @@ fn deserialize_webhook_object(webhook_json: &Value) -> Webhook {
     return webhook;
 }
 
-pub async fn add_webhook(repo_owner: &str, repo_name: &str, access_token: &str) {
+pub async fn add_webhook(repo_owner: &str, repo_name: &str, access_token: &str, desired: &DesiredHook) {
     let url = format!("{}/repos/{}/{}/hooks", github_base_url(), repo_owner, repo_name);
 
     let headers_map_opt = prepare_headers(&access_token);
@@ -67,24 +68,77 @@ pub async fn add_webhook(repo_owner: &str, repo_name: &str, access_token: &str)
         return;
     }
     let headers_map = headers_map_opt.expect("Empty headers_map_opt");
-    let callback_url = format!("{}/api/github/callbacks/webhook", 
-        env::var("SERVER_URL").expect("SERVER_URL must be set"));
-    let payload = json!({
-        "name": "web", 
-        "events": ["pull_request", "pull_request_review"],
-        "config": { "url": callback_url, "content_type":"json", "insecure_ssl":"0"},
-        "active": true,
-    });
-    let response = get_client()
+    let mut payload = webhook_payload(desired);
+    payload["name"] = json!("web");
+    let response = send_request(get_client()
         .post(&url)
         .headers(headers_map)
-        .json(&payload)
-        .send()
-        .await;
-    process_add_webhook_response(response).await;
+        .json(&payload)).await;
+    process_add_webhook_response(repo_owner, repo_name, response).await;
+}
+
+pub async fn update_webhook(repo_owner: &str, repo_name: &str, hook_id: &str, access_token: &str, desired: &DesiredHook) {
+    let url = format!("{}/repos/{}/{}/hooks/{}", github_base_url(), repo_owner, repo_name, hook_id);
+    let headers_map_opt = prepare_headers(access_token);
+    if headers_map_opt.is_none() {
+        return;
+    }
+    let headers_map = headers_map_opt.expect("Empty headers_map_opt");
+    let response = send_request(get_client()
+        .patch(&url)
+        .headers(headers_map)
+        .json(&webhook_payload(desired))).await;
+    if response.is_err() {
+        let err = response.expect_err("No error in response");
+        log::error!("[update_webhook] Error in api call: {:?}", err);
+        return;
+    }
+    let res = response.expect("Uncaught error in response");
+    if !res.status().is_success() {
+        log::error!("[update_webhook] Failed to update webhook {}. Status code: {}, Text: {:?}",
+            hook_id, res.status(), res.text().await);
+        return;
+    }
+    log::info!("Webhook {} updated in {}/{}", hook_id, repo_owner, repo_name);
+}
+
+pub async fn delete_webhook(repo_owner: &str, repo_name: &str, hook_id: &str, access_token: &str) {
+    let url = format!("{}/repos/{}/{}/hooks/{}", github_base_url(), repo_owner, repo_name, hook_id);
+    let headers_map_opt = prepare_headers(access_token);
+    if headers_map_opt.is_none() {
+        return;
+    }
+    let headers_map = headers_map_opt.expect("Empty headers_map_opt");
+    let response = send_request(get_client()
+        .delete(&url)
+        .headers(headers_map)).await;
+    if response.is_err() {
+        let err = response.expect_err("No error in response");
+        log::error!("[delete_webhook] Error in api call: {:?}", err);
+        return;
+    }
+    let res = response.expect("Uncaught error in response");
+    if !res.status().is_success() {
+        log::error!("[delete_webhook] Failed to delete webhook {}. Status code: {}", hook_id, res.status());
+        return;
+    }
+    delete_webhook_from_db(&ProviderEnum::Github.to_string(), repo_owner, repo_name, hook_id);
+    log::info!("Webhook {} deleted from {}/{}", hook_id, repo_owner, repo_name);
+}
+
+fn webhook_payload(desired: &DesiredHook) -> Value {
+    let mut config = json!({ "url": desired.url, "content_type":"json", "insecure_ssl":"0"});
+    if let Some(secret) = &desired.secret {
+        config["secret"] = json!(secret);
+    }
+    json!({
+        "events": desired.events,
+        "config": config,
+        "active": true,
+    })
 }
 
-async fn process_add_webhook_response(response: Result<Response, Error>){
+async fn process_add_webhook_response(repo_owner: &str, repo_name: &str, response: Result<Response, Error>){
     if response.is_err() {
         let err = response.expect_err("No error in response");
         log::error!("[process_add_webhook_response] Error in api call: {:?}", err);
@@ -112,5 +166,5 @@ async fn process_add_webhook_response(response: Result<Response, Error>){
                 config_obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect::<HashMap<String, Value>>()
             }).expect("Config should be a JSON object")
     );
-    save_webhook_to_db(&webhook); 
+    save_webhook_to_db(&ProviderEnum::Github.to_string(), repo_owner, repo_name, webhook.id(), &webhook);
 }
\ No newline at end of file
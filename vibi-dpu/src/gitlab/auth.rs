@@ -0,0 +1,123 @@
+use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::db::gitlab::auth::{gitlab_auth_info, save_gitlab_auth_info_to_db};
+use crate::utils::gitlab_auth_info::GitlabAuthInfo;
+use crate::utils::gitops::set_git_remote_url;
+use crate::utils::reqwest_client::{get_client, send_request};
+use crate::utils::review::Review;
+use crate::utils::user::ProviderEnum;
+
+use super::config::gitlab_web_url;
+
+pub async fn get_access_token_from_gitlab(code: &str) -> Option<GitlabAuthInfo> {
+    let redirect_uri = format!("{}/api/gitlab/callbacks/install",
+        env::var("SERVER_URL").expect("SERVER_URL must be set"));
+    let params = [
+        ("client_id", env::var("GITLAB_CLIENT_ID").expect("GITLAB_CLIENT_ID must be set")),
+        ("client_secret", env::var("GITLAB_CLIENT_SECRET").expect("GITLAB_CLIENT_SECRET must be set")),
+        ("code", code.to_owned()),
+        ("grant_type", "authorization_code".to_owned()),
+        ("redirect_uri", redirect_uri),
+    ];
+    let auth_info_opt = request_token(&params).await;
+    if auth_info_opt.is_none() {
+        log::error!("[get_access_token_from_gitlab] Unable to exchange code for access token");
+        return None;
+    }
+    let mut auth_info = auth_info_opt.expect("Empty auth_info_opt");
+    save_gitlab_auth_info_to_db(&mut auth_info);
+    Some(auth_info)
+}
+
+// Personal access token if this is a PAT setup, else the oauth token, refreshed when expired
+pub async fn gitlab_access_token(review: &Option<Review>) -> Option<String> {
+    if let Some(pat_token) = pat_access_token() {
+        return Some(pat_token);
+    }
+    let authinfo_opt = gitlab_auth_info();
+    if authinfo_opt.is_none() {
+        log::error!("[gitlab_access_token] No gitlab auth info in db");
+        return None;
+    }
+    let authinfo = authinfo_opt.expect("Empty authinfo_opt");
+    let latest_authinfo_opt = update_access_token(&authinfo, review).await;
+    if latest_authinfo_opt.is_none() {
+        log::error!("[gitlab_access_token] Unable to update access token");
+        return None;
+    }
+    Some(latest_authinfo_opt.expect("Empty latest_authinfo_opt").access_token().to_owned())
+}
+
+pub fn pat_access_token() -> Option<String> {
+    let provider_res = env::var("PROVIDER");
+    if provider_res.is_err() || !provider_res.expect("Empty provider_res")
+        .eq_ignore_ascii_case(&ProviderEnum::Gitlab.to_string()) {
+        return None;
+    }
+    let gitlab_pat_res = env::var("GITLAB_PAT");
+    if gitlab_pat_res.is_err() {
+        log::debug!("[pat_access_token] GITLAB PAT env var must be set");
+        return None;
+    }
+    let gitlab_pat = gitlab_pat_res.expect("Empty GITLAB_PAT env var");
+    if gitlab_pat.is_empty() {
+        log::debug!("[pat_access_token] GITLAB PAT 0 length");
+        return None;
+    }
+    Some(gitlab_pat)
+}
+
+async fn update_access_token(auth_info: &GitlabAuthInfo, review_opt: &Option<Review>) -> Option<GitlabAuthInfo> {
+    let now_secs = SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards").as_secs();
+    let timestamp_opt = auth_info.timestamp();
+    if timestamp_opt.is_none() {
+        log::error!("[update_access_token] No timestamp in GitlabAuthInfo");
+        return None;
+    }
+    let expires_at = timestamp_opt.expect("Empty timestamp") + auth_info.expires_in();
+    if expires_at > now_secs {
+        return Some(auth_info.to_owned());
+    }
+    log::info!("Updating expired authentication...");
+    let params = [
+        ("client_id", env::var("GITLAB_CLIENT_ID").expect("GITLAB_CLIENT_ID must be set")),
+        ("client_secret", env::var("GITLAB_CLIENT_SECRET").expect("GITLAB_CLIENT_SECRET must be set")),
+        ("refresh_token", auth_info.refresh_token().to_owned()),
+        ("grant_type", "refresh_token".to_owned()),
+    ];
+    let new_auth_info_opt = request_token(&params).await;
+    if new_auth_info_opt.is_none() {
+        log::error!("[update_access_token] Unable to refresh gitlab access token");
+        return None;
+    }
+    let mut new_auth_info = new_auth_info_opt.expect("Empty new_auth_info_opt");
+    if let Some(review) = review_opt {
+        set_git_remote_url(review, new_auth_info.access_token(), &ProviderEnum::Gitlab.to_string());
+    }
+    save_gitlab_auth_info_to_db(&mut new_auth_info);
+    Some(new_auth_info)
+}
+
+async fn request_token(params: &[(&str, String)]) -> Option<GitlabAuthInfo> {
+    let token_url = format!("{}/oauth/token", gitlab_web_url());
+    let post_res = send_request(get_client().post(&token_url).form(params)).await;
+    if post_res.is_err() {
+        let e = post_res.expect_err("No error in post_res");
+        log::error!("[request_token] error in calling api : {:?}", e);
+        return None;
+    }
+    let response = post_res.expect("Uncaught error in post_res");
+    if !response.status().is_success() {
+        log::error!("[request_token] Failed to get access token. Status code: {}, Response content: {:?}",
+            response.status(), response.text().await);
+        return None;
+    }
+    let parse_res = response.json::<GitlabAuthInfo>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res for GitlabAuthInfo");
+        log::error!("[request_token] error deserializing GitlabAuthInfo: {:?}", e);
+        return None;
+    }
+    Some(parse_res.expect("Uncaught error in parse_res for GitlabAuthInfo"))
+}
@@ -0,0 +1,69 @@
+use serde_json::{json, Value};
+
+use crate::utils::review::Review;
+use crate::utils::reqwest_client::{get_client, send_request};
+
+use super::config::{gitlab_base_url, prepare_headers, project_id};
+
+// Returns the id of the created note
+pub async fn add_comment(comment_text: &str, review: &Review, access_token: &str) -> Option<String> {
+    let url = prepare_notes_url(review);
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[gitlab/add_comment] Unable to prepare headers, empty headers_opt");
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let response_res = send_request(get_client().post(&url)
+        .headers(headers).json(&json!({ "body": comment_text }))).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[gitlab/add_comment] Error in post request for adding note - {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[gitlab/add_comment] Unable to add note, status: {}", response.status());
+        return None;
+    }
+    let response_json_res = response.json::<Value>().await;
+    if response_json_res.is_err() {
+        let e = response_json_res.expect_err("No error in response_json_res");
+        log::error!("[gitlab/add_comment] Unable to deserialize note response: {:?}", e);
+        return None;
+    }
+    let response_json = response_json_res.expect("Uncaught error in response_json_res");
+    response_json["id"].as_u64().map(|note_id| note_id.to_string())
+}
+
+// Some(false) if the note no longer exists, None if the edit failed for any other reason
+pub async fn edit_comment(comment_id: &str, comment_text: &str, review: &Review, access_token: &str) -> Option<bool> {
+    let url = format!("{}/{}", prepare_notes_url(review), comment_id);
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[gitlab/edit_comment] Unable to prepare headers, empty headers_opt");
+        return None;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let response_res = send_request(get_client().put(&url)
+        .headers(headers).json(&json!({ "body": comment_text }))).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[gitlab/edit_comment] Error in put request for editing note - {:?}", e);
+        return None;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Some(false);
+    }
+    if !response.status().is_success() {
+        log::error!("[gitlab/edit_comment] Unable to edit note {}, status: {}", comment_id, response.status());
+        return None;
+    }
+    Some(true)
+}
+
+fn prepare_notes_url(review: &Review) -> String {
+    format!("{}/projects/{}/merge_requests/{}/notes",
+        gitlab_base_url(), project_id(review.repo_owner(), review.repo_name()), review.id())
+}
@@ -0,0 +1,8 @@
+pub mod auth;
+pub mod config;
+pub mod repos;
+pub mod prs;
+pub mod webhook;
+pub mod comment;
+pub mod reviewer;
+pub mod status;
\ No newline at end of file
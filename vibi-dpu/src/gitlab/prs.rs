@@ -0,0 +1,85 @@
+use serde_json::Value;
+
+use crate::db::prs::update_pr_info_in_db;
+use crate::utils::pr_info::PrInfo;
+use crate::utils::user::ProviderEnum;
+
+use super::config::{get_api_paginated, get_api_response, gitlab_base_url, project_id};
+
+// iids of the project's merge requests, `state` is one of opened, closed, merged or all
+pub async fn list_mrs_gitlab(repo_owner: &str, repo_name: &str, access_token: &str, state: &str) -> Option<Vec<String>> {
+    let url = format!("{}/projects/{}/merge_requests?state={}&per_page=100",
+        gitlab_base_url(), project_id(repo_owner, repo_name), state);
+    let mrs_opt = get_api_paginated(&url, access_token).await;
+    if mrs_opt.is_none() {
+        log::error!("[list_mrs_gitlab] Unable to get merge requests for {}/{}", repo_owner, repo_name);
+        return None;
+    }
+    let mr_list = mrs_opt.expect("Empty mrs_opt").iter()
+        .map(|mr| mr["iid"].to_string())
+        .collect();
+    Some(mr_list)
+}
+
+pub async fn get_mr_info_gitlab(repo_owner: &str, repo_name: &str, access_token: &str, mr_iid: &str) -> Option<PrInfo> {
+    let url = format!("{}/projects/{}/merge_requests/{}",
+        gitlab_base_url(), project_id(repo_owner, repo_name), mr_iid);
+    let response_opt = get_api_response(&url, access_token).await;
+    if response_opt.is_none() {
+        log::error!("[get_mr_info_gitlab] Unable to get merge request {} for {}/{}", mr_iid, repo_owner, repo_name);
+        return None;
+    }
+    let parse_res = response_opt.expect("Empty response_opt").json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[get_mr_info_gitlab] Error parsing MR data: {:?}", e);
+        return None;
+    }
+    let mr_data = parse_res.expect("Uncaught error in parse_res");
+    // diff_refs is empty while gitlab is still preparing the merge request
+    let pr_info = PrInfo {
+        base_head_commit: mr_data["diff_refs"]["base_sha"].as_str()?.to_string(),
+        pr_head_commit: mr_data["sha"].as_str()?.to_string(),
+        state: mr_data["state"].as_str()?.to_string(),
+        pr_branch: mr_data["source_branch"].as_str()?.to_string(),
+        author: Some(mr_data["author"]["username"].as_str()?.to_string()),
+    };
+    log::debug!("[get_mr_info_gitlab] MR info: {:?}", &pr_info);
+    Some(pr_info)
+}
+
+pub async fn get_and_store_pr_info(repo_owner: &str, repo_name: &str, access_token: &str, mr_iid: &str) -> Option<PrInfo> {
+    let pr_info_opt = get_mr_info_gitlab(repo_owner, repo_name, access_token, mr_iid).await;
+    if pr_info_opt.is_none() {
+        log::error!("[get_and_store_pr_info] No MR info available for MR: {:?} repository: {:?}/{:?}",
+            mr_iid, repo_owner, repo_name);
+        return None;
+    }
+    let pr_info = pr_info_opt.expect("Empty pr_info_opt");
+    update_pr_info_in_db(repo_owner, repo_name, &pr_info, mr_iid,
+        &ProviderEnum::Gitlab.to_string()).await;
+    Some(pr_info)
+}
+
+// Username of everyone who currently approves the MR, revoked approvals are not listed
+pub async fn mr_approvers(repo_owner: &str, repo_name: &str, mr_iid: &str, access_token: &str) -> Option<Vec<String>> {
+    let url = format!("{}/projects/{}/merge_requests/{}/approvals",
+        gitlab_base_url(), project_id(repo_owner, repo_name), mr_iid);
+    let response_opt = get_api_response(&url, access_token).await;
+    if response_opt.is_none() {
+        log::error!("[mr_approvers] Unable to get approvals of merge request {}", mr_iid);
+        return None;
+    }
+    let parse_res = response_opt.expect("Empty response_opt").json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[mr_approvers] Failed to parse JSON: {:?}", e);
+        return None;
+    }
+    let approvals_json = parse_res.expect("Uncaught error in parse_res");
+    let approvers = approvals_json["approved_by"].as_array().cloned().unwrap_or_default().iter()
+        .filter_map(|approval| approval["user"]["username"].as_str())
+        .map(|username| username.to_string())
+        .collect();
+    Some(approvers)
+}
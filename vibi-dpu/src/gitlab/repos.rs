@@ -0,0 +1,51 @@
+use serde_json::Value;
+
+use crate::db::repo::save_repo_to_db;
+use crate::utils::repo::Repository;
+use crate::utils::user::ProviderEnum;
+
+use super::config::{get_api_paginated, gitlab_base_url};
+
+// Projects where the token can add webhooks, i.e. maintainer access or above
+pub async fn get_gitlab_projects(access_token: &str) -> Option<Vec<Repository>> {
+    let projects_url = format!("{}/projects?membership=true&min_access_level=40&per_page=100",
+        gitlab_base_url());
+    let projects_opt = get_api_paginated(&projects_url, access_token).await;
+    if projects_opt.is_none() {
+        log::error!("[get_gitlab_projects] Unable to call get api and get all projects");
+        return None;
+    }
+    let mut repos = Vec::<Repository>::new();
+    for project_json in projects_opt.expect("Empty projects_opt") {
+        let repo_opt = deserialize_project(&project_json);
+        if repo_opt.is_none() {
+            log::error!("[get_gitlab_projects] Unable to deserialize project: {:?}", &project_json);
+            continue;
+        }
+        let repo = repo_opt.expect("Empty repo_opt");
+        save_repo_to_db(&repo);
+        repos.push(repo);
+    }
+    log::debug!("[get_gitlab_projects] Fetched {:?} projects from Gitlab", &repos);
+    Some(repos)
+}
+
+// Owner is the full namespace path, so projects in subgroups keep their group path
+fn deserialize_project(project_json: &Value) -> Option<Repository> {
+    let path_with_namespace = project_json["path_with_namespace"].as_str()?;
+    let (namespace, path) = path_with_namespace.rsplit_once('/')?;
+    let is_private = project_json["visibility"].as_str() != Some("public");
+    let repo = Repository::new(
+        path.to_string(),
+        project_json["id"].to_string(),
+        namespace.to_string(),
+        is_private,
+        project_json["ssh_url_to_repo"].as_str()?.to_string(),
+        None,
+        None,
+        namespace.to_string(),
+        None,
+        ProviderEnum::Gitlab.to_string(),
+    );
+    Some(repo)
+}
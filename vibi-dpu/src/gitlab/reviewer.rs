@@ -0,0 +1,83 @@
+use serde_json::{json, Value};
+
+use crate::utils::review::Review;
+use crate::utils::reqwest_client::{get_client, send_request};
+
+use super::config::{get_api_response, gitlab_base_url, prepare_headers, project_id};
+
+// Adds to the reviewers already on the MR, gitlab replaces the whole list on update
+pub async fn add_reviewers(usernames: &Vec<String>, review: &Review, access_token: &str) {
+    let mr_url = format!("{}/projects/{}/merge_requests/{}",
+        gitlab_base_url(), project_id(review.repo_owner(), review.repo_name()), review.id());
+    let reviewer_ids_opt = current_reviewer_ids(&mr_url, access_token).await;
+    if reviewer_ids_opt.is_none() {
+        log::error!("[add_reviewers] Unable to get current reviewers of MR {}", review.id());
+        return;
+    }
+    let mut reviewer_ids = reviewer_ids_opt.expect("Empty reviewer_ids_opt");
+    for username in usernames {
+        let user_id_opt = user_id(username, access_token).await;
+        if user_id_opt.is_none() {
+            log::error!("[add_reviewers] No gitlab user found for {}", username);
+            continue;
+        }
+        let user_id = user_id_opt.expect("Empty user_id_opt");
+        if !reviewer_ids.contains(&user_id) {
+            reviewer_ids.push(user_id);
+        }
+    }
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[add_reviewers] Unable to prepare headers, empty headers_opt");
+        return;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let response_res = send_request(get_client().put(&mr_url)
+        .headers(headers).json(&json!({ "reviewer_ids": reviewer_ids }))).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[add_reviewers] Unable to add reviewers: {:?}, {:?}", e, usernames);
+        return;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[add_reviewers] Unable to add reviewers {:?}, status: {}", usernames, response.status());
+        return;
+    }
+    log::debug!("[add_reviewers] Added reviewers {:?} to MR {}", usernames, review.id());
+}
+
+async fn current_reviewer_ids(mr_url: &str, access_token: &str) -> Option<Vec<u64>> {
+    let response_opt = get_api_response(mr_url, access_token).await;
+    if response_opt.is_none() {
+        log::error!("[current_reviewer_ids] Unable to get MR {}", mr_url);
+        return None;
+    }
+    let parse_res = response_opt.expect("Empty response_opt").json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[current_reviewer_ids] Failed to parse MR JSON: {:?}", e);
+        return None;
+    }
+    let mr_json = parse_res.expect("Uncaught error in parse_res");
+    let reviewer_ids = mr_json["reviewers"].as_array().cloned().unwrap_or_default().iter()
+        .filter_map(|reviewer| reviewer["id"].as_u64())
+        .collect();
+    Some(reviewer_ids)
+}
+
+async fn user_id(username: &str, access_token: &str) -> Option<u64> {
+    let url = format!("{}/users?username={}", gitlab_base_url(), username);
+    let response_opt = get_api_response(&url, access_token).await;
+    if response_opt.is_none() {
+        log::error!("[user_id] Unable to look up user {}", username);
+        return None;
+    }
+    let parse_res = response_opt.expect("Empty response_opt").json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[user_id] Failed to parse users JSON: {:?}", e);
+        return None;
+    }
+    parse_res.expect("Uncaught error in parse_res")[0]["id"].as_u64()
+}
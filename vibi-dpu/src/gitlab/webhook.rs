@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::env;
+
+use serde_json::{json, Value};
+
+use crate::db::webhook::save_webhook_to_db;
+use crate::utils::github_webhook::Webhook;
+use crate::utils::reqwest_client::{get_client, send_request};
+use crate::utils::user::ProviderEnum;
+
+use super::config::{get_api_paginated, gitlab_base_url, prepare_headers, project_id};
+
+const WEBHOOK_EVENTS: [&str; 2] = ["merge_requests_events", "push_events"];
+
+pub async fn get_webhooks_in_repo(repo_owner: &str, repo_name: &str, access_token: &str) -> Option<Vec<Webhook>> {
+    let url = format!("{}/projects/{}/hooks", gitlab_base_url(), project_id(repo_owner, repo_name));
+    log::debug!("[get_webhooks_in_repo] Getting webhooks from {}", url);
+    let hooks_opt = get_api_paginated(&url, access_token).await;
+    if hooks_opt.is_none() {
+        log::error!("[get_webhooks_in_repo] Unable to call get api and get all webhooks");
+        return None;
+    }
+    let webhooks = hooks_opt.expect("Empty hooks_opt").iter()
+        .map(deserialize_webhook_object)
+        .collect();
+    Some(webhooks)
+}
+
+pub async fn add_webhook(repo_owner: &str, repo_name: &str, access_token: &str) {
+    let url = format!("{}/projects/{}/hooks", gitlab_base_url(), project_id(repo_owner, repo_name));
+    let headers_opt = prepare_headers(access_token);
+    if headers_opt.is_none() {
+        log::error!("[add_webhook] Unable to prepare headers for {}/{}", repo_owner, repo_name);
+        return;
+    }
+    let headers = headers_opt.expect("Empty headers_opt");
+    let callback_url = format!("{}/api/gitlab/callbacks/webhook",
+        env::var("SERVER_URL").expect("SERVER_URL must be set"));
+    let payload = json!({
+        "url": callback_url,
+        "merge_requests_events": true,
+        "push_events": false,
+        "enable_ssl_verification": true,
+    });
+    let response_res = send_request(get_client().post(&url).headers(headers).json(&payload)).await;
+    if response_res.is_err() {
+        let e = response_res.expect_err("No error in response_res");
+        log::error!("[add_webhook] Error in api call: {:?}", e);
+        return;
+    }
+    let response = response_res.expect("Uncaught error in response_res");
+    if !response.status().is_success() {
+        log::error!("[add_webhook] Failed to add webhook. Status code: {}, Text: {:?}",
+            response.status(), response.text().await);
+        return;
+    }
+    let parse_res = response.json::<Value>().await;
+    if parse_res.is_err() {
+        let e = parse_res.expect_err("No error in parse_res");
+        log::error!("[add_webhook] Unable to deserialize webhook response: {:?}", e);
+        return;
+    }
+    let webhook = deserialize_webhook_object(&parse_res.expect("Uncaught error in parse_res"));
+    log::info!("Webhook added: {:?}", webhook.url());
+    save_webhook_to_db(&ProviderEnum::Gitlab.to_string(), repo_owner, repo_name, webhook.id(), &webhook);
+}
+
+// Gitlab hooks have one flag per event instead of an event list
+fn deserialize_webhook_object(webhook_json: &Value) -> Webhook {
+    let events = WEBHOOK_EVENTS.iter()
+        .filter(|event| webhook_json[**event].as_bool().unwrap_or(false))
+        .map(|event| event.to_string())
+        .collect();
+    let webhook = Webhook::new(
+        webhook_json["id"].to_string(),
+        webhook_json["alert_status"].as_str().is_none_or(|status| status != "disabled"),
+        webhook_json["created_at"].to_string().replace('"', ""),
+        events,
+        String::new(),
+        webhook_json["url"].to_string().replace('"', ""),
+        HashMap::new(),
+    );
+    webhook
+}
@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::env;
+use std::sync::Mutex;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
@@ -6,13 +8,20 @@ use chrono::DateTime;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::utils::rate_limit::{rate_limit_status, RateLimitState};
 use crate::utils::reqwest_client::get_client;
 
+// Latest token refresh error per provider, cleared once a refresh succeeds
+static AUTH_FAILURES: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
 #[derive(Debug, Serialize, Default, Deserialize, Clone)]
 struct HealthStatus {
     status: String,
     timestamp: String,
     topic: String,
+    // Last seen API rate limits, to tell a throttled dpu apart from a stuck one
+    rate_limits: Vec<RateLimitState>,
+    auth_failures: HashMap<String, String>,
 }
 
 pub async fn send_status_start() {
@@ -27,6 +36,38 @@ pub async fn send_status_success() {
     send_status("SUCCESS").await;
 }
 
+pub fn record_auth_failure(provider: &str, error: &str) {
+    let failures_res = AUTH_FAILURES.lock();
+    if failures_res.is_err() {
+        log::error!("[record_auth_failure] Unable to lock auth failures");
+        return;
+    }
+    let mut failures = failures_res.expect("Uncaught error in failures_res");
+    failures.get_or_insert_with(HashMap::new).insert(provider.to_string(), error.to_string());
+}
+
+pub fn clear_auth_failure(provider: &str) {
+    let failures_res = AUTH_FAILURES.lock();
+    if failures_res.is_err() {
+        log::error!("[clear_auth_failure] Unable to lock auth failures");
+        return;
+    }
+    let mut failures = failures_res.expect("Uncaught error in failures_res");
+    if let Some(failures_map) = failures.as_mut() {
+        failures_map.remove(provider);
+    }
+}
+
+fn auth_failures() -> HashMap<String, String> {
+    let failures_res = AUTH_FAILURES.lock();
+    if failures_res.is_err() {
+        log::error!("[auth_failures] Unable to lock auth failures");
+        return HashMap::new();
+    }
+    let failures = failures_res.expect("Uncaught error in failures_res");
+    failures.clone().unwrap_or_default()
+}
+
 async fn send_status(status: &str) {
     let topic_id = env::var("INSTALL_ID")
 		.expect("INSTALL_ID must be set");
@@ -47,7 +88,9 @@ async fn send_status(status: &str) {
     let body = HealthStatus {
         status: status.to_string(),
         timestamp: formatted_timestamp,
-        topic: topic_id
+        topic: topic_id,
+        rate_limits: rate_limit_status(),
+        auth_failures: auth_failures(),
     };
     let post_res = client
 	  .post(&status_url)
@@ -63,4 +106,17 @@ async fn send_status(status: &str) {
             e, &status_url);
 		return;
 	}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auth_failures_are_kept_until_cleared() {
+        record_auth_failure("test-provider", "refresh failed");
+        assert_eq!(auth_failures().get("test-provider"), Some(&"refresh failed".to_string()));
+        clear_auth_failure("test-provider");
+        assert!(!auth_failures().contains_key("test-provider"));
+    }
 }
\ No newline at end of file
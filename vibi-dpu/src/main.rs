@@ -4,10 +4,14 @@ mod db;
 mod core;
 mod bitbucket;
 mod github;
+mod gitlab;
+mod gitea;
+mod bitbucket_server;
 mod utils;
 mod logger;
 mod health;
-use github::auth::app_access_token;
+use db::github::auth::get_github_installations_from_db;
+use github::auth::installation_access_token;
 use health::status::send_status_start;
 use tokio::task;
 use crate::{core::github::setup::process_repos, utils::user::ProviderEnum};
@@ -46,9 +50,26 @@ async fn main() {
 			}
 		}
 	}
+	let gitlab_pat_opt = gitlab::auth::pat_access_token();
+	if let Some(gitlab_pat) = gitlab_pat_opt {
+		is_pat = true;
+		core::gitlab::setup::setup_self_host_user_repos_gitlab(&gitlab_pat).await;
+	}
+	let is_gitea = env::var("PROVIDER").is_ok_and(|provider| provider.eq_ignore_ascii_case("GITEA"));
+	if let Some(gitea_token) = gitea::auth::gitea_access_token().filter(|_| is_gitea) {
+		is_pat = true;
+		core::gitea::setup::setup_self_host_user_repos_gitea(&gitea_token).await;
+	}
+	let is_bitbucket_server = env::var("PROVIDER")
+		.is_ok_and(|provider| provider.eq_ignore_ascii_case(&ProviderEnum::BitbucketServer.to_string()));
+	if let Some(server_token) = bitbucket_server::auth::bitbucket_server_access_token().filter(|_| is_bitbucket_server) {
+		is_pat = true;
+		core::bitbucket_server::setup::setup_self_host_user_repos_bitbucket_server(&server_token).await;
+	}
 	if !is_pat {
 		load_auth_from_previous_installation().await;
 	}
+	core::webhooks::spawn_webhook_reconciler();
 	log::debug!("[main] env vars = {}, {}", &gcp_credentials, &topic_name);
 	pubsub::listener::listen_messages(
 		&gcp_credentials, 
@@ -57,8 +78,11 @@ async fn main() {
 }
 
 async fn load_auth_from_previous_installation() {
-	if let Some(access_token) = app_access_token(&None).await {
-		log::info!("Using Stored Auth...");
-		process_repos(&access_token, &ProviderEnum::Github.to_string()).await;
+	for auth_info in get_github_installations_from_db() {
+		let installation_id = auth_info.installation_id().to_owned().unwrap_or_default();
+		if let Some(access_token) = installation_access_token(auth_info).await {
+			log::info!("Using Stored Auth for installation {}...", &installation_id);
+			process_repos(&access_token, &ProviderEnum::Github.to_string(), &installation_id).await;
+		}
 	}
 }
\ No newline at end of file
@@ -1,8 +1,10 @@
+use crate::core::ownership::process_ownership_query;
 use crate::core::trigger::process_trigger;
-use crate::{core::bitbucket::setup::handle_install_bitbucket, utils::user::ProviderEnum};
-use crate::core::github::setup::{handle_install_github, process_pat_repos};
+use crate::utils::user::ProviderEnum;
+use crate::core::provider::repo_provider;
 use crate::core::review::process_review;
-use crate::db::prs::{bitbucket_process_and_update_pr_if_different, github_process_and_update_pr_if_different};
+use crate::core::uninstall::process_uninstall;
+use crate::core::webhooks::reconcile_all_webhooks;
 use futures_util::StreamExt;
 use google_cloud_auth::credentials::CredentialsFile;
 use google_cloud_default::WithAuthExt;
@@ -57,9 +59,28 @@ async fn process_message(attributes: &HashMap<String, String>, data_bytes: &Vec<
 		}
 		"PATSetup" => {
 			log::info!("Setting up repositories...");
-			process_pat_repos(&data_bytes).await;
+			// github was the only PAT provider before PROVIDER was introduced
+			let provider = std::env::var("PROVIDER").unwrap_or(ProviderEnum::Github.to_string());
+			if let Some(repo_provider) = repo_provider(&provider) {
+				repo_provider.process_pat_repos(data_bytes).await;
+			}
 			log::info!("Processed repos successfully");
 		}
+		"reconcile_webhooks" => {
+			log::info!("Reconciling webhooks...");
+			reconcile_all_webhooks().await;
+			log::info!("Webhooks reconciled!");
+		}
+		"uninstall" => {
+			log::info!("Processing uninstall...");
+			process_uninstall(data_bytes).await;
+			log::info!("Uninstall processed!");
+		}
+		"ownership_query" => {
+			log::info!("Processing ownership query...");
+			process_ownership_query(data_bytes).await;
+			log::info!("Ownership query processed!");
+		}
 		_ => {
 			log::error!("[process_message] Message type not found for message : {:?}", attributes);
 		}
@@ -74,20 +95,18 @@ async fn process_install_callback(data_bytes: &[u8]) {
 		return;
 	}
 	let data = msg_data_res.expect("msg_data not found");
-	if data.repository_provider == ProviderEnum::Github.to_string().to_lowercase() {
-		let code_async = data.installation_code.clone();
-		task::spawn(async move {
-			handle_install_github(&code_async).await;
-			log::info!("Installation Completed!");
-		});
-	}
-	if data.repository_provider == ProviderEnum::Bitbucket.to_string().to_lowercase() {
-		let code_async = data.installation_code.clone();
-		task::spawn(async move {
-			handle_install_bitbucket(&code_async).await;
-			log::info!("Installation Completed!");
-		});
+	let provider_str = data.repository_provider.to_lowercase();
+	let provider_opt = repo_provider(&provider_str);
+	if provider_opt.is_none() {
+		log::error!("[process_install_callback] Unsupported repo provider: {}", &provider_str);
+		return;
 	}
+	let provider = provider_opt.expect("Empty provider_opt");
+	let code_async = data.installation_code.clone();
+	task::spawn(async move {
+		provider.handle_install(&code_async).await;
+		log::info!("Installation Completed!");
+	});
 }
 
 pub async fn get_pubsub_client_config(keypath: &str) -> ClientConfig {
@@ -184,41 +203,10 @@ pub fn deserialized_data(message_data: &Vec<u8>) -> Option<Value> {
 
 async fn process_and_update_pr_if_different(deserialised_msg_data: &Value) -> bool {
 	log::debug!("[process_webhook_callback] {}", deserialised_msg_data);
-	let repo_provider = deserialised_msg_data["repositoryProvider"].to_string().trim_matches('"').to_string();
-	let mut is_reviewable = false;
-	if repo_provider == ProviderEnum::Github.to_string().to_lowercase() {
-		let repo_owner = deserialised_msg_data["eventPayload"]["repository"]["owner"]["login"].to_string().trim_matches('"').to_string();
-		let repo_name = deserialised_msg_data["eventPayload"]["repository"]["name"].to_string().trim_matches('"').to_string();
-		let pr_number = deserialised_msg_data["eventPayload"]["pull_request"]["number"].to_string().trim_matches('"').to_string();
-		let event_type = deserialised_msg_data["eventType"].to_string().trim_matches('"').to_string();
-
-		log::debug!("[process_webhook_callback] {}, {}, {}, {}", event_type, repo_owner, repo_name, pr_number);
-		if event_type == "pull_request_review" {
-			log::info!("Parsed Github PR review event...");
-			is_reviewable = github_process_and_update_pr_if_different(&deserialised_msg_data["eventPayload"], &deserialised_msg_data["repoConfig"], &repo_owner, &repo_name, &pr_number, &repo_provider).await;
-		}
-		if event_type == "pull_request" {
-			log::info!("Parsed Github PR opened event...");
-			is_reviewable = github_process_and_update_pr_if_different(&deserialised_msg_data["eventPayload"], &deserialised_msg_data["repoConfig"], &repo_owner, &repo_name, &pr_number, &repo_provider).await;
-		}
+	let provider = deserialised_msg_data["repositoryProvider"].to_string().trim_matches('"').to_lowercase();
+	let repo_provider_opt = repo_provider(&provider);
+	if repo_provider_opt.is_none() {
+		return false;
 	}
-	if repo_provider == ProviderEnum::Bitbucket.to_string().to_lowercase() {
-		let workspace_slug = deserialised_msg_data["eventPayload"]["repository"]["workspace"]["slug"].to_string().trim_matches('"').to_string();
-		let repo_slug = deserialised_msg_data["eventPayload"]["repository"]["name"].to_string().trim_matches('"').to_string();
-		let pr_number = deserialised_msg_data["eventPayload"]["pullrequest"]["id"].to_string().trim_matches('"').to_string();
-		let event_type = deserialised_msg_data["eventType"].to_string().trim_matches('"').to_string();
-		let if_process_pr = bitbucket_process_and_update_pr_if_different(&deserialised_msg_data["eventPayload"],&workspace_slug,&repo_slug,&pr_number,&repo_provider,
-		)
-		.await;
-
-		if event_type == "pullrequest:approved" {
-			is_reviewable = false;
-			todo!("Process approved event");
-		};
-		if if_process_pr && (event_type == "pullrequest:created" || event_type == "pullrequest:updated") {
-			is_reviewable = true;
-		};
-	};
-	return is_reviewable;
-
+	return repo_provider_opt.expect("Empty repo_provider_opt").process_webhook(deserialised_msg_data).await;
 }
\ No newline at end of file
@@ -1,29 +1,284 @@
-use crate::{core::utils::get_handles_from_server, db::aliases::get_handles_from_db};
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{core::{provider::repo_provider, utils::get_handles_from_server}, db::aliases::{get_handles_from_db, get_resolved_alias_from_db, save_resolved_alias_to_db}};
+use crate::utils::gitops::latest_commit_by_author;
+use crate::utils::user::ProviderEnum;
 
 use super::review::Review;
 
+// Aliases no account could be found for are looked up again after this long
+const UNRESOLVED_RETRY_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResolvedAlias {
+    handles: Vec<String>,
+    resolved_at: u64,
+}
+
+impl ResolvedAlias {
+    pub fn new(handles: Vec<String>, resolved_at: u64) -> Self {
+        Self { handles, resolved_at }
+    }
+
+    pub fn handles(&self) -> &Vec<String> {
+        &self.handles
+    }
+
+    pub fn resolved_at(&self) -> u64 {
+        self.resolved_at
+    }
+}
 
+// Handles mapped on the website come first, so they override everything else.
+// Noreply emails carry the handle itself, other aliases are resolved from their commits.
 pub async fn get_login_handles(git_alias: &str, review: &Review) -> Option<Vec<String>> {
     // Get aliases from db
-    let handles_db_opt = get_handles_from_db(git_alias, &review.provider());
-    if handles_db_opt.is_none() {
-        let server_aliases_opt = get_handles_from_server(review).await;
-        log::debug!("[get_login_handles] server_aliases_opt = {:?}", &server_aliases_opt);
-        if server_aliases_opt.is_none() {
-            log::error!("[get_login_strings] No login strings found for git alias {} from server or db", git_alias);
-            return None;
+    let handles_db_opt = get_handles_from_db(git_alias, review.provider());
+    if handles_db_opt.is_some() {
+        log::debug!("[get_login_handles] handles_db_opt = {:?}", &handles_db_opt);
+        return handles_db_opt;
+    }
+    let server_aliases_opt = get_handles_from_server(review).await;
+    log::debug!("[get_login_handles] server_aliases_opt = {:?}", &server_aliases_opt);
+    let server_handles_opt = server_aliases_opt
+        .and_then(|server_alias_map| server_alias_map.get(git_alias).cloned());
+    if server_handles_opt.is_some() {
+        return server_handles_opt;
+    }
+    if let Some(handle) = noreply_handle(git_alias, review.provider()) {
+        log::debug!("[get_login_handles] Handle {} from noreply alias {}", &handle, git_alias);
+        return Some(vec![handle]);
+    }
+    log::debug!("[get_login_handles] No handles for git alias {} from server or db, resolving from commits", git_alias);
+    return resolve_login_handles(git_alias, review).await;
+}
+
+async fn resolve_login_handles(git_alias: &str, review: &Review) -> Option<Vec<String>> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards").as_secs();
+    if let Some(resolved_alias) = get_resolved_alias_from_db(git_alias, review.provider()) {
+        if !resolved_alias.handles().is_empty() {
+            return Some(resolved_alias.handles().to_owned());
         }
-        let server_alias_map = server_aliases_opt.expect("Empty server_aliases_opt");
-        let login_handles_opt = server_alias_map.get(git_alias);
-        if login_handles_opt.is_none() {
-            log::error!("[get_login_handles] Unable to get login handles from server_alias_map - {:?}",
-                &server_alias_map);
+        if now < resolved_alias.resolved_at() + UNRESOLVED_RETRY_SECS {
             return None;
         }
-        let login_handles = login_handles_opt.expect("Empty login_handles_opt");
-        return Some(login_handles.to_owned());
     }
-    log::debug!("[get_login_handles] handles_db_opt = {:?}", &handles_db_opt);
-    let handles = handles_db_opt.expect("Empty handles_db_opt");
-    return Some(handles);
-}
\ No newline at end of file
+    let handles: Vec<String> = commit_author_handle(git_alias, review).await.into_iter().collect();
+    log::debug!("[resolve_login_handles] Resolved git alias {} to {:?}", git_alias, &handles);
+    save_resolved_alias_to_db(git_alias, review.provider(), &ResolvedAlias::new(handles.clone(), now));
+    if handles.is_empty() {
+        log::error!("[resolve_login_handles] No login handles found for git alias {}", git_alias);
+        return None;
+    }
+    Some(handles)
+}
+
+async fn commit_author_handle(git_alias: &str, review: &Review) -> Option<String> {
+    let commit_opt = latest_commit_by_author(git_alias, review.clone_dir());
+    if commit_opt.is_none() {
+        log::debug!("[commit_author_handle] No commit by {} in {}", git_alias, review.repo_name());
+        return None;
+    }
+    let commit = commit_opt.expect("Empty commit_opt");
+    let provider_opt = repo_provider(review.provider());
+    if provider_opt.is_none() {
+        log::error!("[commit_author_handle] Unsupported repo provider {}", review.provider());
+        return None;
+    }
+    let provider = provider_opt.expect("Empty provider_opt");
+    let access_token_opt = provider.repo_access_token(review.repo_owner(), review.repo_name()).await;
+    if access_token_opt.is_none() {
+        log::error!("[commit_author_handle] No access token for {}/{}", review.repo_owner(), review.repo_name());
+        return None;
+    }
+    let access_token = access_token_opt.expect("Empty access_token_opt");
+    return provider.commit_author_handle(review.repo_owner(), review.repo_name(), &commit, &access_token).await;
+}
+
+// Handle in a provider's noreply email, e.g. 12345+login@users.noreply.github.com
+pub fn noreply_handle(git_alias: &str, provider: &str) -> Option<String> {
+    parse_noreply(git_alias, provider).map(|(_, login)| login)
+}
+
+// Groups aliases of one person: same noreply account id or login, or the same full name.
+// Single word names like "admin" or "ubuntu" are too common to go by.
+pub fn cluster_aliases(aliases: &Vec<String>, provider: &str, author_names: &HashMap<String, String>) -> Vec<Vec<String>> {
+    let mut clusters = Vec::<(HashSet<String>, Vec<String>)>::new();
+    for alias in aliases {
+        let mut cluster_keys = identity_keys(alias, provider, author_names);
+        let mut cluster_aliases = vec![alias.to_owned()];
+        let mut idx = 0;
+        while idx < clusters.len() {
+            if clusters[idx].0.is_disjoint(&cluster_keys) {
+                idx += 1;
+                continue;
+            }
+            let (keys, aliases) = clusters.remove(idx);
+            cluster_keys.extend(keys);
+            cluster_aliases.extend(aliases);
+        }
+        clusters.push((cluster_keys, cluster_aliases));
+    }
+    clusters.into_iter().map(|(_, aliases)| aliases).collect()
+}
+
+fn identity_keys(git_alias: &str, provider: &str, author_names: &HashMap<String, String>) -> HashSet<String> {
+    let mut keys = HashSet::<String>::new();
+    if let Some((id_opt, login)) = parse_noreply(git_alias, provider) {
+        keys.insert(format!("login:{}", login.to_lowercase()));
+        if let Some(id) = id_opt {
+            keys.insert(format!("id:{}", id));
+        }
+    }
+    if let Some(name) = author_names.get(git_alias) {
+        let name_words: Vec<String> = name.split_whitespace().map(|word| word.to_lowercase()).collect();
+        if name_words.len() > 1 {
+            keys.insert(format!("name:{}", name_words.join(" ")));
+        }
+    }
+    keys
+}
+
+// Account id and login in a noreply email. GitHub uses [id+]login@users.noreply.<host>,
+// GitLab [id-]login@users.noreply.<host> and Gitea login@noreply.<host>.
+// Bitbucket commits carry the user's own email, it has no per-user noreply address.
+fn parse_noreply(git_alias: &str, provider: &str) -> Option<(Option<String>, String)> {
+    let alias_split_opt = git_alias.trim().rsplit_once('@');
+    let provider_res = provider.parse::<ProviderEnum>();
+    if alias_split_opt.is_none() || provider_res.is_err() {
+        return None;
+    }
+    let (local_part, domain) = alias_split_opt.expect("Empty alias_split_opt");
+    let domain = domain.to_lowercase();
+    let (id_opt, login) = match provider_res.expect("Uncaught error in provider_res") {
+        ProviderEnum::Github if domain.starts_with("users.noreply.") => match local_part.split_once('+') {
+            Some((id, login)) => (Some(id), login),
+            None => (None, local_part),
+        },
+        ProviderEnum::Gitlab if domain.starts_with("users.noreply.") => match local_part.split_once('-') {
+            Some((id, login)) if id.chars().all(|c| c.is_ascii_digit()) => (Some(id), login),
+            _ => (None, local_part),
+        },
+        ProviderEnum::Gitea if domain.starts_with("noreply.") => (None, local_part),
+        _ => return None,
+    };
+    if login.is_empty() || id_opt.is_some_and(|id| id.is_empty() || !id.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    Some((id_opt.map(|id| id.to_string()), login.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(aliases: &[&str]) -> Vec<String> {
+        aliases.iter().map(|alias| alias.to_string()).collect()
+    }
+
+    fn sorted_clusters(mut clusters: Vec<Vec<String>>) -> Vec<Vec<String>> {
+        for cluster in clusters.iter_mut() {
+            cluster.sort();
+        }
+        clusters.sort();
+        clusters
+    }
+
+    #[test]
+    fn parses_github_noreply_with_and_without_id() {
+        assert_eq!(parse_noreply("12345+octocat@users.noreply.github.com", "github"),
+            Some((Some("12345".to_string()), "octocat".to_string())));
+        assert_eq!(parse_noreply("octocat@users.noreply.github.com", "github"),
+            Some((None, "octocat".to_string())));
+        assert_eq!(parse_noreply("abc+octocat@users.noreply.github.com", "github"), None);
+    }
+
+    #[test]
+    fn parses_gitlab_and_gitea_noreply() {
+        assert_eq!(parse_noreply("42-jane-doe@users.noreply.gitlab.com", "gitlab"),
+            Some((Some("42".to_string()), "jane-doe".to_string())));
+        assert_eq!(parse_noreply("jane-doe@users.noreply.gitlab.com", "gitlab"),
+            Some((None, "jane-doe".to_string())));
+        assert_eq!(parse_noreply("jane@noreply.gitea.example.com", "gitea"),
+            Some((None, "jane".to_string())));
+    }
+
+    #[test]
+    fn other_emails_and_providers_are_not_noreply() {
+        assert_eq!(parse_noreply("octocat@github.com", "github"), None);
+        assert_eq!(parse_noreply("octocat@users.noreply.github.com", "bitbucket"), None);
+        assert_eq!(parse_noreply("not an email", "github"), None);
+        assert_eq!(noreply_handle("1+octocat@users.noreply.github.com", "github"), Some("octocat".to_string()));
+    }
+
+    #[test]
+    fn clusters_noreply_aliases_by_account_id() {
+        let alias_vec = aliases(&["1+old-login@users.noreply.github.com", "1+new-login@users.noreply.github.com",
+            "2+someone@users.noreply.github.com"]);
+        let clusters = sorted_clusters(cluster_aliases(&alias_vec, "github", &HashMap::new()));
+        assert_eq!(clusters, vec![
+            aliases(&["1+new-login@users.noreply.github.com", "1+old-login@users.noreply.github.com"]),
+            aliases(&["2+someone@users.noreply.github.com"]),
+        ]);
+    }
+
+    #[test]
+    fn clusters_by_full_name_but_not_single_words() {
+        let alias_vec = aliases(&["jane@work.com", "jane@home.com", "root@a.com", "root@b.com"]);
+        let mut author_names = HashMap::new();
+        author_names.insert("jane@work.com".to_string(), "Jane Doe".to_string());
+        author_names.insert("jane@home.com".to_string(), "jane  doe".to_string());
+        author_names.insert("root@a.com".to_string(), "ubuntu".to_string());
+        author_names.insert("root@b.com".to_string(), "ubuntu".to_string());
+        let clusters = sorted_clusters(cluster_aliases(&alias_vec, "github", &author_names));
+        assert_eq!(clusters, vec![
+            aliases(&["jane@home.com", "jane@work.com"]),
+            aliases(&["root@a.com"]),
+            aliases(&["root@b.com"]),
+        ]);
+    }
+
+    #[test]
+    fn clusters_merge_through_shared_keys() {
+        // the first and last alias only match through the middle one
+        let alias_vec = aliases(&["7+jdoe@users.noreply.github.com", "jane@work.com", "7+jane@users.noreply.github.com"]);
+        let mut author_names = HashMap::new();
+        author_names.insert("7+jdoe@users.noreply.github.com".to_string(), "Jane Doe".to_string());
+        author_names.insert("jane@work.com".to_string(), "Jane Doe".to_string());
+        let clusters = cluster_aliases(&alias_vec, "github", &author_names);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].len(), 3);
+    }
+
+    #[test]
+    fn noreply_parsing_ignores_case_and_surrounding_space() {
+        assert_eq!(parse_noreply(" 12345+OctoCat@Users.NoReply.GitHub.com ", "github"),
+            Some((Some("12345".to_string()), "OctoCat".to_string())));
+        assert_eq!(parse_noreply("jane@noreply.gitea.example.com", "github"), None);
+        assert_eq!(parse_noreply("1+@users.noreply.github.com", "github"), None);
+        assert_eq!(parse_noreply("+octocat@users.noreply.github.com", "github"), None);
+        assert_eq!(parse_noreply("jane.doe@users.noreply.gitlab.com", "gitlab"),
+            Some((None, "jane.doe".to_string())));
+    }
+
+    #[test]
+    fn clusters_noreply_aliases_by_login_with_and_without_id() {
+        let alias_vec = aliases(&["octocat@users.noreply.github.com", "583231+OctoCat@users.noreply.github.com",
+            "octocat@github.com"]);
+        let clusters = sorted_clusters(cluster_aliases(&alias_vec, "github", &HashMap::new()));
+        assert_eq!(clusters, vec![
+            aliases(&["583231+OctoCat@users.noreply.github.com", "octocat@users.noreply.github.com"]),
+            aliases(&["octocat@github.com"]),
+        ]);
+    }
+
+    #[test]
+    fn aliases_without_keys_stay_apart() {
+        let alias_vec = aliases(&["a@example.com", "b@example.com"]);
+        let clusters = sorted_clusters(cluster_aliases(&alias_vec, "bitbucket", &HashMap::new()));
+        assert_eq!(clusters, vec![aliases(&["a@example.com"]), aliases(&["b@example.com"])]);
+    }
+}
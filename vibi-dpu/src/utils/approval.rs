@@ -0,0 +1,35 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+// Commits a reviewer approved on a PR, oldest first
+#[derive(Debug, Serialize, Default, Deserialize, Clone)]
+pub struct ApprovalRecord {
+    handles: Vec<String>,
+    commits: Vec<String>,
+}
+
+impl ApprovalRecord {
+    // Constructor
+    pub fn new(handles: Vec<String>) -> Self {
+        Self {
+            handles,
+            commits: Vec::new(),
+        }
+    }
+
+    // Public getter methods
+    pub fn handles(&self) -> &Vec<String> {
+        &self.handles
+    }
+
+    pub fn latest_commit(&self) -> Option<&String> {
+        self.commits.last()
+    }
+
+    pub fn add_commit(&mut self, commit: &str) {
+        if self.latest_commit().map(|latest| latest.as_str()) == Some(commit) {
+            return;
+        }
+        self.commits.push(commit.to_string());
+    }
+}
@@ -11,6 +11,8 @@ pub struct Webhook {
     events: Vec<String>,
     ping_url: String,
     url: String,
+    #[serde(default)]
+    secret_set: bool,
 }
 
 impl Webhook {
@@ -22,6 +24,7 @@ impl Webhook {
         events: Vec<String>,
         ping_url: String,
         url: String,
+        secret_set: bool,
     ) -> Self {
         Self {
             uuid,
@@ -30,12 +33,29 @@ impl Webhook {
             events,
             ping_url,
             url,
+            secret_set,
         }
     }
 
+    pub fn uuid(&self) -> &String {
+        &self.uuid
+    }
+
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    pub fn events(&self) -> &Vec<String> {
+        &self.events
+    }
+
     pub fn url(&self) -> &String {
         &self.url
     }
+
+    pub fn secret_set(&self) -> bool {
+        self.secret_set
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -46,6 +66,8 @@ pub struct WebhookResponse {
     created_at: String,
     events: Vec<String>,
     links: HashMap<String, HashMap<String, String>>,
+    #[serde(default)]
+    secret_set: bool,
 }
 
 impl WebhookResponse {
@@ -74,4 +96,8 @@ impl WebhookResponse {
     pub fn links(&self) -> &HashMap<String, HashMap<String, String>> {
         &self.links
     }
+
+    pub fn secret_set(&self) -> bool {
+        self.secret_set
+    }
 }
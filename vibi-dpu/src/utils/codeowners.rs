@@ -0,0 +1,242 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+// Locations checked for a CODEOWNERS file, GitHub and Bitbucket layouts
+pub const CODEOWNERS_PATHS: [&str; 4] = [
+    ".github/CODEOWNERS",
+    ".bitbucket/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+
+#[derive(Debug, Serialize, Default, Deserialize, Clone)]
+pub struct CodeownersRule {
+    pattern: String,
+    owners: Vec<String>,
+}
+
+impl CodeownersRule {
+    // Constructor
+    pub fn new(pattern: String, owners: Vec<String>) -> Self {
+        Self {
+            pattern,
+            owners,
+        }
+    }
+
+    // Public getter methods
+    pub fn owners(&self) -> &Vec<String> {
+        &self.owners
+    }
+
+    pub fn matches(&self, filepath: &str) -> bool {
+        let mut pattern = self.pattern.as_str();
+        let dir_only = pattern.ends_with('/');
+        // `docs/*` owns the files directly in docs, not the nested ones
+        let files_only = pattern.ends_with("/*");
+        pattern = pattern.trim_end_matches('/');
+        // a slash anywhere but the end anchors the pattern to the repo root
+        let anchored = pattern.contains('/');
+        pattern = pattern.trim_start_matches('/');
+        if pattern.is_empty() || pattern == "**" || (pattern == "*" && !anchored) {
+            return true;
+        }
+        let segments: Vec<&str> = filepath.trim_start_matches('/').split('/').collect();
+        let start_max = if anchored { 1 } else { segments.len() };
+        for start in 0..start_max {
+            for end in start..segments.len() {
+                // a pattern matching a directory owns everything inside it
+                let is_file = end == segments.len() - 1;
+                if (dir_only && is_file) || (files_only && !is_file) {
+                    continue;
+                }
+                let subpath = segments[start..=end].join("/");
+                if glob_match(pattern.as_bytes(), subpath.as_bytes()) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+#[derive(Debug, Serialize, Default, Deserialize, Clone)]
+pub struct Codeowners {
+    rules: Vec<CodeownersRule>,
+}
+
+impl Codeowners {
+    // Parses both GitHub style and Bitbucket style (with `[Section]` headers) CODEOWNERS files
+    pub fn parse(content: &str) -> Self {
+        let mut rules = Vec::<CodeownersRule>::new();
+        for raw_line in content.lines() {
+            let line = raw_line.split(" #").next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') || line.starts_with("^[") {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let pattern_opt = parts.next();
+            if pattern_opt.is_none() {
+                continue;
+            }
+            let pattern = pattern_opt.expect("Empty pattern_opt").to_string();
+            let owners: Vec<String> = parts.map(|owner| owner.to_string()).collect();
+            rules.push(CodeownersRule::new(pattern, owners));
+        }
+        Self { rules }
+    }
+
+    // The last matching rule wins, as on GitHub and Bitbucket
+    pub fn owners_for_path(&self, filepath: &str) -> Vec<String> {
+        for rule in self.rules.iter().rev() {
+            if rule.matches(filepath) {
+                return rule.owners().to_owned();
+            }
+        }
+        Vec::new()
+    }
+}
+
+// Teams are `@org/team` on GitHub and `@@group` or `@teams/name` on Bitbucket
+pub fn is_team_owner(owner: &str) -> bool {
+    owner.starts_with("@@") || (owner.starts_with('@') && owner.contains('/'))
+}
+
+// Login handle for `@user` style owners, None for email owners
+pub fn owner_handle(owner: &str) -> Option<String> {
+    if !owner.starts_with('@') {
+        return None;
+    }
+    let handle = owner.trim_start_matches('@');
+    if handle.is_empty() {
+        return None;
+    }
+    Some(handle.to_string())
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    if pattern.is_empty() {
+        return text.is_empty();
+    }
+    if pattern.starts_with(b"**") {
+        let rest = pattern[2..].strip_prefix(b"/").unwrap_or(&pattern[2..]);
+        if rest.is_empty() {
+            return true;
+        }
+        for idx in 0..=text.len() {
+            if (idx == 0 || text[idx - 1] == b'/') && glob_match(rest, &text[idx..]) {
+                return true;
+            }
+        }
+        return glob_match(&pattern[2..], text);
+    }
+    match pattern[0] {
+        b'*' => {
+            for idx in 0..=text.len() {
+                if glob_match(&pattern[1..], &text[idx..]) {
+                    return true;
+                }
+                if idx < text.len() && text[idx] == b'/' {
+                    break;
+                }
+            }
+            false
+        }
+        b'?' => {
+            !text.is_empty() && text[0] != b'/' && glob_match(&pattern[1..], &text[1..])
+        }
+        c => {
+            !text.is_empty() && text[0] == c && glob_match(&pattern[1..], &text[1..])
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str) -> CodeownersRule {
+        CodeownersRule::new(pattern.to_string(), vec!["@owner".to_string()])
+    }
+
+    #[test]
+    fn unanchored_patterns_match_at_any_depth() {
+        assert!(rule("*.rs").matches("main.rs"));
+        assert!(rule("*.rs").matches("src/core/main.rs"));
+        assert!(rule("build").matches("tools/build/run.sh"));
+        assert!(!rule("*.rs").matches("src/main.rsx"));
+    }
+
+    #[test]
+    fn slashes_anchor_patterns_to_the_root() {
+        assert!(rule("/src").matches("src/main.rs"));
+        assert!(rule("src/core").matches("src/core/relevance.rs"));
+        assert!(!rule("/src").matches("lib/src/main.rs"));
+        assert!(!rule("src/core").matches("lib/src/core/relevance.rs"));
+    }
+
+    #[test]
+    fn trailing_slash_matches_directories_only() {
+        assert!(rule("docs/").matches("docs/index.md"));
+        assert!(rule("docs/").matches("site/docs/index.md"));
+        assert!(!rule("docs/").matches("docs"));
+    }
+
+    #[test]
+    fn single_star_stays_in_one_directory() {
+        assert!(rule("docs/*").matches("docs/index.md"));
+        assert!(!rule("docs/*").matches("docs/api/index.md"));
+        assert!(!rule("src/*.rs").matches("src/core/main.rs"));
+        assert!(rule("?.md").matches("a.md"));
+        assert!(!rule("?.md").matches("ab.md"));
+    }
+
+    #[test]
+    fn double_star_crosses_directories() {
+        assert!(rule("**/logs").matches("logs/today.log"));
+        assert!(rule("**/logs").matches("deep/nested/logs/today.log"));
+        assert!(rule("apps/**").matches("apps/web/src/index.ts"));
+        assert!(rule("src/**/test.rs").matches("src/test.rs"));
+        assert!(rule("src/**/test.rs").matches("src/a/b/test.rs"));
+        assert!(!rule("apps/**").matches("lib/apps/web.ts"));
+    }
+
+    #[test]
+    fn catch_all_patterns_match_everything() {
+        assert!(rule("*").matches("README.md"));
+        assert!(rule("*").matches("src/main.rs"));
+        assert!(rule("**").matches("src/main.rs"));
+        assert!(rule("/").matches("src/main.rs"));
+    }
+
+    #[test]
+    fn parse_skips_comments_and_sections() {
+        let content = "# global owners\n* @org/core\n\n[Docs]\ndocs/ @writer # docs team\n^[Optional]\n*.rs @alice alice@example.com\n";
+        let codeowners = Codeowners::parse(content);
+        assert_eq!(codeowners.rules.len(), 3);
+        assert_eq!(codeowners.owners_for_path("docs/index.md"), vec!["@writer".to_string()]);
+        assert_eq!(codeowners.owners_for_path("src/main.rs"),
+            vec!["@alice".to_string(), "alice@example.com".to_string()]);
+        assert_eq!(codeowners.owners_for_path("README.md"), vec!["@org/core".to_string()]);
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let codeowners = Codeowners::parse("*.rs @alice\nsrc/core/ @bob\n");
+        assert_eq!(codeowners.owners_for_path("src/core/main.rs"), vec!["@bob".to_string()]);
+        assert_eq!(codeowners.owners_for_path("src/main.rs"), vec!["@alice".to_string()]);
+        assert!(Codeowners::parse("docs/ @writer").owners_for_path("src/main.rs").is_empty());
+    }
+
+    #[test]
+    fn team_and_user_owners() {
+        assert!(is_team_owner("@org/team"));
+        assert!(is_team_owner("@@group"));
+        assert!(!is_team_owner("@alice"));
+        assert!(!is_team_owner("alice@example.com"));
+        assert_eq!(owner_handle("@alice"), Some("alice".to_string()));
+        assert_eq!(owner_handle("alice@example.com"), None);
+        assert_eq!(owner_handle("@"), None);
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommentTemplateKind {
+    Relevance,
+    Approval,
+}
+
+impl CommentTemplateKind {
+    // Repo local template, used when the repo config doesn't have one
+    pub fn file_path(&self) -> &'static str {
+        match self {
+            CommentTemplateKind::Relevance => ".vibinex/relevance_comment.md",
+            CommentTemplateKind::Approval => ".vibinex/approval_comment.md",
+        }
+    }
+}
+
+// Replaces `{{ name }}` placeholders, unknown names are left as they are so typos show up in the comment
+pub fn render_template(template: &str, vars: &HashMap<String, String>) -> String {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end_opt = rest[start..].find("}}");
+        if end_opt.is_none() {
+            break;
+        }
+        let end = start + end_opt.expect("Empty end_opt");
+        rendered += &rest[..start];
+        let name = rest[start + 2..end].trim();
+        match vars.get(name) {
+            Some(value) => rendered += value,
+            None => rendered += &rest[start..end + 2],
+        }
+        rest = &rest[end + 2..];
+    }
+    rendered += rest;
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<String, String> {
+        let mut vars = HashMap::new();
+        vars.insert("coverage".to_string(), "50.00%".to_string());
+        vars.insert("commit".to_string(), "abc1234".to_string());
+        vars
+    }
+
+    #[test]
+    fn replaces_placeholders_with_or_without_spaces() {
+        assert_eq!(render_template("{{coverage}} at {{ commit }}", &vars()), "50.00% at abc1234");
+    }
+
+    #[test]
+    fn keeps_unknown_placeholders() {
+        assert_eq!(render_template("{{ coverag }} left", &vars()), "{{ coverag }} left");
+    }
+
+    #[test]
+    fn keeps_unclosed_placeholders() {
+        assert_eq!(render_template("{{coverage}} and {{ commit", &vars()), "50.00% and {{ commit");
+    }
+
+    #[test]
+    fn text_without_placeholders_is_unchanged() {
+        assert_eq!(render_template("no vars here }}", &vars()), "no vars here }}");
+    }
+}
@@ -95,14 +95,6 @@ impl CoverageMap {
     }
 
     // Public getter methods
-    pub fn provider(&self) -> &String {
-        &self.provider
-    }
-
-    pub fn handle_map(&self) -> &HashMap<String, Coverage> {
-        &self.handle_map
-    }
-
     pub fn coverage_total_str(&self) -> String {
         let coverage_total_str = format!("{:.2}", self.coverage_total);
         coverage_total_str
@@ -73,6 +73,13 @@ impl GithubAuthInfo {
 			log::error!("[github_auth_info/save_to_file] Unable to write to file: {:?}", err);
 		}
 	}
+
+	pub fn remove_file() {
+		log::debug!("[github_auth_info/remove_file] Removing auth info file: {}", &PATH);
+		if let Err(err) = std::fs::remove_file(PATH) {
+			log::error!("[github_auth_info/remove_file] Unable to remove file: {:?}", err);
+		}
+	}
 }
 
 static PATH: &str = "/app/config/dpu_creds.json";
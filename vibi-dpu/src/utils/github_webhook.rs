@@ -37,7 +37,23 @@ impl Webhook {
     }
 
     // Public getter methods
+    pub fn id(&self) -> &String {
+        &self.id
+    }
+
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    pub fn events(&self) -> &Vec<String> {
+        &self.events
+    }
+
     pub fn url(&self) -> &String {
         &self.url
     }
+
+    pub fn config(&self) -> &HashMap<String, serde_json::Value> {
+        &self.config
+    }
 }
\ No newline at end of file
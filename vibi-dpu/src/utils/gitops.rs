@@ -13,8 +13,51 @@ use super::hunk::BlameItem;
 use super::review::Review;
 use super::lineitem::LineItem;
 use crate::db::repo::save_repo_to_db;
+use crate::core::provider::repo_provider;
 use crate::utils::repo::Repository;
 
+// One hunk of a diff, a count of 0 is a pure insertion or deletion after the start line
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffHunk {
+	old_start: usize,
+	old_count: usize,
+	new_start: usize,
+	new_count: usize,
+}
+
+impl DiffHunk {
+	// Constructor
+	pub fn new(old_start: usize, old_count: usize, new_start: usize, new_count: usize) -> Self {
+		Self {
+			old_start,
+			old_count,
+			new_start,
+			new_count,
+		}
+	}
+
+	// Lines the hunk covers on the old side, inclusive
+	pub fn old_span(&self) -> (usize, usize) {
+		line_span(self.old_start, self.old_count)
+	}
+
+	// Lines the hunk covers on the new side, inclusive
+	pub fn new_span(&self) -> (usize, usize) {
+		line_span(self.new_start, self.new_count)
+	}
+}
+
+// An empty side touches the lines on both sides of where it happened
+fn line_span(start: usize, count: usize) -> (usize, usize) {
+	if count == 0 {
+		return (start, start + 1);
+	}
+	(start, start + count - 1)
+}
+
+// Files with more changed lines than this are left out of blame
+pub const EXCLUDED_FILE_LINES: i32 = 500;
+
 #[derive(Debug, Serialize, Default, Deserialize)]
 pub struct StatItem {
 	filepath: String,
@@ -22,6 +65,21 @@ pub struct StatItem {
 	deletions: i32,
 }
 
+impl StatItem {
+	// Public getter methods
+	pub fn filepath(&self) -> &String {
+		&self.filepath
+	}
+
+	pub fn additions(&self) -> i32 {
+		self.additions
+	}
+
+	pub fn deletions(&self) -> i32 {
+		self.deletions
+	}
+}
+
 pub fn commit_exists(commit: &str, directory: &str) -> bool {
 	let output_res = Command::new("git")
 		.arg("rev-list")
@@ -89,6 +147,38 @@ pub async fn git_pull(review: &Review, access_token: &str) {
 	};
 }
 
+// Fetches the remote and fast-forwards the checked out branch, keeps a clone current between reviews
+pub fn git_fetch(git_url: &str, directory: &str, access_token: &str, repo_provider: &str) -> bool {
+	log::debug!("[git_fetch] directory = {}", directory);
+	set_git_url(git_url, directory, access_token, repo_provider);
+	let output_res = Command::new("git")
+		.arg("fetch").arg("--prune").arg("origin")
+		.current_dir(directory)
+		.output();
+	if output_res.is_err() {
+		let e = output_res.expect_err("No error in output_res");
+		log::error!("[git_fetch] failed to execute git fetch: {:?}", e);
+		return false;
+	}
+	let output = output_res.expect("Uncaught error in output_res");
+	if !output.status.success() {
+		log::error!("[git_fetch] git fetch failed with exit code {:?} and error: {:?}",
+			output.status.code(), String::from_utf8_lossy(&output.stderr));
+		return false;
+	}
+	let merge_res = Command::new("git")
+		.arg("merge").arg("--ff-only").arg("@{u}")
+		.current_dir(directory)
+		.output();
+	match merge_res {
+		Ok(merge_output) if !merge_output.status.success() => log::warn!(
+			"[git_fetch] Unable to fast-forward {}: {:?}", directory, String::from_utf8_lossy(&merge_output.stderr)),
+		Err(e) => log::error!("[git_fetch] failed to execute git merge: {:?}", e),
+		_ => {}
+	};
+	true
+}
+
 fn set_git_url(git_url: &str, directory: &str, access_token: &str, repo_provider: &str) {
     let clone_url_opt = create_clone_url(git_url, access_token, repo_provider);
 	if clone_url_opt.is_none(){
@@ -155,7 +245,7 @@ fn process_statoutput(statstr: &str) -> Option<(Vec<StatItem>, Vec<StatItem>)>{
     let statvec = process_statitems(statstr);
     let mut excluded_files = Vec::<StatItem>::new();
     let mut filtered_files = Vec::<StatItem>::new();
-    let line_threshold = 500;
+    let line_threshold = EXCLUDED_FILE_LINES;
     for item in statvec {
         // logic for exclusion
         if (item.additions > line_threshold) || 
@@ -502,18 +592,9 @@ fn extract_timestamp(wordvec: &Vec<&str>, mut idx: usize) -> String {
 	return timestamp.to_string();
 }
 
-pub fn create_clone_url(git_url: &str, access_token: &str, repo_provider: &str) -> Option<String> {
-	log::debug!("[create_clone_url] Creating clone URL for repo provider: {}", repo_provider);
-	let mut clone_url = None;
-	if repo_provider == "github" {
-		clone_url = Some(git_url.to_string()
-			.replace("git@", format!("https://x-access-token:{access_token}@").as_str())
-			.replace("github.com:", "github.com/"));
-	} else if repo_provider == "bitbucket" {
-		clone_url = Some(git_url.to_string()
-			.replace("git@", format!("https://x-token-auth:{{{access_token}}}@").as_str())
-			.replace("bitbucket.org:", "bitbucket.org/"));
-	}
+pub fn create_clone_url(git_url: &str, access_token: &str, repo_provider_str: &str) -> Option<String> {
+	log::debug!("[create_clone_url] Creating clone URL for repo provider: {}", repo_provider_str);
+	let clone_url = repo_provider(repo_provider_str).and_then(|provider| provider.clone_url(git_url, access_token));
 	log::debug!("[create_clone_url] clone URL: {:?}", clone_url);
 	return clone_url;
 }
@@ -660,3 +741,383 @@ pub fn get_git_aliases(repo: &Repository) -> Option<Vec<String>> {
 	log::debug!("[get_git_aliases] Extracted unique git aliases: {:?}", &unique_emails);
 	return Some(unique_emails);
 }
+
+// Latest author name of every email in the history, names are used to tell which aliases are one person
+pub fn git_author_names(directory: &str) -> Option<HashMap<String, String>> {
+	let output_res = Command::new("git")
+		.arg("log").arg("--all").arg("--format=%ae%x09%an")
+		.current_dir(directory)
+		.output();
+	if output_res.is_err() {
+		let e = output_res.expect_err("No error in output_res");
+		log::error!("[git_author_names] git log failed to start: {:?}", e);
+		return None;
+	}
+	let output = output_res.expect("Uncaught error in output_res");
+	if !output.status.success() {
+		log::error!("[git_author_names] git log failed with exit code {:?} and error: {:?}",
+			output.status.code(), String::from_utf8_lossy(&output.stderr));
+		return None;
+	}
+	let mut author_names = HashMap::<String, String>::new();
+	for line in String::from_utf8_lossy(&output.stdout).lines() {
+		if let Some((email, name)) = line.split_once('\t') {
+			author_names.entry(email.trim().to_string()).or_insert(name.trim().to_string());
+		}
+	}
+	Some(author_names)
+}
+
+pub fn default_branch_head(directory: &str) -> Option<String> {
+	let output_res = Command::new("git")
+		.arg("rev-parse")
+		.arg("origin/HEAD")
+		.current_dir(directory)
+		.output();
+	if output_res.is_err() {
+		let e = output_res.expect_err("No error in output_res");
+		log::error!("[default_branch_head] git rev-parse failed to start: {:?}", e);
+		return None;
+	}
+	let output = output_res.expect("Uncaught error in output_res");
+	if !output.status.success() {
+		log::error!("[default_branch_head] git rev-parse failed with exit code {:?} and error: {:?}",
+			output.status.code(), String::from_utf8_lossy(&output.stderr));
+		return None;
+	}
+	let head_res = str::from_utf8(&output.stdout);
+	if head_res.is_err() {
+		let e = head_res.expect_err("No error in head_res");
+		log::error!("[default_branch_head] Unable to parse git rev-parse output: {:?}", e);
+		return None;
+	}
+	let head = head_res.expect("Uncaught error in head_res").trim().to_string();
+	if head.is_empty() {
+		return None;
+	}
+	Some(head)
+}
+
+// Most recent commit by a git alias, emails are matched whole so a@x.com doesn't match ba@x.com
+pub fn latest_commit_by_author(git_alias: &str, directory: &str) -> Option<String> {
+	let author_pattern = if git_alias.contains('@') { format!("<{}>", git_alias) } else { git_alias.to_string() };
+	let output_res = Command::new("git")
+		.arg("log").arg("-1").arg("--all").arg("--format=%H")
+		.arg("--fixed-strings").arg(format!("--author={}", author_pattern))
+		.current_dir(directory)
+		.output();
+	if output_res.is_err() {
+		let e = output_res.expect_err("No error in output_res");
+		log::error!("[latest_commit_by_author] git log failed to start: {:?}", e);
+		return None;
+	}
+	let output = output_res.expect("Uncaught error in output_res");
+	if !output.status.success() {
+		log::error!("[latest_commit_by_author] git log failed with exit code {:?} and error: {:?}",
+			output.status.code(), String::from_utf8_lossy(&output.stderr));
+		return None;
+	}
+	let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+	if commit.is_empty() {
+		return None;
+	}
+	Some(commit)
+}
+
+// Name of the remote default branch, e.g. main, from the clone's origin/HEAD
+pub fn default_branch_name(directory: &str) -> Option<String> {
+	let output_res = Command::new("git")
+		.arg("symbolic-ref").arg("--short").arg("refs/remotes/origin/HEAD")
+		.current_dir(directory)
+		.output();
+	if output_res.is_err() {
+		let e = output_res.expect_err("No error in output_res");
+		log::error!("[default_branch_name] git symbolic-ref failed to start: {:?}", e);
+		return None;
+	}
+	let output = output_res.expect("Uncaught error in output_res");
+	if !output.status.success() {
+		log::error!("[default_branch_name] git symbolic-ref failed with exit code {:?} and error: {:?}",
+			output.status.code(), String::from_utf8_lossy(&output.stderr));
+		return None;
+	}
+	remote_branch_name(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn remote_branch_name(symbolic_ref: &str) -> Option<String> {
+	symbolic_ref.trim().strip_prefix("origin/").map(|name| name.to_string())
+}
+
+pub fn is_ancestor_commit(ancestor: &str, commit: &str, directory: &str) -> bool {
+	let output_res = Command::new("git")
+		.arg("merge-base")
+		.arg("--is-ancestor")
+		.arg(ancestor)
+		.arg(commit)
+		.current_dir(directory)
+		.output();
+	if output_res.is_err() {
+		let e = output_res.expect_err("No error in output_res");
+		log::error!("[is_ancestor_commit] git merge-base failed to start: {:?}", e);
+		return false;
+	}
+	let output = output_res.expect("Uncaught error in output_res");
+	output.status.success()
+}
+
+// Lines changed per file per author email, from `git log --numstat` over commit_range
+pub fn get_numstat_history(commit_range: &str, directory: &str) -> Option<HashMap<String, HashMap<String, u64>>> {
+	let output_res = Command::new("git")
+		.arg("log")
+		.arg("--numstat")
+		.arg("--no-renames")
+		.arg("--no-merges")
+		.arg("--format=%H%x09%ae")
+		.arg(commit_range)
+		.current_dir(directory)
+		.output();
+	if output_res.is_err() {
+		let e = output_res.expect_err("No error in output_res");
+		log::error!("[get_numstat_history] git log command failed to start: {:?}", e);
+		return None;
+	}
+	let output = output_res.expect("Uncaught error in output_res");
+	if !output.status.success() {
+		log::error!("[get_numstat_history] git log failed with exit code {:?} and error: {:?}",
+			output.status.code(), String::from_utf8_lossy(&output.stderr));
+		return None;
+	}
+	let logstr = String::from_utf8_lossy(&output.stdout);
+	Some(parse_numstat_history(&logstr))
+}
+
+// Lines added and deleted per file and author email, from `git log --numstat --format=%H%x09%ae`
+fn parse_numstat_history(logstr: &str) -> HashMap<String, HashMap<String, u64>> {
+	let mut filemap = HashMap::<String, HashMap<String, u64>>::new();
+	let mut author = String::new();
+	for line in logstr.lines() {
+		let fields: Vec<&str> = line.split("\t").collect();
+		if fields.len() == 2 {
+			// commit header line: <sha>\t<author email>
+			author = fields[1].trim().to_string();
+			continue;
+		}
+		if fields.len() < 3 || author.is_empty() {
+			continue;
+		}
+		// binary files are reported as "-"
+		let additions = fields[0].parse::<u64>().unwrap_or(0);
+		let deletions = fields[1].parse::<u64>().unwrap_or(0);
+		let lines = additions + deletions;
+		if lines == 0 {
+			continue;
+		}
+		let authormap = filemap.entry(fields[2].to_string()).or_default();
+		*authormap.entry(author.to_owned()).or_insert(0) += lines;
+	}
+	filemap
+}
+
+pub fn get_file_at_commit(commit: &str, filepath: &str, directory: &str) -> Option<String> {
+	let object = format!("{}:{}", commit, filepath);
+	let output_res = Command::new("git")
+		.arg("show")
+		.arg(&object)
+		.current_dir(directory)
+		.output();
+	if output_res.is_err() {
+		let e = output_res.expect_err("No error in output_res");
+		log::error!("[get_file_at_commit] git show command failed to start: {:?}", e);
+		return None;
+	}
+	let output = output_res.expect("Uncaught error in output_res");
+	if !output.status.success() {
+		// file doesn't exist at this commit
+		log::debug!("[get_file_at_commit] git show {} exited with {:?}", &object, output.status.code());
+		return None;
+	}
+	let content_res = str::from_utf8(&output.stdout);
+	if content_res.is_err() {
+		let e = content_res.expect_err("No error in content_res");
+		log::error!("[get_file_at_commit] Unable to parse {} as utf8: {:?}", &object, e);
+		return None;
+	}
+	let content = content_res.expect("Uncaught error in content_res");
+	Some(content.to_string())
+}
+
+// Hunks of `git diff -U0` per file, keyed by the path on the old side
+pub fn get_diff_hunks(from_commit: &str, to_commit: &str, directory: &str) -> Option<HashMap<String, Vec<DiffHunk>>> {
+	let output_res = Command::new("git")
+		.arg("diff")
+		.arg("-U0")
+		.arg("--no-renames")
+		.arg("--no-color")
+		.arg(from_commit)
+		.arg(to_commit)
+		.current_dir(directory)
+		.output();
+	if output_res.is_err() {
+		let e = output_res.expect_err("No error in output_res");
+		log::error!("[get_diff_hunks] git diff command failed to start: {:?}", e);
+		return None;
+	}
+	let output = output_res.expect("Uncaught error in output_res");
+	if !output.status.success() {
+		log::error!("[get_diff_hunks] git diff {}..{} exited with {:?}", from_commit, to_commit, output.status.code());
+		return None;
+	}
+	let diff_res = str::from_utf8(&output.stdout);
+	if diff_res.is_err() {
+		let e = diff_res.expect_err("No error in diff_res");
+		log::error!("[get_diff_hunks] Unable to parse git diff output as utf8: {:?}", e);
+		return None;
+	}
+	Some(parse_diff_hunks(diff_res.expect("Uncaught error in diff_res")))
+}
+
+fn parse_diff_hunks(diffstr: &str) -> HashMap<String, Vec<DiffHunk>> {
+	let mut hunkmap = HashMap::<String, Vec<DiffHunk>>::new();
+	let mut filepath_opt: Option<String> = None;
+	// removed lines can start with "---" too, file names are only read before the first hunk
+	let mut in_header = false;
+	for line in diffstr.lines() {
+		if line.starts_with("diff --git ") {
+			in_header = true;
+			filepath_opt = None;
+			continue;
+		}
+		if in_header {
+			if let Some(old_path) = line.strip_prefix("--- ") {
+				filepath_opt = old_path.strip_prefix("a/").map(str::to_string);
+				continue;
+			}
+			if let Some(new_path) = line.strip_prefix("+++ ") {
+				// added files only have a new side
+				if filepath_opt.is_none() {
+					filepath_opt = new_path.strip_prefix("b/").map(str::to_string);
+				}
+				continue;
+			}
+		}
+		if !line.starts_with("@@ ") {
+			continue;
+		}
+		in_header = false;
+		let hunk_opt = parse_hunk_header(line);
+		if hunk_opt.is_none() || filepath_opt.is_none() {
+			continue;
+		}
+		let filepath = filepath_opt.to_owned().expect("Empty filepath_opt");
+		hunkmap.entry(filepath).or_default().push(hunk_opt.expect("Empty hunk_opt"));
+	}
+	hunkmap
+}
+
+// "@@ -12,3 +12,4 @@", a missing count means one line
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+	let ranges: Vec<&str> = line.split_whitespace().skip(1).take(2).collect();
+	if ranges.len() != 2 {
+		return None;
+	}
+	let (old_start, old_count) = parse_hunk_range(ranges[0].strip_prefix('-')?)?;
+	let (new_start, new_count) = parse_hunk_range(ranges[1].strip_prefix('+')?)?;
+	Some(DiffHunk::new(old_start, old_count, new_start, new_count))
+}
+
+fn parse_hunk_range(range: &str) -> Option<(usize, usize)> {
+	let mut parts = range.splitn(2, ',');
+	let start = parts.next()?.parse::<usize>().ok()?;
+	let count = match parts.next() {
+		Some(count) => count.parse::<usize>().ok()?,
+		None => 1,
+	};
+	Some((start, count))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn numstat_history_sums_lines_per_file_and_author() {
+		let logstr = "aaa111\talice@example.com\n\
+			\n\
+			10\t2\tsrc/main.rs\n\
+			3\t0\tREADME.md\n\
+			bbb222\tbob@example.com\n\
+			\n\
+			1\t1\tsrc/main.rs\n\
+			ccc333\talice@example.com\n\
+			\n\
+			4\t4\tsrc/main.rs\n";
+		let filemap = parse_numstat_history(logstr);
+		assert_eq!(filemap.len(), 2);
+		let main_authors = filemap.get("src/main.rs").expect("Empty main_authors");
+		assert_eq!(main_authors.get("alice@example.com"), Some(&20));
+		assert_eq!(main_authors.get("bob@example.com"), Some(&2));
+		assert_eq!(filemap.get("README.md").and_then(|authors| authors.get("alice@example.com")), Some(&3));
+	}
+
+	#[test]
+	fn numstat_history_skips_binary_and_empty_changes() {
+		let logstr = "aaa111\talice@example.com\n-\t-\tlogo.png\n0\t0\tempty.txt\n2\t0\tdocs/a b.md\n";
+		let filemap = parse_numstat_history(logstr);
+		assert_eq!(filemap.len(), 1);
+		assert_eq!(filemap.get("docs/a b.md").and_then(|authors| authors.get("alice@example.com")), Some(&2));
+	}
+
+	#[test]
+	fn numstat_history_ignores_lines_before_the_first_commit() {
+		let filemap = parse_numstat_history("5\t5\torphan.rs\n");
+		assert!(filemap.is_empty());
+	}
+
+	#[test]
+	fn diff_hunks_are_keyed_by_old_path() {
+		let diffstr = "diff --git a/src/main.rs b/src/main.rs\n\
+			index 1111111..2222222 100644\n\
+			--- a/src/main.rs\n\
+			+++ b/src/main.rs\n\
+			@@ -3 +3 @@ fn main() {\n\
+			-    old();\n\
+			+    new();\n\
+			@@ -10,2 +9,0 @@\n\
+			--- removed comment\n\
+			-removed\n\
+			diff --git a/docs/new.md b/docs/new.md\n\
+			new file mode 100644\n\
+			--- /dev/null\n\
+			+++ b/docs/new.md\n\
+			@@ -0,0 +1,3 @@\n\
+			+a\n\
+			+b\n\
+			+c\n";
+		let hunkmap = parse_diff_hunks(diffstr);
+		assert_eq!(hunkmap.len(), 2);
+		assert_eq!(hunkmap.get("src/main.rs"), Some(&vec![DiffHunk::new(3, 1, 3, 1), DiffHunk::new(10, 2, 9, 0)]));
+		assert_eq!(hunkmap.get("docs/new.md"), Some(&vec![DiffHunk::new(0, 0, 1, 3)]));
+	}
+
+	#[test]
+	fn diff_hunk_spans_cover_insertions_and_deletions() {
+		assert_eq!(DiffHunk::new(10, 2, 9, 0).old_span(), (10, 11));
+		assert_eq!(DiffHunk::new(10, 2, 9, 0).new_span(), (9, 10));
+		assert_eq!(DiffHunk::new(5, 0, 6, 2).old_span(), (5, 6));
+		assert_eq!(DiffHunk::new(5, 0, 6, 2).new_span(), (6, 7));
+	}
+
+	#[test]
+	fn default_branch_is_read_from_origin_head() {
+		assert_eq!(remote_branch_name("origin/main\n"), Some("main".to_string()));
+		assert_eq!(remote_branch_name("origin/release/2.x"), Some("release/2.x".to_string()));
+		assert_eq!(remote_branch_name("upstream/main"), None);
+	}
+
+	#[test]
+	fn malformed_hunk_headers_are_skipped() {
+		assert_eq!(parse_hunk_header("@@ -a,1 +2 @@"), None);
+		assert_eq!(parse_hunk_header("@@"), None);
+		assert_eq!(parse_hunk_header("@@ -7,2 +8 @@"), Some(DiffHunk::new(7, 2, 8, 1)));
+	}
+}
@@ -59,6 +59,10 @@ impl BlameItem {
         &self.author
     }
 
+    pub fn filepath(&self) -> &String {
+        &self.filepath
+    }
+
     pub fn line_start(&self) -> &String {
         &self.line_start
     }
@@ -116,14 +120,6 @@ impl HunkMap {
     }
 
     // Public getter methods
-    pub fn repo_owner(&self) -> &String {
-        &self.repo_owner
-    }
-
-    pub fn repo_name(&self) -> &String {
-        &self.repo_name
-    }
-
     pub fn prhunkvec(&self) -> &Vec<PrHunkItem> {
         &self.prhunkvec
     }
@@ -16,4 +16,10 @@ pub mod setup_info;
 pub mod aliases;
 pub mod relevance;
 pub mod coverage;
-pub mod parsing;
\ No newline at end of file
+pub mod parsing;
+pub mod ownership;
+pub mod codeowners;
+pub mod approval;
+pub mod comment_template;
+pub mod gitlab_auth_info;
+pub mod rate_limit;
\ No newline at end of file
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Serialize, Default, Deserialize, Clone)]
+pub struct PathOwnership {
+    path: String,
+    is_dir: bool,
+    authors: HashMap<String, u64>,
+}
+
+impl PathOwnership {
+    // Constructor
+    pub fn new(path: String, is_dir: bool, authors: HashMap<String, u64>) -> Self {
+        Self {
+            path,
+            is_dir,
+            authors,
+        }
+    }
+
+    // Public getter methods
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    pub fn total_lines(&self) -> u64 {
+        self.authors.values().sum()
+    }
+
+    // Adds lines changed per author on top of the existing counts
+    pub fn merge_authors(&mut self, authors: &HashMap<String, u64>) {
+        for (author, lines) in authors {
+            *self.authors.entry(author.to_owned()).or_insert(0) += lines;
+        }
+    }
+
+    // Authors sorted by share of lines changed, highest first
+    pub fn ranked_owners(&self) -> Vec<(String, f32)> {
+        let total = self.total_lines();
+        let mut owners: Vec<(String, f32)> = Vec::new();
+        if total == 0 {
+            return owners;
+        }
+        for (author, lines) in &self.authors {
+            owners.push((author.to_owned(), *lines as f32 / total as f32 * 100.0));
+        }
+        owners.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+        owners
+    }
+}
+
+#[derive(Debug, Serialize, Default, Deserialize, Clone)]
+pub struct OwnershipMeta {
+    last_indexed_commit: String,
+    updated_at: u64,
+}
+
+impl OwnershipMeta {
+    // Constructor
+    pub fn new(last_indexed_commit: String, updated_at: u64) -> Self {
+        Self {
+            last_indexed_commit,
+            updated_at,
+        }
+    }
+
+    // Public getter methods
+    pub fn last_indexed_commit(&self) -> &String {
+        &self.last_indexed_commit
+    }
+}
@@ -9,6 +9,17 @@ pub struct PrInfo {
     pub author: Option<String>,
 }
 
+// One entry of a provider's PR listing, enough to decide what to do with the PR before fetching it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PrSummary {
+    pub number: String,
+    pub title: String,
+    pub draft: bool,
+    pub base_ref: String,
+    pub head_ref: String,
+    pub author: Option<String>,
+}
+
 impl PrInfo {
 
     // Public getter methods
@@ -0,0 +1,234 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::{header::{HeaderMap, RETRY_AFTER}, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+
+// Below this many remaining calls, requests are spread out over the time left until reset
+const LOW_REMAINING_CALLS: u64 = 50;
+// Longer waits are not worth blocking a review for, the call fails instead
+const MAX_WAIT_SECS: u64 = 300;
+const BACKOFF_BASE_SECS: u64 = 2;
+
+// Last known rate limit state per API host and credential
+static RATE_LIMITS: Mutex<Option<HashMap<LimitKey, RateLimitState>>> = Mutex::new(None);
+
+// Hosts count limits per installation or token, so each credential gets its own state
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LimitKey {
+    host: String,
+    credential: Option<String>,
+}
+
+impl LimitKey {
+    // Only a hash of the Authorization header is kept, enough to tell credentials apart
+    pub fn new(host: &str, authorization_opt: Option<&str>) -> Self {
+        let credential = authorization_opt.map(|authorization| {
+            let mut hasher = DefaultHasher::new();
+            authorization.hash(&mut hasher);
+            format!("{:016x}", hasher.finish())
+        });
+        Self {
+            host: host.to_string(),
+            credential,
+        }
+    }
+
+    pub fn host(&self) -> &String {
+        &self.host
+    }
+}
+
+#[derive(Debug, Serialize, Default, Deserialize, Clone)]
+pub struct RateLimitState {
+    host: String,
+    credential: Option<String>,
+    limit: Option<u64>,
+    remaining: Option<u64>,
+    reset_at: Option<u64>,
+    // Set by Retry-After or an exhausted limit, no calls go out before this
+    blocked_until: Option<u64>,
+    throttled_calls: u64,
+}
+
+pub fn rate_limit_status() -> Vec<RateLimitState> {
+    let limits_guard = RATE_LIMITS.lock();
+    if limits_guard.is_err() {
+        log::error!("[rate_limit_status] Unable to lock rate limit state");
+        return Vec::new();
+    }
+    let limits = limits_guard.expect("Uncaught error in limits_guard");
+    limits.as_ref()
+        .map(|state_map| state_map.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+// How long to hold a call so the credential's remaining budget lasts until reset
+pub fn throttle_delay(key: &LimitKey) -> Option<Duration> {
+    // nothing is known about a credential before its first response
+    let state = key_state(key)?;
+    let now = now_secs();
+    if let Some(blocked_until) = state.blocked_until {
+        if blocked_until > now {
+            return Some(Duration::from_secs((blocked_until - now).min(MAX_WAIT_SECS)));
+        }
+    }
+    let (remaining, reset_at) = match (state.remaining, state.reset_at) {
+        (Some(remaining), Some(reset_at)) => (remaining, reset_at),
+        _ => return None,
+    };
+    if remaining > LOW_REMAINING_CALLS || reset_at <= now {
+        return None;
+    }
+    let delay_ms = (reset_at - now) * 1000 / (remaining + 1);
+    Some(Duration::from_millis(delay_ms.min(MAX_WAIT_SECS * 1000)))
+}
+
+pub fn record_throttled(key: &LimitKey) {
+    update_key_state(key, |state| state.throttled_calls += 1);
+}
+
+// Reads X-RateLimit-* and Retry-After, returns the wait before a retry if the call was rate limited
+pub fn record_response(key: &LimitKey, response: &Response, attempt: u32) -> Option<Duration> {
+    let headers = response.headers();
+    let limit_opt = header_u64(headers, "x-ratelimit-limit");
+    let remaining_opt = header_u64(headers, "x-ratelimit-remaining");
+    let reset_opt = header_u64(headers, "x-ratelimit-reset");
+    let retry_after_opt = header_u64(headers, RETRY_AFTER.as_str());
+    let status = response.status();
+    let now = now_secs();
+    let wait_opt = rate_limited_wait(status, remaining_opt, reset_opt, retry_after_opt, attempt, now);
+    update_key_state(key, |state| {
+        if limit_opt.is_some() {
+            state.limit = limit_opt;
+        }
+        if remaining_opt.is_some() {
+            state.remaining = remaining_opt;
+        }
+        if reset_opt.is_some() {
+            state.reset_at = reset_opt;
+        }
+        if let Some(wait) = wait_opt {
+            state.blocked_until = Some(now + wait);
+        }
+    });
+    if wait_opt.is_some() {
+        log::warn!("[record_response] Rate limited by {}, status: {}, retry in {:?}s",
+            key.host(), status, wait_opt);
+    }
+    wait_opt.map(Duration::from_secs)
+}
+
+// Seconds to wait if the response is rate limited, Retry-After first, then the reset time, else exponential backoff
+fn rate_limited_wait(status: StatusCode, remaining_opt: Option<u64>, reset_opt: Option<u64>,
+    retry_after_opt: Option<u64>, attempt: u32, now: u64) -> Option<u64> {
+    // GitHub signals both primary and secondary limits with a 403
+    let is_limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN && (remaining_opt == Some(0) || retry_after_opt.is_some()));
+    if !is_limited {
+        return None;
+    }
+    if let Some(retry_after) = retry_after_opt {
+        return Some(retry_after);
+    }
+    if let (Some(0), Some(reset_at)) = (remaining_opt, reset_opt) {
+        return Some(reset_at.saturating_sub(now) + 1);
+    }
+    Some(BACKOFF_BASE_SECS.pow(attempt + 1))
+}
+
+pub fn max_wait() -> Duration {
+    Duration::from_secs(MAX_WAIT_SECS)
+}
+
+fn key_state(key: &LimitKey) -> Option<RateLimitState> {
+    let limits_guard = RATE_LIMITS.lock();
+    if limits_guard.is_err() {
+        log::error!("[key_state] Unable to lock rate limit state");
+        return None;
+    }
+    let limits = limits_guard.expect("Uncaught error in limits_guard");
+    limits.as_ref().and_then(|state_map| state_map.get(key).cloned())
+}
+
+fn update_key_state<F: FnOnce(&mut RateLimitState)>(key: &LimitKey, update: F) {
+    let limits_guard = RATE_LIMITS.lock();
+    if limits_guard.is_err() {
+        log::error!("[update_key_state] Unable to lock rate limit state");
+        return;
+    }
+    let mut limits = limits_guard.expect("Uncaught error in limits_guard");
+    let state = limits.get_or_insert_with(HashMap::new)
+        .entry(key.clone())
+        .or_insert_with(|| RateLimitState {
+            host: key.host.to_string(),
+            credential: key.credential.clone(),
+            ..Default::default()
+        });
+    update(state);
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .expect("Time went backwards").as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_wins_over_reset() {
+        let wait_opt = rate_limited_wait(StatusCode::TOO_MANY_REQUESTS, Some(0), Some(1_000), Some(7), 0, 900);
+        assert_eq!(wait_opt, Some(7));
+    }
+
+    #[test]
+    fn exhausted_limit_waits_until_reset() {
+        let wait_opt = rate_limited_wait(StatusCode::FORBIDDEN, Some(0), Some(1_000), None, 0, 900);
+        assert_eq!(wait_opt, Some(101));
+    }
+
+    #[test]
+    fn backoff_doubles_per_attempt_without_headers() {
+        let waits: Vec<Option<u64>> = (0..3)
+            .map(|attempt| rate_limited_wait(StatusCode::TOO_MANY_REQUESTS, None, None, None, attempt, 0))
+            .collect();
+        assert_eq!(waits, vec![Some(2), Some(4), Some(8)]);
+    }
+
+    #[test]
+    fn forbidden_without_limit_headers_is_not_limited() {
+        assert_eq!(rate_limited_wait(StatusCode::FORBIDDEN, Some(10), Some(1_000), None, 0, 900), None);
+        assert_eq!(rate_limited_wait(StatusCode::OK, Some(0), Some(1_000), None, 0, 900), None);
+    }
+
+    #[test]
+    fn credentials_on_one_host_are_limited_separately() {
+        let blocked_key = LimitKey::new("blocked.example.com", Some("Bearer installation-1"));
+        let other_key = LimitKey::new("blocked.example.com", Some("Bearer installation-2"));
+        update_key_state(&blocked_key, |state| state.blocked_until = Some(now_secs() + 60));
+        assert!(throttle_delay(&blocked_key).is_some());
+        assert_eq!(throttle_delay(&other_key), None);
+    }
+
+    #[test]
+    fn low_remaining_calls_are_spread_until_reset() {
+        let key = LimitKey::new("low.example.com", Some("Bearer token"));
+        update_key_state(&key, |state| {
+            state.remaining = Some(9);
+            state.reset_at = Some(now_secs() + 100);
+        });
+        let delay = throttle_delay(&key).expect("Empty delay");
+        assert!(delay <= Duration::from_secs(10) && delay >= Duration::from_secs(9));
+    }
+}
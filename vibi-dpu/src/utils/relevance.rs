@@ -1,6 +1,25 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RelevanceSource {
+    #[default]
+    Blame,
+    Ownership,
+    Codeowners,
+}
+
+impl RelevanceSource {
+    pub fn label(&self) -> &str {
+        match self {
+            RelevanceSource::Blame => "git blame",
+            RelevanceSource::Ownership => "ownership history",
+            RelevanceSource::Codeowners => "CODEOWNERS",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Default, Deserialize, Clone)]
 pub struct Relevance {
     provider: String,
@@ -8,6 +27,11 @@ pub struct Relevance {
     relevance_str: String,
     relevance_num: f32,
     handles: Option<Vec<String>>,
+    #[serde(default)]
+    source: RelevanceSource,
+    // Other aliases of the same person, counted into this relevance
+    #[serde(default)]
+    merged_aliases: Vec<String>,
 }
 
 impl Relevance {
@@ -18,6 +42,7 @@ impl Relevance {
         relevance_str: String,
         relevance_num: f32,
         handles: Option<Vec<String>>,
+        source: RelevanceSource,
     ) -> Self {
         Self {
             provider,
@@ -25,6 +50,8 @@ impl Relevance {
             relevance_str,
             relevance_num,
             handles,
+            source,
+            merged_aliases: Vec::new(),
         }
     }
 
@@ -48,4 +75,35 @@ impl Relevance {
     pub fn handles(&self) -> &Option<Vec<String>> {
         &self.handles
     }
+
+    pub fn source(&self) -> &RelevanceSource {
+        &self.source
+    }
+
+    pub fn aliases(&self) -> Vec<&String> {
+        let mut aliases = vec![&self.git_alias];
+        aliases.extend(self.merged_aliases.iter());
+        aliases
+    }
+
+    pub fn set_merged_aliases(&mut self, merged_aliases: Vec<String>) {
+        self.merged_aliases = merged_aliases;
+    }
+
+    pub fn set_relevance_num(&mut self, relevance_num: f32) {
+        self.relevance_num = relevance_num;
+        self.relevance_str = format!("{:.2}", relevance_num);
+    }
+}
+
+// Blame relevance of a github alias, with its handles if any were mapped
+#[cfg(test)]
+pub fn test_relevance(git_alias: &str, relevance_num: f32, handles: &[&str]) -> Relevance {
+    let handles_opt = if handles.is_empty() {
+        None
+    } else {
+        Some(handles.iter().map(|handle| handle.to_string()).collect())
+    };
+    Relevance::new("github".to_string(), git_alias.to_string(), format!("{:.2}", relevance_num),
+        relevance_num, handles_opt, RelevanceSource::Blame)
 }
@@ -1,9 +1,55 @@
 use serde::{Serialize, Deserialize};
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RelevanceMode {
+    #[default]
+    BlameOnly,
+    OwnersOnly,
+    Union,
+    // CODEOWNERS are always requested, blame ranks everyone else
+    OwnersRequired,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RepoConfig {
     comment: bool,
-    auto_assign: bool
+    auto_assign: bool,
+    #[serde(default)]
+    relevance_mode: RelevanceMode,
+    // handles or git emails never suggested as reviewers
+    #[serde(default)]
+    reviewer_denylist: Vec<String>,
+    #[serde(default)]
+    max_reviewers: Option<usize>,
+    // percentage below which people aren't auto assigned
+    #[serde(default)]
+    min_relevance: Option<f32>,
+    #[serde(default)]
+    load_balancing: bool,
+    #[serde(default = "default_max_open_reviews")]
+    max_open_reviews: u64,
+    // teams that can be requested instead of their members, `org/team` or a Bitbucket group slug
+    #[serde(default)]
+    team_reviewers: Vec<String>,
+    #[serde(default = "default_team_min_relevance")]
+    team_min_relevance: f32,
+    // percentage of relevance approvals need to cover, publishes a commit status when set
+    #[serde(default)]
+    coverage_gate: Option<f32>,
+    // markdown with `{{ variable }}` placeholders, falls back to the default comment
+    #[serde(default)]
+    relevance_comment_template: Option<String>,
+    #[serde(default)]
+    approval_comment_template: Option<String>,
+}
+
+fn default_max_open_reviews() -> u64 {
+    5
+}
+
+fn default_team_min_relevance() -> f32 {
+    50.0
 }
 
 impl RepoConfig {
@@ -16,11 +62,76 @@ impl RepoConfig {
         self.auto_assign
     }
 
+    pub fn relevance_mode(&self) -> &RelevanceMode {
+        &self.relevance_mode
+    }
+
+    pub fn reviewer_denylist(&self) -> &Vec<String> {
+        &self.reviewer_denylist
+    }
+
+    pub fn max_reviewers(&self) -> Option<usize> {
+        self.max_reviewers
+    }
+
+    pub fn min_relevance(&self) -> Option<f32> {
+        self.min_relevance
+    }
+
+    pub fn load_balancing(&self) -> bool {
+        self.load_balancing
+    }
+
+    pub fn max_open_reviews(&self) -> u64 {
+        self.max_open_reviews
+    }
+
+    pub fn team_reviewers(&self) -> &Vec<String> {
+        &self.team_reviewers
+    }
+
+    pub fn team_min_relevance(&self) -> f32 {
+        self.team_min_relevance
+    }
+
+    pub fn coverage_gate(&self) -> Option<f32> {
+        self.coverage_gate
+    }
+
+    pub fn relevance_comment_template(&self) -> &Option<String> {
+        &self.relevance_comment_template
+    }
+
+    pub fn approval_comment_template(&self) -> &Option<String> {
+        &self.approval_comment_template
+    }
+
     // Function to create a default RepoConfig
     pub fn default() -> Self {
         RepoConfig {
             comment: true,
-            auto_assign: true
+            auto_assign: true,
+            relevance_mode: RelevanceMode::default(),
+            reviewer_denylist: Vec::new(),
+            max_reviewers: None,
+            min_relevance: None,
+            load_balancing: false,
+            max_open_reviews: default_max_open_reviews(),
+            team_reviewers: Vec::new(),
+            team_min_relevance: default_team_min_relevance(),
+            coverage_gate: None,
+            relevance_comment_template: None,
+            approval_comment_template: None,
         }
     }
+}
+
+// Config with comments and auto assign on, overridden by the given fields
+#[cfg(test)]
+pub fn test_repo_config(overrides: serde_json::Value) -> RepoConfig {
+    let mut config = serde_json::json!({"comment": true, "auto_assign": true});
+    if let (Some(config_map), Some(overrides_map)) = (config.as_object_mut(), overrides.as_object()) {
+        config_map.extend(overrides_map.clone());
+    }
+    serde_json::from_value(config).expect("Unable to deserialize repo config")
 }
\ No newline at end of file
@@ -1,6 +1,10 @@
 use std::sync::Arc;
 use once_cell::sync::Lazy;
-use reqwest::Client;
+use reqwest::{header::AUTHORIZATION, Client, Method, RequestBuilder, Response};
+
+use crate::utils::rate_limit::{max_wait, record_response, record_throttled, throttle_delay, LimitKey};
+
+const MAX_RETRIES: u32 = 3;
 
 static CLIENT: Lazy<Arc<Client>> = Lazy::new(|| {
     Arc::new(Client::new())
@@ -8,4 +12,50 @@ static CLIENT: Lazy<Arc<Client>> = Lazy::new(|| {
 
 pub fn get_client() -> Arc<Client> {
     Arc::clone(&CLIENT)
+}
+
+// Sends an API request, slowing down when the rate limit of its host and credential runs low.
+// Idempotent requests that get rate limited are retried after the wait the host asks for.
+pub async fn send_request(request_builder: RequestBuilder) -> Result<Response, reqwest::Error> {
+    let request = request_builder.build()?;
+    let host = request.url().host_str().unwrap_or_default().to_string();
+    let authorization_opt = request.headers().get(AUTHORIZATION)
+        .and_then(|authorization| authorization.to_str().ok());
+    let limit_key = LimitKey::new(&host, authorization_opt);
+    let is_idempotent = matches!(*request.method(),
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS);
+    let client = get_client();
+    let mut attempt = 0;
+    loop {
+        if let Some(delay) = throttle_delay(&limit_key) {
+            log::debug!("[send_request] Throttling call to {} for {:?}", &host, delay);
+            record_throttled(&limit_key);
+            tokio::time::sleep(delay).await;
+        }
+        let retry_request_opt = if is_idempotent { request.try_clone() } else { None };
+        let current_request = match retry_request_opt {
+            Some(retry_request) if attempt < MAX_RETRIES => retry_request,
+            _ => return send_final(&client, request, &limit_key, attempt).await,
+        };
+        let response = client.execute(current_request).await?;
+        let wait_opt = record_response(&limit_key, &response, attempt);
+        if wait_opt.is_none() {
+            return Ok(response);
+        }
+        let wait = wait_opt.expect("Empty wait_opt");
+        if wait > max_wait() {
+            log::error!("[send_request] Rate limit on {} resets in {:?}, not retrying {}",
+                &host, wait, response.url());
+            return Ok(response);
+        }
+        attempt += 1;
+        log::info!("[send_request] Retrying {} in {:?}, attempt {}", response.url(), wait, attempt);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+async fn send_final(client: &Client, request: reqwest::Request, limit_key: &LimitKey, attempt: u32) -> Result<Response, reqwest::Error> {
+    let response = client.execute(request).await?;
+    record_response(limit_key, &response, attempt);
+    Ok(response)
 }
\ No newline at end of file
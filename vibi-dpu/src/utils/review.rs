@@ -15,6 +15,9 @@ pub struct Review {
     clone_dir: String,
     clone_url: String,
     author: String,
+    // Login or display name of the author, where `author` holds an id
+    #[serde(default)]
+    author_handle: Option<String>,
     relevance: Option<Vec<Relevance>>,
 }
 
@@ -44,6 +47,7 @@ impl Review {
             clone_dir,
             clone_url,
             author,
+            author_handle: None,
             relevance,
         }
     }
@@ -89,6 +93,10 @@ impl Review {
         &self.author
     }
 
+    pub fn author_handle(&self) -> &Option<String> {
+        &self.author_handle
+    }
+
     pub fn relevance(&self) -> &Option<Vec<Relevance>> {
         &self.relevance
     }
@@ -96,4 +104,16 @@ impl Review {
     pub fn set_relevance(&mut self, relevance: Option<Vec<Relevance>>) {
         self.relevance = relevance;
     }
+
+    pub fn set_author_handle(&mut self, author_handle: Option<String>) {
+        self.author_handle = author_handle;
+    }
+}
+
+// Review of a github PR with a clone dir that doesn't exist, for tests that need no git repo
+#[cfg(test)]
+pub fn test_review(pr_number: &str, pr_head_commit: &str) -> Review {
+    Review::new("base".to_string(), pr_head_commit.to_string(), pr_number.to_string(),
+        "repo".to_string(), "owner".to_string(), "github".to_string(), "db_key".to_string(),
+        "/nonexistent/clone_dir".to_string(), "clone_url".to_string(), "author".to_string(), None)
 }
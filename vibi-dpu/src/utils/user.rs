@@ -1,4 +1,5 @@
 use std::fmt;
+use std::str::FromStr;
 use std::collections::HashSet;
 
 use serde::{Deserialize, Serialize};
@@ -8,6 +9,9 @@ use serde::de::Deserializer;
 pub enum ProviderEnum {
     Bitbucket,
     Github,
+    Gitlab,
+    Gitea,
+    BitbucketServer,
 }
 
 impl fmt::Display for ProviderEnum {
@@ -15,6 +19,25 @@ impl fmt::Display for ProviderEnum {
         match *self {
             ProviderEnum::Bitbucket => write!(f, "bitbucket"),
             ProviderEnum::Github => write!(f, "github"),
+            ProviderEnum::Gitlab => write!(f, "gitlab"),
+            ProviderEnum::Gitea => write!(f, "gitea"),
+            ProviderEnum::BitbucketServer => write!(f, "bitbucket-server"),
+        }
+    }
+}
+
+// Inverse of Display, case-insensitive since some callers pass upper case env values
+impl FromStr for ProviderEnum {
+    type Err = String;
+
+    fn from_str(provider: &str) -> Result<Self, Self::Err> {
+        match provider.to_lowercase().as_str() {
+            "bitbucket" => Ok(ProviderEnum::Bitbucket),
+            "github" => Ok(ProviderEnum::Github),
+            "gitlab" => Ok(ProviderEnum::Gitlab),
+            "gitea" => Ok(ProviderEnum::Gitea),
+            "bitbucket-server" => Ok(ProviderEnum::BitbucketServer),
+            _ => Err(format!("Unknown repo provider: {}", provider)),
         }
     }
 }
@@ -69,6 +92,10 @@ impl BitbucketUser {
     pub fn display_name(&self) -> &String {
         &self.display_name
     }
+
+    pub fn nickname(&self) -> &String {
+        &self.nickname
+    }
 }
 
 #[derive(Deserialize)]
@@ -103,6 +130,10 @@ impl WorkspaceUser {
         &self.display_name
     }
 
+    pub fn users(&self) -> &HashSet<BitbucketUser> {
+        &self.users
+    }
+
     pub fn users_mut(&mut self) -> &mut HashSet<BitbucketUser> {
         &mut self.users
     }